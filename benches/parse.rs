@@ -0,0 +1,97 @@
+//! Simple timing benchmarks for the parse path.
+//!
+//! Run with `cargo bench`. These use `std::time::Instant` rather than an external harness so the
+//! crate keeps its small dependency tree.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use cl_parse::CommandLineDef;
+
+const ITERATIONS: u32 = 2_000;
+
+fn report(name: &str, elapsed: Duration, iterations: u32) {
+  println!("{name:<32} {:>10.0} ns/iter", elapsed.as_nanos() as f64 / iterations as f64);
+}
+
+/// Parses against a definition with many options so alias lookup dominates.
+fn bench_option_lookup() {
+  let names: Vec<&'static str> = (0..200)
+    .map(|i| &*Box::leak(format!("--option-{i:03}").into_boxed_str()))
+    .collect();
+  let mut def = CommandLineDef::new();
+  for name in &names {
+    def.add_option(vec![name], Some("value"), Some("0"), "A numbered option");
+  }
+  let mut args = vec!["program".to_string()];
+  for name in names.iter().step_by(4) {
+    args.push(name.to_string());
+    args.push("1".to_string());
+  }
+
+  let start = Instant::now();
+  for _ in 0..ITERATIONS {
+    black_box(def.parse(black_box(args.clone()).into_iter()));
+  }
+  report("option_lookup (200 defs)", start.elapsed(), ITERATIONS);
+}
+
+/// Defines many options with two aliases and a few valid values, the common shape, so the storage
+/// of the aliases and valid values dominates.
+fn bench_definition() {
+  const OPTIONS: usize = 1_000;
+  let leak = |name: String| -> &'static str { Box::leak(name.into_boxed_str()) };
+  let aliases: Vec<(&'static str, &'static str)> = (0..OPTIONS)
+    .map(|i| (leak(format!("--opt-{i:04}")), leak(format!("--option-{i:04}"))))
+    .collect();
+
+  let iterations = 200;
+  let start = Instant::now();
+  for _ in 0..iterations {
+    let mut def = CommandLineDef::new();
+    for (abbreviated, long) in &aliases {
+      def
+        .add_option(vec![abbreviated, long], Some("level"), Some("low"), "A numbered option")
+        .valid_values(long, vec!["low", "medium", "high"]);
+    }
+    black_box(&def);
+  }
+  report("definition (1k defs)", start.elapsed(), iterations);
+
+  let mut shared = CommandLineDef::new();
+  for (abbreviated, long) in &aliases {
+    shared
+      .add_option(vec![abbreviated, long], Some("level"), Some("low"), "A numbered option")
+      .valid_values(long, vec!["low", "medium", "high"]);
+  }
+  let start = Instant::now();
+  for _ in 0..iterations {
+    black_box(CommandLineDef::new().include(black_box(&shared)));
+  }
+  report("include (1k defs)", start.elapsed(), iterations);
+}
+
+/// Parses a very large number of positionals, as seen when a tool is invoked through xargs.
+fn bench_positionals() {
+  const ARGUMENTS: usize = 100_000;
+  let mut def = CommandLineDef::new();
+  def.add_flag(vec!["-v", "--verbose"], "Verbose output");
+  for i in 0..ARGUMENTS {
+    def.add_argument(Box::leak(format!("file-{i}").into_boxed_str()));
+  }
+  let mut args = Vec::with_capacity(ARGUMENTS + 2);
+  args.push("program".to_string());
+  args.push("-v".to_string());
+  args.extend((0..ARGUMENTS).map(|i| format!("path/to/file-{i}.txt")));
+
+  let iterations = 20;
+  let start = Instant::now();
+  for _ in 0..iterations {
+    black_box(def.parse(black_box(args.clone()).into_iter()));
+  }
+  report("positionals (100k args)", start.elapsed(), iterations);
+}
+
+fn main() {
+  bench_option_lookup();
+  bench_definition();
+  bench_positionals();
+}
@@ -1,18 +1,151 @@
-use std::cmp::max;
-use std::collections::HashMap;
-use crate::text::T;
-use super::option_def::OptionDef;
-use super::{FALSE, format_usage, LONG_HELP, LONG_OPTION, panic_msg, SHORT_HELP, SHORT_OPTION, TRUE};
-use super::command_line::CommandLine;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::max;
+use core::iter::{once, Peekable};
+use core::ops::{Range, RangeInclusive};
+use crate::text::{message, T};
+use super::option_def::{OptionDef, ValidValues};
+use super::{COLON_VALUE_SEPARATOR, FALSE, leak, LONG_HELP, LONG_OPTION, LONG_QUIET, LONG_VERBOSE, LONG_VERSION, panic_msg, PLUS_OPTION, SHORT_HELP,
+            SHORT_OPTION, SLASH_HELP, SLASH_OPTION, VALUE_DELIMITER, VALUE_SEPARATOR, SHORT_QUIET, SHORT_VERBOSE, SHORT_VERSION, TRUE};
+use super::command_line::{CommandLine, Options, Values};
+use super::help_layout::{HelpLayout, ValidValuesPosition};
+use super::hyperlink::{hyperlink, Stream};
+#[cfg(feature = "std")]
+use super::error_behavior::{ErrorBehavior, ExitHandler};
+#[cfg(feature = "std")]
+use super::output::OutputWriter;
+use super::compiled_def::{Precomputed, PROGRAM_PLACEHOLDER};
+use super::def_error::{DefError, DefErrorKind};
+use super::parse_error::{ErrorKind, ParseError};
+use super::source::Source;
+use super::subcommand::Subcommand;
+use super::warning::{Warning, WarningKind};
+use super::shell::split_posix;
+use super::environment::Environment;
+#[cfg(feature = "dotenv")]
+use std::path::PathBuf;
+
+/// The default .env file
+#[cfg(feature = "dotenv")]
+const DOTENV: &str = ".env";
+
+/// Receives the warnings found while parsing
+pub(crate) type WarningSink = Arc<dyn Fn(&Warning) + Send + Sync>;
+
+/// Rewrites the args before they are parsed
+pub(crate) type Preprocessor = Arc<dyn Fn(Vec<String>) -> Vec<String> + Send + Sync>;
+
+/// Stores values derived from the parsed ones
+pub(crate) type Finalizer = Arc<dyn Fn(&mut CommandLine) + Send + Sync>;
+
+/// Produces the version text from the program name
+pub(crate) type VersionText = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// The args assigned to the arguments, the name of the argument of each, and the indexes of the
+/// args of the variadic argument
+type AssignedArguments = (Vec<String>, Vec<&'static str>, Range<usize>);
+
+/// The OptionDefs found in an arg, with the alias each was given with
+type Found<'a> = Vec<(&'a OptionDef, String)>;
 
 /// Defines the valid commandline options and arguments for this program
+///
+/// A CommandLineDef is `Send + Sync` and parsing only borrows it, so a single definition can be
+/// shared by many threads parsing commandlines at the same time without any locking.
 pub struct CommandLineDef {
   /// The list of option_defs added
   pub(crate) option_defs: Vec<OptionDef>,
-  /// Maps the individual aliases of the OptionDef to the OptionDef.
-  pub(crate) option_def_map:HashMap<&'static str, usize>,
+  /// Maps the individual aliases to the index of their OptionDef. Kept sorted by alias so lookups
+  /// are a binary search with no hashing or allocation.
+  pub(crate) alias_table:Vec<(&'static str, usize)>,
   /// Descriptive names for each of the arguments. e.g. file_path
   pub(crate) argument_names:Vec<&'static str>,
+  /// The index of the variadic argument in `argument_names`, if there is one
+  pub(crate) variadic:Option<usize>,
+  /// The arguments that can be given with an option instead, with the index of the argument in
+  /// `argument_names` and the first alias of the option
+  pub(crate) alternatives:Vec<(usize, &'static str)>,
+  /// Receives the warnings found while parsing. If `None`, warnings are written to stderr.
+  pub(crate) warning_sink:Option<WarningSink>,
+  /// Produces the text displayed by the version option, if there is one
+  pub(crate) version_text:Option<VersionText>,
+  /// The environment variable holding extra args to parse before the commandline args
+  pub(crate) args_env_var:Option<&'static str>,
+  /// The .env file loaded before the environment variables are read, if any
+  #[cfg(feature = "dotenv")]
+  pub(crate) dotenv_path:Option<PathBuf>,
+  /// The config values, by option key
+  pub(crate) config:BTreeMap<String, String>,
+  /// The sources of option values, from highest to lowest precedence
+  pub(crate) precedence:Vec<Source>,
+  /// True if errors in the definition are kept for `build`, instead of panicking
+  pub(crate) defer_errors:bool,
+  /// The first error in the definition, kept for `build`
+  pub(crate) def_error:Option<DefError>,
+  /// The usage message and environment computed once by `build`, as the definition no longer changes
+  pub(crate) precomputed:Option<Precomputed>,
+  /// The subcommands, in the order they were added
+  pub(crate) subcommands:Vec<Subcommand>,
+  /// The canonical name of the subcommand run when the args do not name one
+  pub(crate) default_subcommand:Option<&'static str>,
+  /// True if a subcommand can be named by an unambiguous prefix of one of its names
+  pub(crate) infer_subcommands:bool,
+  /// The examples shown in the help, as the commandline args after the program name and a
+  /// description
+  pub(crate) examples:Vec<(&'static str, &'static str)>,
+  /// The layout of the help, with placeholders for its sections. If `None`, the sections follow
+  /// the usage line.
+  pub(crate) help_template:Option<&'static str>,
+  /// The layout of the option and subcommand lines of the help
+  pub(crate) help_layout:HelpLayout,
+  /// The environment variable choosing the plain layout for the help, if any
+  pub(crate) plain_help_env_var:Option<&'static str>,
+  /// True if parse errors are displayed with a line pointing to the help instead of the usage
+  pub(crate) concise_errors:bool,
+  /// True if parse errors are displayed as JSON, for programs that read them
+  pub(crate) json_errors:bool,
+  /// What `parse` does with a ParseError
+  #[cfg(feature = "std")]
+  pub(crate) error_behavior:ErrorBehavior,
+  /// Called with the exit code in place of `std::process::exit`, if set
+  #[cfg(feature = "std")]
+  pub(crate) exit_handler:Option<ExitHandler>,
+  /// The writers that receive the output in place of stdout and stderr, if set
+  #[cfg(feature = "std")]
+  pub(crate) output:Option<(OutputWriter, OutputWriter)>,
+  /// True if `/?` requests help, as it does for Windows tools
+  pub(crate) slash_help:bool,
+  /// True if option values can be attached with ':', and options can start with '/'
+  pub(crate) colon_separator:bool,
+  /// True if args that are negative numbers, and not options, are arguments
+  pub(crate) negative_numbers:bool,
+  /// True if a long option can be given by an unambiguous prefix, such as `--verb` for `--verbose`
+  pub(crate) infer_long_options:bool,
+  /// True if wildcards in arguments are expanded to the matching paths
+  #[cfg(feature = "glob")]
+  pub(crate) expand_wildcards:bool,
+  /// Rewrites the args before they are parsed, if set
+  pub(crate) preprocessor:Option<Preprocessor>,
+  /// Stores values derived from the parsed ones, if set
+  pub(crate) finalizer:Option<Finalizer>,
+}
+
+// fails to compile if a CommandLineDef can no longer be shared between threads
+const _: () = {
+  const fn assert_send_sync<T: Send + Sync>() {}
+  assert_send_sync::<CommandLineDef>();
+};
+
+impl Default for CommandLineDef {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
 }
 
 impl CommandLineDef {
@@ -21,10 +154,112 @@ impl CommandLineDef {
   pub fn new() -> Self {
     let mut cl_def = CommandLineDef {
       option_defs:Vec::default(),
-      option_def_map:HashMap::default(),
+      alias_table:Vec::default(),
       argument_names:Vec::default(),
+      variadic:None,
+      alternatives:Vec::default(),
+      warning_sink:None,
+      version_text:None,
+      args_env_var:None,
+      #[cfg(feature = "dotenv")]
+      dotenv_path:Some(PathBuf::from(DOTENV)),
+      config:BTreeMap::default(),
+      precedence:Source::PRECEDENCE.to_vec(),
+      defer_errors:false,
+      def_error:None,
+      precomputed:None,
+      subcommands:Vec::default(),
+      default_subcommand:None,
+      infer_subcommands:false,
+      examples:Vec::default(),
+      help_template:None,
+      help_layout:HelpLayout::default(),
+      plain_help_env_var:None,
+      concise_errors:false,
+      json_errors:false,
+      #[cfg(feature = "std")]
+      error_behavior:ErrorBehavior::default(),
+      #[cfg(feature = "std")]
+      exit_handler:None,
+      #[cfg(feature = "std")]
+      output:None,
+      slash_help:cfg!(windows),
+      colon_separator:false,
+      negative_numbers:true,
+      infer_long_options:false,
+      preprocessor:None,
+      finalizer:None,
+      #[cfg(feature = "glob")]
+      expand_wildcards:false,
     };
-    cl_def.add_option(vec!["-h", "--help"], None, None, "Display usage message");
+    cl_def.help_option(vec![SHORT_HELP, LONG_HELP]);
+    cl_def
+  }
+
+  /// Creates a new CommandLineDef from a classic getopt option string. e.g. `"ab:c::"`
+  ///
+  /// Each character defines a short option. A character followed by `:` is a required option that
+  /// takes a value, either attached or as the next argument. One followed by `::` is an option with
+  /// an optional value, which like getopt is only taken when it is attached, such as `-cvalue`. It
+  /// is empty when the option is given alone, or not specified. The flag `h` is provided by the
+  /// built-in help option. The leading `+`, `-` and `:` getopt mode characters are ignored.
+  ///
+  /// # Arguments
+  ///
+  /// * `optstring` - The getopt option string
+  ///
+  /// # Panics
+  ///
+  /// * Panics if a `:` does not follow an option character
+  /// * Panics if an option character is defined more than once
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec![
+  ///   "program".to_string(),
+  ///   "-a".to_string(),
+  ///   "-b".to_string(), "value".to_string(),
+  ///   "-c".to_string(), "file".to_string(),
+  /// ];
+  /// let cl = CommandLineDef::from_optstring("ab:c::").add_argument("file").parse(args.into_iter());
+  ///
+  /// let a:bool = cl.option("-a");
+  /// assert_eq!(a, true);
+  ///
+  /// let b:String = cl.option("-b");
+  /// assert_eq!(b, "value");
+  ///
+  /// let c:String = cl.option("-c");
+  /// assert_eq!(c, "");
+  /// assert_eq!(cl.argument::<String>(0), "file");
+  /// ```
+  pub fn from_optstring(optstring:&str) -> Self {
+    let mut cl_def = CommandLineDef::new();
+    let mut chars = optstring.trim_start_matches(['+', '-']).trim_start_matches(':').chars().peekable();
+    while let Some(c) = chars.next() {
+      if c == ':' {
+        panic_msg(T.optstring_invalid(optstring));
+      }
+      let mut colons = 0;
+      while chars.next_if_eq(&':').is_some() {
+        colons += 1;
+      }
+      if colons > 2 {
+        panic_msg(T.optstring_invalid(optstring));
+      }
+      let alias = leak(format!("-{c}"));
+      if colons == 0 {
+        if alias != SHORT_HELP {
+          cl_def.add_flag(vec![alias], "");
+        }
+      } else if colons == 1 {
+        cl_def.add_option(vec![alias], Some("value"), None, "").allow_empty_values(alias);
+      } else {
+        cl_def.add_option(vec![alias], Some("value"), Some(""), "").optional_value(alias);
+      }
+    }
     cl_def
   }
 
@@ -79,16 +314,243 @@ impl CommandLineDef {
     self.add_option(aliases, None, None, description)
   }
 
+  /// Adds a flag that runs an action as soon as it is parsed, such as printing a version or license
+  /// and exiting. The action is called with this definition and the program name. If the action
+  /// returns, parsing continues and the flag is set like any other flag.
+  ///
+  /// # Arguments
+  ///
+  /// * `aliases` - The aliases for this option. e.g. `"-L","--license"`
+  /// * `description` - The description of this option. e.g. `Display the license`.
+  /// * `action` - Called when the option is parsed
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the alias does not start with '-' or '--'.
+  /// * Panics if the alias starts with '--' and the length is less than 4
+  /// * Panics if the alias starts with '-' and the length is not equal to 2
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use std::sync::atomic::{AtomicBool, Ordering};
+  /// use cl_parse::CommandLineDef;
+  /// static LICENSE_SHOWN:AtomicBool = AtomicBool::new(false);
+  ///
+  /// let args=vec!["program".to_string(), "--license".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_action(vec!["-L","--license"], "Display the license", |_cl_def, program_name| {
+  ///     println!("{program_name} is licensed under the MIT license");
+  ///     LICENSE_SHOWN.store(true, Ordering::SeqCst);
+  ///   })
+  ///   .parse(args.into_iter());
+  ///
+  /// assert!(LICENSE_SHOWN.load(Ordering::SeqCst));
+  /// let license:bool = cl.option("-L");
+  /// assert_eq!(license, true);
+  /// ```
+  #[inline]
+  pub fn add_action(&mut self, aliases:Vec<&'static str>, description:&'static str,
+                    action:impl Fn(&CommandLineDef, &str) + Send + Sync + 'static) -> &mut Self {
+    let count = self.option_defs.len();
+    self.add_flag(aliases, description);
+    if let Some(od) = self.option_defs.get_mut(count) {
+      od.action = Some(Arc::new(action));
+    }
+    self
+  }
+
+  /// Adds a toggle, a flag that is set with its short alias, such as `-x`, and cleared with the
+  /// alias prefixed by `+` instead, such as `+x`, like the options of the shell's `set`. Toggles can
+  /// be grouped with either prefix, e.g. `+xv`. The toggle is false if it is not specified. Use
+  /// `plus_enables` to make `+` set the toggle instead.
+  ///
+  /// # Arguments
+  ///
+  /// * `aliases` - The aliases for this option, including at least one short alias. e.g. `"-x","--xtrace"`
+  /// * `description` - The description of this option. e.g. `Print the commands as they are run`.
+  ///
+  /// # Panics
+  ///
+  /// * Panics if none of the aliases is a short alias
+  /// * Panics if the aliases are invalid, as for `add_flag`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "-x".to_string(), "+e".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_toggle(vec!["-x","--xtrace"], "Print the commands as they are run")
+  ///   .add_toggle(vec!["-e"], "Exit on the first error")
+  ///   .add_toggle(vec!["-u"], "Treat unset variables as an error")
+  ///   .parse(args.into_iter());
+  ///
+  /// assert_eq!(cl.option::<bool>("--xtrace"), true);
+  /// assert_eq!(cl.option::<bool>("-e"), false);
+  /// assert_eq!(cl.option::<bool>("-u"), false);
+  /// ```
+  #[inline]
+  pub fn add_toggle(&mut self, aliases:Vec<&'static str>, description:&'static str) -> &mut Self {
+    if aliases.iter().all(|alias| alias.starts_with(LONG_OPTION)) {
+      self.def_failed(DefErrorKind::InvalidOption, T.option_invalid_toggle(aliases.first().copied().unwrap_or_default()));
+      return self;
+    }
+    let count = self.option_defs.len();
+    self.add_flag(aliases, description);
+    if let Some(od) = self.option_defs.get_mut(count) {
+      od.toggle = Some(false);
+    }
+    self
+  }
+
+  /// Makes the `+` prefix set a toggle and the `-` prefix clear it, e.g. `+o` enables the toggle.
+  /// The toggle is still false if it is not specified.
+  ///
+  /// # Arguments
+  ///
+  /// * `alias` - Any alias of the toggle
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined, or is not a toggle
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "+o".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_toggle(vec!["-o"], "Enable the optimizations")
+  ///   .plus_enables("-o")
+  ///   .parse(args.into_iter());
+  ///
+  /// assert_eq!(cl.option::<bool>("-o"), true);
+  /// ```
+  #[inline]
+  pub fn plus_enables(&mut self, alias:&'static str) -> &mut Self {
+    match self.option_def_mut(alias) {
+      Some(od) if od.toggle.is_some() => od.toggle = Some(true),
+      Some(_) => self.def_failed(DefErrorKind::InvalidOption, T.option_invalid_toggle(alias)),
+      None => {},
+    }
+    self
+  }
+
+  /// Adds a pair of flags, `--enable-<name>` and `--disable-<name>`, that set and clear one
+  /// boolean, shown as a single help entry. Either alias reads the boolean, and the last of the
+  /// pair given on the commandline wins.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name after the `--enable-` and `--disable-` prefixes. e.g. `cache`
+  /// * `enabled` - The value of the boolean if neither flag is specified
+  /// * `description` - The description of this option. e.g. `Cache the results`.
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the aliases are invalid, or are already defined, as for `add_flag`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=["program", "--disable-cache", "--enable-cache", "--disable-color"].map(String::from);
+  /// let cl = CommandLineDef::new()
+  ///   .add_enable_disable("cache", false, "Cache the results")
+  ///   .add_enable_disable("color", true, "Color the output")
+  ///   .add_enable_disable("progress", true, "Show the progress")
+  ///   .parse(args.into_iter());
+  ///
+  /// assert_eq!(cl.option::<bool>("--enable-cache"), true);
+  /// assert_eq!(cl.option::<bool>("--enable-color"), false);
+  /// assert_eq!(cl.option::<bool>("--disable-progress"), true);
+  /// ```
+  pub fn add_enable_disable(&mut self, name:&'static str, enabled:bool, description:&'static str) -> &mut Self {
+    let disable = leak(format!("{LONG_OPTION}disable-{name}"));
+    let count = self.option_defs.len();
+    self.add_flag(vec![leak(format!("{LONG_OPTION}enable-{name}")), disable], description);
+    if let Some(od) = self.option_defs.get_mut(count) {
+      od.aliases.sort_by_key(|alias| *alias == disable);
+      od.disable = Some(disable);
+      od.default_value = Some(if enabled { TRUE } else { FALSE });
+    }
+    self
+  }
+
+  /// Adds a flag that counts how many times it is specified, e.g. `-vvv`. Its value is the number
+  /// of occurrences, and 0 if it is not specified.
+  ///
+  /// # Arguments
+  ///
+  /// * `aliases` - The aliases for this option. e.g. `"-d","--debug"`
+  /// * `description` - The description of this option. e.g. `Increase the debug level`.
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the alias does not start with '-' or '--'.
+  /// * Panics if the alias starts with '--' and the length is less than 4
+  /// * Panics if the alias starts with '-' and the length is not equal to 2
+  /// * Panics if an alias is defined more than once
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "-dd".to_string(), "--debug".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_counter(vec!["-d","--debug"], "Increase the debug level")
+  ///   .add_counter(vec!["-t"], "Increase the trace level")
+  ///   .parse(args.into_iter());
+  ///
+  /// let debug:u32 = cl.option("--debug");
+  /// assert_eq!(debug, 3);
+  ///
+  /// let trace:u32 = cl.option("-t");
+  /// assert_eq!(trace, 0);
+  /// ```
+  #[inline]
+  pub fn add_counter(&mut self, aliases:Vec<&'static str>, description:&'static str) -> &mut Self {
+    let count = self.option_defs.len();
+    self.add_flag(aliases, description);
+    if let Some(od) = self.option_defs.get_mut(count) {
+      od.counting = true;
+      od.default_value = Some("0");
+    }
+    self
+  }
+
+  /// Adds the standard `-v`, `--verbose` and `-q`, `--quiet` counters, so the verbosity of every
+  /// program is set the same way. Use [`CommandLine::verbosity`] to get the verbosity level.
+  ///
+  /// # Panics
+  ///
+  /// * Panics if any of the aliases is already defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "-vv".to_string()];
+  /// let cl = CommandLineDef::new().add_verbosity().parse(args.into_iter());
+  /// assert_eq!(cl.verbosity(), 2);
+  /// ```
+  #[inline]
+  pub fn add_verbosity(&mut self) -> &mut Self {
+    self.add_counter(vec![SHORT_VERBOSE, LONG_VERBOSE], T.verbose_description())
+      .add_counter(vec![SHORT_QUIET, LONG_QUIET], T.quiet_description())
+  }
+
   /// Adds a new option definition to this commandline definition
   ///
   /// # Arguments
   ///
   /// * `aliases` - The aliases for this option. e.g. `"-n","--negative"`
   /// * `value_name` - The `Option<&'static str>` name for the value associated with the option.
-  /// If set to `None`, this option will be treated as a flag, and its value will default to "false".
+  ///   If set to `None`, this option will be treated as a flag, and its value will default to "false".
   /// * `default_value` - An `Option<T>` containing the value to use if one is not supplied. If `None`,
-  /// then this option will be considered required and will panic if this option is not specified on
-  /// the commandline. If `value_name`==`None`, `default_value` will be ignored.
+  ///   then this option will be considered required and will panic if this option is not specified on
+  ///   the commandline. If `value_name`==`None`, `default_value` will be ignored.
   /// * `description` - The description of this option. e.g. `A negative number`.
   ///
   /// # Panics
@@ -129,13 +591,56 @@ impl CommandLineDef {
   #[inline]
   pub fn add_option(&mut self, mut aliases:Vec<&'static str>, value_name:Option<&'static str>, default_value:Option<&'static str>, description:&'static str) -> &mut Self {
     let default = if value_name.is_some() { default_value } else { Some(FALSE) };
+    if let Err(message) = OptionDef::validate_aliases(&aliases) {
+      self.def_failed(DefErrorKind::InvalidName, message);
+      return self;
+    }
     aliases.sort_by(|a,b| a.trim_start_matches(SHORT_OPTION).cmp(b.trim_start_matches(SHORT_OPTION)));
-    self.option_defs.push(OptionDef::new(aliases, value_name, default, description));
-    let od_idx = self.option_defs.len()-1;
-    for alias in &self.option_defs[od_idx].aliases {
-      if self.option_def_map.insert(alias, od_idx).is_some() {
-        panic_msg(T.option_redefined(alias));
-      }
+    self.push_option_def(OptionDef::new(aliases, value_name, default, description));
+    self
+  }
+
+  /// Adds the options of another definition, so a set of options defined once can be shared by
+  /// several subcommands. The help option and the arguments of the other definition are not
+  /// included.
+  ///
+  /// # Arguments
+  ///
+  /// * `options` - The definition with the options to include
+  ///
+  /// # Panics
+  ///
+  /// * Panics if an alias is defined more than once
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let mut output_options = CommandLineDef::new();
+  /// output_options
+  ///   .add_option(vec!["-o","--output"], Some("dir"), Some("target"), "The output directory")
+  ///   .add_flag(vec!["--release"], "Build with optimizations");
+  ///
+  /// let mut build = CommandLineDef::new();
+  /// build.include(&output_options);
+  /// let mut test = CommandLineDef::new();
+  /// test.include(&output_options).add_flag(vec!["--no-run"], "Compile, but do not run the tests");
+  ///
+  /// let args=vec!["cargo".to_string(), "test".to_string(), "--release".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_subcommand(vec!["build"], "Compile the package", build)
+  ///   .add_subcommand(vec!["test"], "Run the tests", test)
+  ///   .parse(args.into_iter());
+  ///
+  /// let (_, test) = cl.subcommand().unwrap();
+  /// let release:bool = test.option("--release");
+  /// assert_eq!(release, true);
+  /// let output:String = test.option("-o");
+  /// assert_eq!(output, "target");
+  /// ```
+  pub fn include(&mut self, options:&CommandLineDef) -> &mut Self {
+    for od in options.option_defs.iter().filter(|od| !od.help) {
+      self.push_option_def(od.clone());
     }
     self
   }
@@ -190,177 +695,2070 @@ impl CommandLineDef {
     self
   }
 
-  /// Creates a new CommandLine from this CommandLineDef and the args
+  /// Adds a variadic argument, which takes any number of args, including none. Arguments can be
+  /// defined before and after it, such as `cp` with `[<src>...] <dest>`, and the args are assigned
+  /// to the arguments after the variadic argument first. The args of the variadic argument are
+  /// retrieved with `CommandLine::variadic`, and each arg is still an argument of the
+  /// CommandLine, so the last argument is `cl.argument(cl.arguments() - 1)`.
   ///
   /// # Arguments
   ///
-  /// * `args` - A string iterator that holds the commandline arguments to be parsed
+  /// * `argument_name` - The name of the argument. e.g. `src`
   ///
-  /// * Panics if an option is specified and its value is missing
-  /// * Panics if an undefined option is present on the commandline
-  /// * Panics if a required option is not present on the commandline
-  /// * Panics if number of arguments is incorrect
+  /// # Panics
+  ///
+  /// * Panics if a variadic argument has already been added
+  /// * Panics if the definition has subcommands
   ///
   /// # Examples
   ///
   /// ```
-  ///  use std::collections::VecDeque;
-  ///  use std::env;
-  ///  // Simulate env::args()
-  ///  let env_args=vec![String::from("program"), String::from("-f"), String::from("/file/path")];
-  ///  use cl_parse::{CommandLine, CommandLineDef};
-  ///  let cl = CommandLineDef::new().add_option(vec!["-f","--filename"], Some("filepath"),
-  ///      None, "The file to be parsed").parse(env_args.into_iter());
+  /// use cl_parse::CommandLineDef;
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.disable_help().add_variadic_argument("src").add_argument("dest");
+  /// assert_eq!(cl_def.usage("cp"), "Usage: cp [<src>...] <dest>");
   ///
-  ///   // Test Program Name
-  ///   assert_eq!(false, cl.program_name().is_empty());
+  /// let args=vec!["cp".to_string(), "a.txt".to_string(), "b.txt".to_string(), "backup".to_string()];
+  /// let cl = cl_def.parse(args.into_iter());
+  /// let sources:Vec<String> = cl.variadic();
+  /// assert_eq!(sources, vec!["a.txt", "b.txt"]);
+  /// let dest:String = cl.argument(cl.arguments() - 1);
+  /// assert_eq!(dest, "backup");
   /// ```
-  pub fn parse(&self, args: impl Iterator<Item=String>) -> CommandLine {
-    let mut options:HashMap<String, String> = HashMap::default();
-    let mut arguments:Vec<String> = Vec::default();
-
-    // make the iterator peekable so we can see the next one
-    let mut peekable_args = args.peekable();
-
-    let program_name = peekable_args.next().unwrap_or_else(String::default);
-    let usage = self.usage(&program_name);
-    let mut skip_next = false;
-
-    while let Some(arg) = peekable_args.next() {
-      if arg == SHORT_HELP || arg == LONG_HELP {
-        panic!("{}", usage);
-      }
-      if !skip_next {
-        skip_next = if arg.starts_with(SHORT_OPTION) {
-          self.parse_option(arg, peekable_args.peek(), &usage, &mut options)
-        } else {
-          arguments.push(arg);
-          false
-        }
-      } else {
-        skip_next = false;
-      }
+  pub fn add_variadic_argument(&mut self, argument_name:&'static str) -> &mut Self {
+    if self.variadic.is_some() {
+      self.def_failed(DefErrorKind::Redefined, T.argument_variadic_redefined(argument_name));
+      return self;
     }
-    // make sure we got the defined number of arguments
-    if arguments.len() != self.argument_names.len() {
-      panic_msg(format_usage(
-        &T.argument_defined_ne_found(self.argument_names.len(), arguments.len()),
-        &usage));
+    if !self.subcommands.is_empty() {
+      self.def_failed(DefErrorKind::Conflict, T.argument_variadic_subcommand());
+      return self;
     }
-    self.add_default_options(&mut options, &usage);
-    CommandLine::new(program_name, options, arguments)
+    self.variadic = Some(self.argument_names.len());
+    self.add_argument(argument_name)
   }
 
-  #[inline]
-  fn usage(&self, program_name:&str) -> String {
-    let mut flags: Vec<char> = Vec::default();
-    let mut options: Vec<String> = Vec::default();
-    let mut requireds: Vec<String> = Vec::default();
-    let mut help_lines: Vec<(String, String)> = Vec::default();
-    let mut max_len = 0;
-
-    for od in &self.option_defs {
-      let mut help_options = od.aliases.join(", ");
-      if od.value_name.is_some() {
-        let value_name = od.value_name.unwrap();
-        help_options = format!("{} <{}>", help_options, value_name);
-        if od.default_value.is_none() {
-          requireds.push(format!("{} <{}>",od.aliases[0],value_name));
-        } else {
-          options.push(format!("[{} <{}>]",od.aliases[0],value_name));
-        }
-      } else if od.aliases[0].starts_with(LONG_OPTION) {
-        options.push(format!("{}",od.aliases[0]))
-      } else {
-        flags.push(od.aliases[0].chars().last().unwrap())
-      }
-      max_len = max(max_len, help_options.len());
-      help_lines.push((help_options, od.description.to_string()));
-    }
-
-    let mut usage = T.usage(program_name);
-
-    if !flags.is_empty() {
-      flags.sort();
-      usage.push_str(&format!(" [-{}]", flags.iter().fold(String::default(),|acc, c |{acc + &c.to_string()})));
+  /// Adds an input that is given either with an option or as an argument, but not both, such as
+  /// `-f <file>` or `<file>`. The value is retrieved with `CommandLine::option`, whichever way it
+  /// was given, and is not one of the CommandLine's arguments. The argument is defined at this
+  /// position among the arguments. Giving neither is an [`ErrorKind::WrongArgumentCount`], and
+  /// giving both is an [`ErrorKind::MultipleOccurrences`].
+  ///
+  /// # Arguments
+  ///
+  /// * `aliases` - The aliases of the option. e.g. `-f`, `--file`
+  /// * `value_name` - The name of the option's value, which is also the name of the argument
+  /// * `description` - The description of the option
+  ///
+  /// # Panics
+  ///
+  /// * Panics if there are no aliases, an alias is not valid, or has already been defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.disable_help().add_option_or_argument(vec!["-f","--file"], "file", "The file to read");
+  /// assert_eq!(cl_def.usage("program"), "Usage: program (-f <file> | <file>)\n-f, --file <file> : The file to read");
+  ///
+  /// let args=vec!["program".to_string(), "notes.txt".to_string()];
+  /// let file:String = cl_def.parse(args.into_iter()).option("--file");
+  /// assert_eq!(file, "notes.txt");
+  ///
+  /// let args=vec!["program".to_string(), "-f".to_string(), "notes.txt".to_string()];
+  /// let file:String = cl_def.parse(args.into_iter()).option("--file");
+  /// assert_eq!(file, "notes.txt");
+  ///
+  /// let args=vec!["program".to_string(), "-f".to_string(), "notes.txt".to_string(), "todo.txt".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::MultipleOccurrences);
+  /// assert_eq!(err.message(), "Option '-f' and argument 'file' cannot both be given");
+  /// ```
+  pub fn add_option_or_argument(&mut self, aliases:Vec<&'static str>, value_name:&'static str, description:&'static str) -> &mut Self {
+    let count = self.option_defs.len();
+    self.add_option(aliases, Some(value_name), None, description);
+    // the option is not added if the definition has an error that is kept for build
+    if let Some(od) = self.option_defs.get(count) {
+      self.alternatives.push((self.argument_names.len(), od.aliases[0]));
+      self.add_argument(value_name);
     }
+    self
+  }
 
-    if !options.is_empty() {
-      options.sort_by(|a,b| a.trim_start_matches(SHORT_OPTION).cmp(b.trim_start_matches(SHORT_OPTION)));
-      usage.push_str(&format!(" {}", options.join(" ").to_string()));
+  /// Adds an example to the help. Examples are shown after the options and subcommands, in the
+  /// order they were added. A subcommand's examples are shown in the subcommand's help.
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - The commandline args after the program name. e.g. `--release`
+  /// * `description` - What the example does. e.g. `Build with optimizations`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let mut build = CommandLineDef::new();
+  /// build
+  ///   .add_flag(vec!["--release"], "Build with optimizations")
+  ///   .add_example("--release", "Build a release binary");
+  ///
+  /// let args=vec!["cargo".to_string(), "build".to_string(), "-h".to_string()];
+  /// let err = CommandLineDef::new()
+  ///   .add_subcommand(vec!["build"], "Compile the package", build)
+  ///   .try_parse(args.into_iter())
+  ///   .unwrap_err();
+  ///
+  /// assert_eq!(err.usage(), "Usage: cargo build [-h] --release
+  /// -h, --help : Display usage message
+  ///  --release : Build with optimizations
+  /// Examples:
+  ///   cargo build --release
+  ///     Build a release binary");
+  /// ```
+  #[inline]
+  pub fn add_example(&mut self, args:&'static str, description:&'static str) -> &mut Self {
+    self.examples.push((args, description));
+    self
+  }
+
+  /// Sets a template for the layout of the help. The placeholders are replaced with the sections
+  /// of the help, and any other text is kept as written. A section that is empty is replaced with
+  /// an empty string.
+  ///
+  /// * `{usage}` - The usage line. e.g. `Usage: program [-h] <file>`
+  /// * `{options}` - The help lines of the options
+  /// * `{arguments}` - The arguments, one per line
+  /// * `{commands}` - The subcommands, with their heading
+  /// * `{examples}` - The examples, with their heading
+  ///
+  /// # Arguments
+  ///
+  /// * `template` - The layout of the help
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let usage = CommandLineDef::new()
+  ///   .disable_help()
+  ///   .add_flag(vec!["-v","--verbose"], "Verbose output")
+  ///   .add_argument("file")
+  ///   .help_template("{usage}\n\nArguments:\n{arguments}\n\nOptions:\n{options}")
+  ///   .usage("program");
+  ///
+  /// assert_eq!(usage, "Usage: program [-v] <file>\n\nArguments:\n  <file>\n\nOptions:\n-v, --verbose : Verbose output");
+  /// ```
+  #[inline]
+  pub fn help_template(&mut self, template:&'static str) -> &mut Self {
+    self.help_template = Some(template);
+    self
+  }
+
+  /// Displays parse errors with a line pointing to the help, such as
+  /// `Try 'program --help' for more information.`, instead of the whole usage message, so the
+  /// error is not scrolled off the screen by a large usage message. The usage message is still
+  /// returned by `ParseError::usage`. If the help option is disabled, only the error is displayed.
+  ///
+  /// # Arguments
+  ///
+  /// * `enabled` - True to display concise errors
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "-x".to_string()];
+  /// let err = CommandLineDef::new()
+  ///   .concise_errors(true)
+  ///   .try_parse(args.into_iter())
+  ///   .unwrap_err();
+  ///
+  /// assert_eq!(err.to_string(), "Option '-x' not defined\nTry 'program --help' for more information.");
+  /// ```
+  #[inline]
+  pub fn concise_errors(&mut self, enabled:bool) -> &mut Self {
+    self.concise_errors = enabled;
+    self
+  }
+
+  /// Displays parse errors as JSON, as returned by `ParseError::to_json`, for tools invoked by
+  /// IDEs and orchestration systems that read the errors. Help and version requests are still
+  /// displayed as text.
+  ///
+  /// # Arguments
+  ///
+  /// * `enabled` - True to display errors as JSON
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "-x".to_string()];
+  /// let err = CommandLineDef::new()
+  ///   .json_errors(true)
+  ///   .try_parse(args.into_iter())
+  ///   .unwrap_err();
+  ///
+  /// assert_eq!(err.to_string(),
+  ///   r#"{"kind": "OptionNotDefined", "option": "-x", "message": "Option '-x' not defined", "suggestions": []}"#);
+  /// ```
+  #[inline]
+  pub fn json_errors(&mut self, enabled:bool) -> &mut Self {
+    self.json_errors = enabled;
+    self
+  }
+
+  /// Changes the aliases of the built-in help option, e.g. to free `-h` for another option. If the
+  /// help option was disabled, it is added again.
+  ///
+  /// # Arguments
+  ///
+  /// * `aliases` - The new aliases for the help option. e.g. `"-?","--help"`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the alias does not start with '-' or '--'.
+  /// * Panics if the alias starts with '--' and the length is less than 4
+  /// * Panics if the alias starts with '-' and the length is not equal to 2
+  /// * Panics if an alias is already defined by another option
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def
+  ///   .help_option(vec!["-?","--help"])
+  ///   .add_option(vec!["-h","--host"], Some("host"), Some("localhost"), "The host to connect to");
+  ///
+  /// let args=vec!["program".to_string(), "-h".to_string(), "example.com".to_string()];
+  /// let host:String = cl_def.parse(args.into_iter()).option("--host");
+  /// assert_eq!(host, "example.com");
+  ///
+  /// let args=vec!["program".to_string(), "-?".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::Help);
+  /// ```
+  pub fn help_option(&mut self, aliases:Vec<&'static str>) -> &mut Self {
+    let description = match self.option_defs.iter().position(|od| od.help) {
+      Some(idx) => self.remove_option_def(idx).description,
+      None => T.help_description(),
+    };
+    let count = self.option_defs.len();
+    self.add_flag(aliases, description);
+    if let Some(od) = self.option_defs.get_mut(count) {
+      od.help = true;
+    }
+    self
+  }
+
+  /// Sets whether `/?` requests help, like the help option, as users of Windows tools expect. It
+  /// is enabled by default on Windows, and has no effect when the help option is disabled.
+  ///
+  /// # Arguments
+  ///
+  /// * `enabled` - True if `/?` requests help
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let args=vec!["program".to_string(), "/?".to_string()];
+  /// let err = CommandLineDef::new()
+  ///   .slash_help(true)
+  ///   .try_parse(args.into_iter())
+  ///   .unwrap_err();
+  ///
+  /// assert_eq!(err.kind(), ErrorKind::Help);
+  /// ```
+  #[inline]
+  pub fn slash_help(&mut self, enabled:bool) -> &mut Self {
+    self.slash_help = enabled;
+    self
+  }
+
+  /// Expands wildcards in arguments to the paths that match them, sorted by path, as a POSIX
+  /// shell does. Shells such as cmd.exe pass wildcards to the program unexpanded. An argument
+  /// that does not match any path, or is not a valid pattern, is kept as it is. Option values are
+  /// not expanded.
+  ///
+  /// # Arguments
+  ///
+  /// * `enabled` - True if wildcards in arguments are expanded
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "Cargo.*".to_string(), "*.none".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_argument("lock")
+  ///   .add_argument("manifest")
+  ///   .add_argument("pattern")
+  ///   .expand_wildcards(true)
+  ///   .parse(args.into_iter());
+  ///
+  /// let manifest:String = cl.argument(1);
+  /// assert_eq!(manifest, "Cargo.toml");
+  /// let pattern:String = cl.argument(2);
+  /// assert_eq!(pattern, "*.none");
+  /// ```
+  #[cfg(feature = "glob")]
+  #[inline]
+  pub fn expand_wildcards(&mut self, enabled:bool) -> &mut Self {
+    self.expand_wildcards = enabled;
+    self
+  }
+
+  /// Sets whether args that are negative numbers, such as `-1`, `-.5` or `-2e3`, are arguments.
+  /// This is the default. An arg is only a negative number if there is no option with the arg as
+  /// its alias, and no flag named by its first digit, so `-1` is still a flag when `-1` is defined.
+  /// When disabled, every arg starting with '-' is an option, so a negative number can only be
+  /// the value of an option. Values of options are taken from the next arg either way.
+  ///
+  /// # Arguments
+  ///
+  /// * `enabled` - True if negative numbers are arguments
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.add_argument("offset");
+  ///
+  /// let args=vec!["program".to_string(), "-.5".to_string()];
+  /// let cl = cl_def.parse(args.clone().into_iter());
+  /// let offset:f32 = cl.argument(0);
+  /// assert_eq!(offset, -0.5);
+  ///
+  /// let err = cl_def.negative_numbers(false).try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::OptionNotDefined);
+  /// ```
+  #[inline]
+  pub fn negative_numbers(&mut self, enabled:bool) -> &mut Self {
+    self.negative_numbers = enabled;
+    self
+  }
+
+  /// Lets a long option be given by a prefix of one of its long aliases, such as `--verb` for
+  /// `--verbose`, when the prefix does not start the aliases of other options. An option given in
+  /// full is always that option, even if it is the prefix of another. The abbreviation may have a
+  /// value attached, e.g. `--out=file`.
+  ///
+  /// # Arguments
+  ///
+  /// * `enabled` - True if long options can be abbreviated
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def
+  ///   .add_flag(vec!["-v","--verbose"], "Verbose output")
+  ///   .add_option(vec!["--version-file"], Some("file"), Some("VERSION"), "The version file")
+  ///   .infer_long_options(true);
+  ///
+  /// let args=vec!["program".to_string(), "--verb".to_string(), "--version-f=v.txt".to_string()];
+  /// let cl = cl_def.parse(args.into_iter());
+  /// assert_eq!(cl.option::<bool>("--verbose"), true);
+  /// assert_eq!(cl.option::<String>("--version-file"), "v.txt");
+  ///
+  /// let args=vec!["program".to_string(), "--ver".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::AmbiguousOption);
+  /// assert_eq!(err.message(), "Option '--ver' is ambiguous, it could be --verbose, --version-file");
+  /// ```
+  #[inline]
+  pub fn infer_long_options(&mut self, enabled:bool) -> &mut Self {
+    self.infer_long_options = enabled;
+    self
+  }
+
+  /// Sets whether option values can be attached with ':' as well as '=', and options can start
+  /// with '/', as in MSVC and .NET style commandlines. e.g. `--out:file` or `/out:file`. An option
+  /// starting with '/' must have a value attached, and `/name` is the long option `--name`, or the
+  /// short option `-n` when the name is a single character. An arg starting with '/' that does not
+  /// name a defined option, such as a path, is an argument.
+  ///
+  /// # Arguments
+  ///
+  /// * `enabled` - True if values can be attached with ':'
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "/out:a.exe".to_string(), "--level:2".to_string(),
+  ///   "/usr/src".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-o","--out"], Some("file"), None, "The output file")
+  ///   .add_option(vec!["-l","--level"], Some("level"), Some("1"), "The optimization level")
+  ///   .add_argument("dir")
+  ///   .colon_separator(true)
+  ///   .parse(args.into_iter());
+  ///
+  /// let out:String = cl.option("-o");
+  /// assert_eq!(out, "a.exe");
+  /// let level:u8 = cl.option("-l");
+  /// assert_eq!(level, 2);
+  /// let dir:String = cl.argument(0);
+  /// assert_eq!(dir, "/usr/src");
+  /// ```
+  #[inline]
+  pub fn colon_separator(&mut self, enabled:bool) -> &mut Self {
+    self.colon_separator = enabled;
+    self
+  }
+
+  /// Adds a `-V`, `--version` option that displays the version text. See
+  /// [`CommandLineDef::version_with`] to build the text when it is displayed.
+  ///
+  /// # Arguments
+  ///
+  /// * `text` - The version text. e.g. `program 1.0.0`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if `-V` or `--version` is already defined by another option
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let args=vec!["program".to_string(), "--version".to_string()];
+  /// let err = CommandLineDef::new()
+  ///   .version(concat!("program ", env!("CARGO_PKG_VERSION")))
+  ///   .try_parse(args.into_iter())
+  ///   .unwrap_err();
+  ///
+  /// assert_eq!(err.kind(), ErrorKind::Version);
+  /// assert_eq!(err.to_string(), concat!("program ", env!("CARGO_PKG_VERSION")));
+  /// ```
+  #[inline]
+  pub fn version(&mut self, text:&'static str) -> &mut Self {
+    self.version_with(move |_| text.to_string())
+  }
+
+  /// Adds a `-V`, `--version` option that displays the text returned by the closure, so the version
+  /// can include details such as the git SHA, build date or enabled features. The closure is
+  /// called with the program name when the version is requested. Parsing stops with an
+  /// [`ErrorKind::Version`] error whose message is the version text.
+  ///
+  /// # Arguments
+  ///
+  /// * `version_text` - Returns the version text for the program name
+  ///
+  /// # Panics
+  ///
+  /// * Panics if `-V` or `--version` is already defined by another option
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let args=vec!["program".to_string(), "-V".to_string()];
+  /// let err = CommandLineDef::new()
+  ///   .version_with(|program_name| format!("{program_name} 1.0.0 (abc1234 2024-01-01)"))
+  ///   .try_parse(args.into_iter())
+  ///   .unwrap_err();
+  ///
+  /// assert_eq!(err.kind(), ErrorKind::Version);
+  /// assert_eq!(err.message(), "program 1.0.0 (abc1234 2024-01-01)");
+  /// ```
+  pub fn version_with(&mut self, version_text:impl Fn(&str) -> String + Send + Sync + 'static) -> &mut Self {
+    if self.version_text.is_none() {
+      let count = self.option_defs.len();
+      self.add_flag(vec![SHORT_VERSION, LONG_VERSION], T.version_description());
+      if let Some(od) = self.option_defs.get_mut(count) {
+        od.version = true;
+      }
+    }
+    self.version_text = Some(Arc::new(version_text));
+    self
+  }
+
+  /// Removes the built-in help option, so its aliases can be used by other options. The usage
+  /// message is still available with [`CommandLineDef::usage`].
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "-h".to_string(), "example.com".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .disable_help()
+  ///   .add_option(vec!["-h"], Some("host"), None, "The host to connect to")
+  ///   .parse(args.into_iter());
+  ///
+  /// let host:String = cl.option("-h");
+  /// assert_eq!(host, "example.com");
+  /// ```
+  pub fn disable_help(&mut self) -> &mut Self {
+    if let Some(idx) = self.option_defs.iter().position(|od| od.help) {
+      self.remove_option_def(idx);
+    }
+    self
+  }
+
+  /// Restricts the values an option accepts. A value that is not one of the valid values is
+  /// rejected with an [`ErrorKind::InvalidValue`] error.
+  ///
+  /// # Arguments
+  ///
+  /// * `alias` - Any alias of the option
+  /// * `values` - The valid values. e.g. `"low","medium","high"`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def
+  ///   .add_option(vec!["-l","--level"], Some("level"), Some("medium"), "The level")
+  ///   .valid_values("--level", vec!["low","medium","high"]);
+  ///
+  /// let args=vec!["program".to_string(), "-l".to_string(), "high".to_string()];
+  /// let level:String = cl_def.parse(args.into_iter()).option("-l");
+  /// assert_eq!(level, "high");
+  ///
+  /// let args=vec!["program".to_string(), "-l".to_string(), "max".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  /// assert_eq!(err.message(), "Invalid value 'max' for option '-l'. Valid values are: low, medium, high");
+  /// ```
+  #[inline]
+  pub fn valid_values(&mut self, alias:&'static str, values:Vec<&'static str>) -> &mut Self {
+    if let Some(od) = self.option_def_mut(alias) {
+      od.valid_values = ValidValues::from_vec(values);
+    }
+    self
+  }
+
+  /// Sets the URL of an option's documentation. The help links the option and a `see docs`
+  /// reference to the URL when the terminal supports hyperlinks, and otherwise shows the URL after
+  /// the description. See [`HelpLayout::hyperlinks`].
+  ///
+  /// # Arguments
+  ///
+  /// * `alias` - Any alias of the option
+  /// * `url` - The URL of the documentation. e.g. `https://example.com/docs#level`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, HelpLayout};
+  /// let usage = CommandLineDef::new()
+  ///   .disable_help()
+  ///   .add_option(vec!["-l","--level"], Some("level"), Some("low"), "The level")
+  ///   .doc_url("--level", "https://example.com/docs#level")
+  ///   .help_layout(HelpLayout::new().hyperlinks(false))
+  ///   .usage("program");
+  ///
+  /// assert!(usage.ends_with("-l, --level <level> : The level [default: low] [docs: https://example.com/docs#level]"));
+  /// ```
+  #[inline]
+  pub fn doc_url(&mut self, alias:&'static str, url:&'static str) -> &mut Self {
+    if let Some(od) = self.option_def_mut(alias) {
+      od.doc_url = Some(url);
+    }
+    self
+  }
+
+  /// Sets the number of values an option takes, so `--point 3 4` takes the two args following the
+  /// option as its values. Fewer values on the commandline is an [`ErrorKind::MissingValue`].
+  /// The values are stored separated by commas, and are retrieved with `CommandLine::values` or
+  /// `CommandLine::tuple`. Values from the environment, config and default are written the same
+  /// way. e.g. `0,0`
+  ///
+  /// # Arguments
+  ///
+  /// * `alias` - Any alias of the option
+  /// * `arity` - The number of values the option takes
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  /// * Panics if the option is a flag, or `arity` is 0
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def
+  ///   .add_option(vec!["-p","--point"], Some("coord"), Some("0,0"), "The point to draw")
+  ///   .arity("--point", 2);
+  ///
+  /// let args=vec!["program".to_string(), "--point".to_string(), "3".to_string(), "4".to_string()];
+  /// let cl = cl_def.parse(args.into_iter());
+  /// let point:Vec<i32> = cl.values("--point");
+  /// assert_eq!(point, vec![3, 4]);
+  /// let (x, y):(i32, i32) = cl.tuple("--point");
+  /// assert_eq!((x, y), (3, 4));
+  ///
+  /// let args=vec!["program".to_string(), "--point".to_string(), "3".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::MissingValue);
+  /// assert_eq!(err.message(), "Option '--point' requires 2 values, found 1");
+  /// ```
+  #[inline]
+  pub fn arity(&mut self, alias:&'static str, arity:usize) -> &mut Self {
+    self.arity_range(alias, arity..=arity)
+  }
+
+  /// Sets the range of the number of values an option takes, so `--files a.txt b.txt` takes 1 to 5
+  /// values with an arity of `1..=5`. Values are taken from the args following the option until
+  /// the maximum is reached, an arg is a defined option, or an arg is `--`. Fewer values than the minimum on the
+  /// commandline is an [`ErrorKind::MissingValue`]. Values from the environment or config outside
+  /// the range are an [`ErrorKind::InvalidValue`]. A minimum of 0 lets the option be used without
+  /// values, giving an empty value.
+  ///
+  /// # Arguments
+  ///
+  /// * `alias` - Any alias of the option
+  /// * `arity` - The range of the number of values the option takes. e.g. `1..=5`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  /// * Panics if the option is a flag, or the range is empty or only contains 0
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def
+  ///   .add_flag(vec!["-v","--verbose"], "Verbose output")
+  ///   .add_option(vec!["-f","--files"], Some("file"), None, "The files to read")
+  ///   .arity_range("--files", 1..=5);
+  ///
+  /// let args=vec!["program".to_string(), "-f".to_string(), "a.txt".to_string(), "b.txt".to_string(), "-v".to_string()];
+  /// let cl = cl_def.parse(args.into_iter());
+  /// let files:Vec<String> = cl.values("--files");
+  /// assert_eq!(files, vec!["a.txt", "b.txt"]);
+  /// assert_eq!(cl.option::<bool>("-v"), true);
+  ///
+  /// let args=vec!["program".to_string(), "-f".to_string(), "-v".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::MissingValue);
+  /// assert_eq!(err.message(), "Option '-f' requires 1 to 5 values, found 0");
+  /// ```
+  pub fn arity_range(&mut self, alias:&'static str, arity:RangeInclusive<usize>) -> &mut Self {
+    let Some(od) = self.option_def_mut(alias) else {
+      return self;
+    };
+    if od.value_name.is_none() || arity.is_empty() || *arity.end() == 0 {
+      let arity = match arity.start() == arity.end() {
+        true => arity.start().to_string(),
+        false => format!("{}..={}", arity.start(), arity.end()),
+      };
+      self.def_failed(DefErrorKind::InvalidOption, T.option_invalid_arity(alias, &arity));
+      return self;
+    }
+    od.arity = arity;
+    self
+  }
+
+  /// Makes an option with a range of values lazy, so it only takes the minimum number of values
+  /// and the args after them are arguments. By default an option is greedy, taking values until
+  /// the maximum is reached or an arg is a defined option.
+  ///
+  /// # Arguments
+  ///
+  /// * `alias` - Any alias of the option
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def
+  ///   .add_option(vec!["-t","--tags"], Some("tag"), None, "The tags to add")
+  ///   .arity_range("--tags", 1..=3)
+  ///   .add_argument("file");
+  ///
+  /// let args=vec!["program".to_string(), "-t".to_string(), "draft".to_string(), "notes.txt".to_string()];
+  /// assert!(cl_def.try_parse(args.clone().into_iter()).is_err());
+  ///
+  /// cl_def.lazy_values("--tags");
+  /// let cl = cl_def.parse(args.into_iter());
+  /// let tags:Vec<String> = cl.values("--tags");
+  /// assert_eq!(tags, vec!["draft"]);
+  /// assert_eq!(cl.argument::<String>(0), "notes.txt");
+  /// ```
+  #[inline]
+  pub fn lazy_values(&mut self, alias:&'static str) -> &mut Self {
+    if let Some(od) = self.option_def_mut(alias) {
+      od.lazy = true;
+    }
+    self
+  }
+
+  /// Allows the option's value to be attached and empty on the commandline, so `--name=` stores an
+  /// empty value. By default an empty attached value is an [`ErrorKind::MissingValue`], while
+  /// `--name ''` always stores an empty value.
+  ///
+  /// # Arguments
+  ///
+  /// * `alias` - Any alias of the option
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.add_option(vec!["-n","--name"], Some("name"), Some("anonymous"), "The name");
+  ///
+  /// let args=vec!["program".to_string(), "--name".to_string(), "".to_string()];
+  /// let name:String = cl_def.parse(args.into_iter()).option("--name");
+  /// assert_eq!(name, "");
+  ///
+  /// let args=vec!["program".to_string(), "--name=".to_string()];
+  /// let err = cl_def.try_parse(args.clone().into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::MissingValue);
+  ///
+  /// cl_def.allow_empty_values("-n");
+  /// let name:String = cl_def.parse(args.into_iter()).option("--name");
+  /// assert_eq!(name, "");
+  /// ```
+  #[inline]
+  pub fn allow_empty_values(&mut self, alias:&'static str) -> &mut Self {
+    if let Some(od) = self.option_def_mut(alias) {
+      od.allow_empty = true;
+    }
+    self
+  }
+
+  /// Makes the option's value optional, as getopt does for `c::`. The value is only taken when it is
+  /// attached to the option, such as `-cvalue` or `--color=value`, and the option given alone has
+  /// an empty value.
+  #[inline]
+  pub(crate) fn optional_value(&mut self, alias:&'static str) -> &mut Self {
+    if let Some(od) = self.option_def_mut(alias) {
+      od.optional_value = true;
+    }
+    self
+  }
+
+  /// Sets an environment variable used for the option's value when the option is not on the
+  /// commandline. The commandline takes precedence over the environment variable, which takes
+  /// precedence over the default value.
+  ///
+  /// # Arguments
+  ///
+  /// * `alias` - Any alias of the option
+  /// * `var` - The name of the environment variable. e.g. `MYAPP_HOST`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// std::env::set_var("DOC_ENV_HOST", "example.com");
+  ///
+  /// let args=vec!["program".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["--host"], Some("host"), Some("localhost"), "The host")
+  ///   .env("--host", "DOC_ENV_HOST")
+  ///   .parse(args.into_iter());
+  ///
+  /// let host:String = cl.option("--host");
+  /// assert_eq!(host, "example.com");
+  /// ```
+  #[cfg(feature = "std")]
+  #[inline]
+  pub fn env(&mut self, alias:&'static str, var:&'static str) -> &mut Self {
+    if let Some(od) = self.option_def_mut(alias) {
+      od.env = Some(var);
+    }
+    self
+  }
+
+  /// Makes an option settable only with an environment variable, to keep secrets such as API
+  /// tokens out of the shell history. The help message shows the environment variable instead of
+  /// the aliases, and using the option on the commandline is rejected with an
+  /// [`ErrorKind::EnvOnlyOption`] error. The value is still retrieved with the option's aliases.
+  ///
+  /// # Arguments
+  ///
+  /// * `alias` - Any alias of the option
+  /// * `var` - The name of the environment variable. e.g. `MYAPP_TOKEN`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// std::env::set_var("DOC_ENV_ONLY_TOKEN", "secret");
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def
+  ///   .add_option(vec!["--token"], Some("token"), None, "The API token")
+  ///   .env_only("--token", "DOC_ENV_ONLY_TOKEN");
+  ///
+  /// let args=vec!["program".to_string()];
+  /// let token:String = cl_def.parse(args.into_iter()).option("--token");
+  /// assert_eq!(token, "secret");
+  ///
+  /// let args=vec!["program".to_string(), "--token".to_string(), "secret".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::EnvOnlyOption);
+  /// assert_eq!(err.message(), "Option '--token' can only be set with the environment variable 'DOC_ENV_ONLY_TOKEN'");
+  /// ```
+  #[cfg(feature = "std")]
+  #[inline]
+  pub fn env_only(&mut self, alias:&'static str, var:&'static str) -> &mut Self {
+    if let Some(od) = self.option_def_mut(alias) {
+      od.env = Some(var);
+      od.env_only = true;
+    }
+    self
+  }
+
+  /// Sets an environment variable holding extra args, like `RUSTFLAGS`. The value is split into
+  /// words the way a POSIX shell does, and the words are parsed before the commandline args.
+  ///
+  /// # Arguments
+  ///
+  /// * `var` - The name of the environment variable. e.g. `MYTOOL_FLAGS`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// std::env::set_var("DOC_TOOL_FLAGS", "--name 'a b' -v");
+  ///
+  /// let args=vec!["program".to_string(), "input.txt".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["--name"], Some("name"), None, "The name")
+  ///   .add_flag(vec!["-v"], "Verbose output")
+  ///   .add_argument("input")
+  ///   .args_env_var("DOC_TOOL_FLAGS")
+  ///   .parse(args.into_iter());
+  ///
+  /// let name:String = cl.option("--name");
+  /// assert_eq!(name, "a b");
+  /// let input:String = cl.argument(0);
+  /// assert_eq!(input, "input.txt");
+  /// ```
+  #[cfg(feature = "std")]
+  #[inline]
+  pub fn args_env_var(&mut self, var:&'static str) -> &mut Self {
+    self.args_env_var = Some(var);
+    self
+  }
+
+  /// Sets the .env file loaded before the environment variables are read. By default `.env` in the
+  /// current directory is loaded. The variables in the file do not override the ones already set,
+  /// and the process environment is not modified. A missing file is ignored.
+  ///
+  /// # Arguments
+  ///
+  /// * `path` - The path of the .env file
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let path = std::env::temp_dir().join("cl_parse_doc_dotenv");
+  /// std::fs::write(&path, "DOC_DOTENV_HOST=example.com\n").unwrap();
+  ///
+  /// let args=vec!["program".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["--host"], Some("host"), Some("localhost"), "The host")
+  ///   .env("--host", "DOC_DOTENV_HOST")
+  ///   .dotenv(&path)
+  ///   .parse(args.into_iter());
+  ///
+  /// let host:String = cl.option("--host");
+  /// assert_eq!(host, "example.com");
+  /// assert!(std::env::var("DOC_DOTENV_HOST").is_err());
+  /// ```
+  #[cfg(feature = "dotenv")]
+  #[inline]
+  pub fn dotenv(&mut self, path:impl Into<PathBuf>) -> &mut Self {
+    self.dotenv_path = Some(path.into());
+    self
+  }
+
+  /// Stops a .env file from being loaded before the environment variables are read
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["--host"], Some("host"), Some("localhost"), "The host")
+  ///   .env("--host", "DOC_NO_DOTENV_HOST")
+  ///   .disable_dotenv()
+  ///   .parse(args.into_iter());
+  ///
+  /// let host:String = cl.option("--host");
+  /// assert_eq!(host, "localhost");
+  /// ```
+  #[cfg(feature = "dotenv")]
+  #[inline]
+  pub fn disable_dotenv(&mut self) -> &mut Self {
+    self.dotenv_path = None;
+    self
+  }
+
+  /// Sets the config values, such as the ones read from a config file. Each value is used for
+  /// the option whose first long alias, without the leading dashes, is the key, or whose short
+  /// alias is the key if it has no long alias. By default, config values take precedence over
+  /// default values, and environment variables and the commandline take precedence over them.
+  /// Keys that are not options are ignored.
+  ///
+  /// # Arguments
+  ///
+  /// * `values` - The config values, by option key. e.g. `("output", "out.txt")`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "--port".to_string(), "443".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-H","--host"], Some("host"), Some("localhost"), "The host")
+  ///   .add_option(vec!["-p","--port"], Some("port"), Some("80"), "The port")
+  ///   .config([("host", "example.com"), ("port", "8080")])
+  ///   .parse(args.into_iter());
+  ///
+  /// let host:String = cl.option("-H");
+  /// assert_eq!(host, "example.com");
+  ///
+  /// let port:u16 = cl.option("-p");
+  /// assert_eq!(port, 443);
+  /// ```
+  pub fn config<K:Into<String>, V:Into<String>>(&mut self, values:impl IntoIterator<Item=(K, V)>) -> &mut Self {
+    self.config = values.into_iter().map(|(key, value)| (key.into(), value.into())).collect();
+    self
+  }
+
+  /// Sets the precedence of the sources of option values, from highest to lowest. The default is
+  /// the commandline, then environment variables, then config values, then default values. Sources
+  /// that are not listed are not used.
+  ///
+  /// # Arguments
+  ///
+  /// * `sources` - The sources, from highest to lowest precedence
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # #[cfg(feature = "std")] {
+  /// use cl_parse::{CommandLineDef, Source};
+  /// std::env::set_var("DOC_PRECEDENCE_HOST", "env.example.com");
+  ///
+  /// let args=vec!["program".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["--host"], Some("host"), Some("localhost"), "The host")
+  ///   .env("--host", "DOC_PRECEDENCE_HOST")
+  ///   .config([("host", "config.example.com")])
+  ///   .precedence(vec![Source::CommandLine, Source::Config, Source::Env, Source::Default])
+  ///   .parse(args.into_iter());
+  ///
+  /// let host:String = cl.option("--host");
+  /// assert_eq!(host, "config.example.com");
+  /// # }
+  /// ```
+  #[inline]
+  pub fn precedence(&mut self, sources:Vec<Source>) -> &mut Self {
+    self.precedence = sources;
+    self
+  }
+
+  /// Stops an option's value from coming from a source. A value found in the source is ignored,
+  /// including a value on the commandline.
+  ///
+  /// # Arguments
+  ///
+  /// * `alias` - Any alias of the option
+  /// * `source` - The source to ignore for the option
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, Source};
+  /// let args=vec!["program".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["--log-file"], Some("path"), Some("out.log"), "The log file")
+  ///   .exclude_source("--log-file", Source::Config)
+  ///   .config([("log-file", "/etc/shadow")])
+  ///   .parse(args.into_iter());
+  ///
+  /// let log_file:String = cl.option("--log-file");
+  /// assert_eq!(log_file, "out.log");
+  /// ```
+  #[inline]
+  pub fn exclude_source(&mut self, alias:&'static str, source:Source) -> &mut Self {
+    if let Some(od) = self.option_def_mut(alias) {
+      od.excluded_sources.push(source);
+    }
+    self
+  }
+
+  /// Marks an option as deprecated. The option still parses as before, but a
+  /// [`WarningKind::DeprecatedOption`] warning is raised when it is used.
+  ///
+  /// # Arguments
+  ///
+  /// * `alias` - Any alias of the option
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use std::sync::{Arc, Mutex};
+  /// use cl_parse::CommandLineDef;
+  /// let warnings = Arc::new(Mutex::new(Vec::new()));
+  /// let sink = warnings.clone();
+  ///
+  /// let args=vec!["program".to_string(), "--old".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_flag(vec!["--old"], "Use --new instead")
+  ///   .deprecate_option("--old")
+  ///   .on_warning(move |warning| sink.lock().unwrap().push(warning.to_string()))
+  ///   .parse(args.into_iter());
+  ///
+  /// let old:bool = cl.option("--old");
+  /// assert_eq!(old, true);
+  /// assert_eq!(*warnings.lock().unwrap(), vec!["Option '--old' is deprecated"]);
+  /// ```
+  #[inline]
+  pub fn deprecate_option(&mut self, alias:&'static str) -> &mut Self {
+    if let Some(od) = self.option_def_mut(alias) {
+      od.deprecated = true;
+    }
+    self
+  }
+
+  /// Sets the sink that receives the warnings found while parsing, so applications can route them
+  /// to their logger. Parsing continues after a warning. Without a sink, warnings are written to
+  /// stderr, or the error writer set with `with_output`, or dropped without std.
+  ///
+  /// # Arguments
+  ///
+  /// * `sink` - Called with each warning
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, WarningKind};
+  /// let args=vec!["program".to_string(), "-o".to_string()];
+  /// CommandLineDef::new()
+  ///   .add_flag(vec!["-o"], "An old flag")
+  ///   .deprecate_option("-o")
+  ///   .on_warning(|warning| assert_eq!(warning.kind(), WarningKind::DeprecatedOption))
+  ///   .parse(args.into_iter());
+  /// ```
+  #[inline]
+  pub fn on_warning(&mut self, sink:impl Fn(&Warning) + Send + Sync + 'static) -> &mut Self {
+    self.warning_sink = Some(Arc::new(sink));
+    self
+  }
+
+  /// Sets a closure that rewrites the args before they are parsed, so applications can expand
+  /// their own abbreviations or strip a wrapper's args. The closure receives the args after the
+  /// program name, including those from the environment variable set with `args_env_var`, and
+  /// returns the args to parse. The args after a subcommand name are rewritten by the
+  /// subcommand's closure, if it has one.
+  ///
+  /// # Arguments
+  ///
+  /// * `preprocessor` - Returns the args to parse
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "-O".to_string(), "main.rs".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["--opt-level"], Some("level"), Some("0"), "The optimization level")
+  ///   .add_argument("file")
+  ///   .preprocess(|args| args.into_iter()
+  ///     .flat_map(|arg| match arg.as_str() {
+  ///       "-O" => vec!["--opt-level".to_string(), "3".to_string()],
+  ///       _ => vec![arg],
+  ///     })
+  ///     .collect())
+  ///   .parse(args.into_iter());
+  ///
+  /// let level:u8 = cl.option("--opt-level");
+  /// assert_eq!(level, 3);
+  /// ```
+  #[inline]
+  pub fn preprocess(&mut self, preprocessor:impl Fn(Vec<String>) -> Vec<String> + Send + Sync + 'static) -> &mut Self {
+    self.preprocessor = Some(Arc::new(preprocessor));
+    self
+  }
+
+  /// Sets a closure that runs after the commandline has been parsed and validated, and can store
+  /// values derived from the parsed ones with `CommandLine::set_option`, so the rest of the
+  /// program reads every value from the CommandLine. A subcommand's closure runs before its
+  /// parent's.
+  ///
+  /// # Arguments
+  ///
+  /// * `finalizer` - Called with the parsed commandline
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-j","--jobs"], Some("jobs"), Some("0"), "The number of jobs, 0 for one per CPU")
+  ///   .finalize(|cl| {
+  ///     if cl.option::<u32>("--jobs") == 0 {
+  ///       cl.set_option("--jobs", 8);
+  ///     }
+  ///   })
+  ///   .parse(args.into_iter());
+  ///
+  /// let jobs:u32 = cl.option("-j");
+  /// assert_eq!(jobs, 8);
+  /// ```
+  #[inline]
+  pub fn finalize(&mut self, finalizer:impl Fn(&mut CommandLine) + Send + Sync + 'static) -> &mut Self {
+    self.finalizer = Some(Arc::new(finalizer));
+    self
+  }
+
+  /// Creates a new CommandLine from this CommandLineDef and the args
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - A string iterator that holds the commandline arguments to be parsed
+  ///
+  /// # Panics
+  ///
+  /// * Panics with the usage message if -h or --help is specified
+  /// * Panics if an option is specified and its value is missing
+  /// * Panics if an undefined option is present on the commandline
+  /// * Panics if a required option is not present on the commandline
+  /// * Panics if number of arguments is incorrect
+  ///
+  /// With the `std` feature, `on_error` can exit or unwind with the error instead of panicking.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use std::collections::VecDeque;
+  ///  use std::env;
+  ///  // Simulate env::args()
+  ///  let env_args=vec![String::from("program"), String::from("-f"), String::from("/file/path")];
+  ///  use cl_parse::{CommandLine, CommandLineDef};
+  ///  let cl = CommandLineDef::new().add_option(vec!["-f","--filename"], Some("filepath"),
+  ///      None, "The file to be parsed").parse(env_args.into_iter());
+  ///
+  ///   // Test Program Name
+  ///   assert_eq!(false, cl.program_name().is_empty());
+  /// ```
+  pub fn parse(&self, args: impl Iterator<Item=String>) -> CommandLine {
+    self.try_parse(args).unwrap_or_else(|e| self.fail(e))
+  }
+
+  /// Panics with the error message and the usage message
+  #[cfg(not(feature = "std"))]
+  #[inline]
+  fn fail(&self, error:ParseError) -> ! {
+    panic!("{}", error)
+  }
+
+  /// Creates a new CommandLine from this CommandLineDef and the args, returning an error instead of
+  /// panicking when the commandline is invalid. This suits environments where unwinding is not
+  /// available or desirable, such as wasm.
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - A string iterator that holds the commandline arguments to be parsed
+  ///
+  /// # Errors
+  ///
+  /// * [`ErrorKind::InvalidEnvArgs`] if the args in the environment variable set with
+  ///   `args_env_var` have an unterminated quote or escape
+  /// * [`ErrorKind::InvalidDotenv`] if the .env file cannot be read or parsed
+  /// * [`ErrorKind::Help`] if the help option, -h or --help by default, is specified
+  /// * [`ErrorKind::Version`] if -V or --version is specified and a version has been set
+  /// * [`ErrorKind::MissingValue`] if an option is specified and its value is missing, or attached
+  ///   and empty without `allow_empty_values`
+  /// * [`ErrorKind::OptionNotDefined`] if an undefined option is present on the commandline
+  /// * [`ErrorKind::AmbiguousOption`] if long options can be abbreviated, and an abbreviation is
+  ///   the prefix of several options
+  /// * [`ErrorKind::MultipleOccurrences`] if an option or its aliases are specified more than once
+  /// * [`ErrorKind::InvalidValue`] if an option's value is not one of its valid values
+  /// * [`ErrorKind::EnvOnlyOption`] if an option that can only be set from the environment is
+  ///   present on the commandline
+  /// * [`ErrorKind::NonAsciiFlags`] if a flag concatenation contains a non-ASCII character
+  /// * [`ErrorKind::MissingRequiredOption`] if a required option is not present on the commandline,
+  ///   or in its environment variable
+  /// * [`ErrorKind::WrongArgumentCount`] if number of arguments is incorrect. When arguments are
+  ///   missing, the message names them.
+  /// * [`ErrorKind::SubcommandNotDefined`] if the definition has subcommands, and the arg naming the
+  ///   subcommand is not one of them
+  /// * [`ErrorKind::AmbiguousSubcommand`] if subcommands are inferred, and the arg naming the
+  ///   subcommand is a prefix of several of them
+  ///
+  /// Errors in the args after a subcommand are returned with the subcommand's usage message.
+  ///
+  /// The args are checked in the order they appear on the commandline, and then the options not
+  /// found are checked in the order they were defined. When the commandline has several errors,
+  /// the error returned is always the first one found in that order.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::{CommandLineDef, ErrorKind};
+  ///  let args=vec![String::from("program"), String::from("-f")];
+  ///  let err = CommandLineDef::new()
+  ///    .add_option(vec!["-f","--filename"], Some("filepath"), None, "The file to be parsed")
+  ///    .try_parse(args.into_iter())
+  ///    .unwrap_err();
+  ///
+  ///  assert_eq!(err.kind(), ErrorKind::MissingValue);
+  ///  assert_eq!(err.message(), "A value is required for option '-f'");
+  /// ```
+  pub fn try_parse(&self, mut args: impl Iterator<Item=String>) -> Result<CommandLine, ParseError> {
+    let mut options = Options::default();
+
+    let program_name = args.next().unwrap_or_default();
+    let loaded;
+    let env = match self.precomputed.as_ref().and_then(|precomputed| precomputed.environment.as_ref()) {
+      Some(environment) => environment,
+      None => {
+        loaded = Environment::load(self, &program_name)?;
+        &loaded
+      },
+    };
+    let env_args = self.env_args(env, &program_name)?;
+
+    let args:Vec<String> = match &self.preprocessor {
+      Some(preprocessor) => preprocessor(env_args.into_iter().chain(args).collect()),
+      None => env_args.into_iter().chain(args).collect(),
+    };
+    let arg_count = args.len();
+    // make the iterator peekable so we can see the next one
+    let mut peekable_args = args.into_iter().peekable();
+    let mut indices:BTreeMap<&'static str, Vec<usize>> = BTreeMap::default();
+    let mut matched:BTreeMap<&'static str, Arc<str>> = BTreeMap::default();
+    // the usage message is only built when it is needed, and positionals are reserved up front,
+    // so parsing stays linear in the number of args
+    let mut arguments:Vec<String> = Vec::with_capacity(peekable_args.size_hint().0);
+    let mut subcommand = None;
+    let mut rest = Vec::default();
+
+    while let Some(arg) = peekable_args.next() {
+      // `--` ends the options, and the args after it are arguments, even if they start with '-'
+      if arg == LONG_OPTION {
+        rest = peekable_args.by_ref().collect();
+        for arg in &rest {
+          self.push_argument(&mut arguments, arg.clone());
+        }
+        break;
+      }
+      let arg = self.expand_abbreviation(arg, &program_name)?;
+      if let Some(od) = self.find_option_def(&arg).or_else(|| self.find_slash_help(&arg)) {
+        if od.help {
+          let usage = self.usage_for(&program_name, Stream::Stdout);
+          return Err(ParseError::new(ErrorKind::Help, usage.clone(), usage));
+        }
+        if let (true, Some(version_text)) = (od.version, &self.version_text) {
+          return Err(self.parse_error(ErrorKind::Version, version_text(&program_name), &program_name));
+        }
+      }
+      // the position of the arg, counting the program name as 0
+      let position = arg_count - peekable_args.len();
+      let found = if let Some((option, value)) = self.split_attached_value(&arg) {
+        self.parse_attached_option(option, value, &mut peekable_args, &program_name, &mut options)?
+      } else if arg.starts_with(SHORT_OPTION) && !self.is_negative_number(&arg) {
+        self.parse_option(arg, None, &mut peekable_args, &program_name, &mut options)?
+      } else if let Some(toggles) = self.parse_plus_toggles(&arg, &program_name, &mut options)? {
+        toggles
+      } else if !self.subcommands.is_empty() && arguments.len() == self.argument_count(&options) {
+        subcommand = Some(self.resolve_subcommand(&arg, &program_name)?);
+        break;
+      } else {
+        self.push_argument(&mut arguments, arg);
+        continue;
+      };
+      for (od, given) in found {
+        let given:Arc<str> = Arc::from(given);
+        for alias in &od.aliases {
+          indices.entry(alias).or_default().push(position);
+          matched.insert(*alias, given.clone());
+        }
+      }
+    }
+    // make sure we got the defined number of arguments
+    let (arguments, names, variadic) = self.assign_arguments(arguments, &mut options, &program_name)?;
+    let (defaults, given) = self.add_default_options(env, &mut options, &program_name)?;
+    let mut cl = CommandLine::new(program_name, options, arguments);
+    cl.set_defaults(defaults);
+    cl.set_given(given);
+    cl.set_indices(indices);
+    cl.set_matched(matched);
+    cl.set_argument_names(names);
+    cl.set_option_names(self.option_defs.iter().map(|od| od.canonical_alias().to_string()).collect());
+    cl.set_option_forms(self.option_defs.iter().map(|od| (od.canonical_alias(), od.form())).collect());
+    cl.set_variadic(variadic);
+    cl.set_rest(rest);
+    let subcommand = subcommand.or_else(|| self.default_subcommand.and_then(|name| self.find_subcommand(name)));
+    if let Some(subcommand) = subcommand {
+      let name = subcommand.names[0];
+      // boxed, so parsing subcommands does not instantiate try_parse for ever deeper iterators
+      let sub_args:Box<dyn Iterator<Item=String>> = Box::new(once(format!("{} {}", cl.program_name(), name)).chain(peekable_args));
+      cl.set_subcommand(name, subcommand.cl_def.try_parse(sub_args)?);
+    }
+    if let Some(finalizer) = &self.finalizer {
+      finalizer(&mut cl);
+      cl.set_aliases(|name| self.find_option_def(name).map(|od| od.aliases.as_slice()));
+    }
+    Ok(cl)
+  }
+
+  /// Returns the usage message, as displayed by the help option. The usage line lists the flags,
+  /// then the optional options, then the required options, each sorted by name, followed by the
+  /// arguments in the order they were defined. The help lines follow in the order the options were
+  /// defined. Like the help, it is laid out for stdout, as described in `HelpLayout::hyperlinks`.
+  ///
+  /// # Arguments
+  ///
+  /// * `program_name` - The name of the program shown in the usage line
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let usage = CommandLineDef::new()
+  ///   .disable_help()
+  ///   .add_flag(vec!["-v","--verbose"], "Verbose output")
+  ///   .usage("program");
+  ///
+  /// assert_eq!(usage, "Usage: program [-v]\n-v, --verbose : Verbose output");
+  /// ```
+  #[inline]
+  pub fn usage(&self, program_name:&str) -> String {
+    self.usage_for(program_name, Stream::Stdout)
+  }
+
+  /// Returns the usage message for the stream it is written to
+  pub(crate) fn usage_for(&self, program_name:&str, stream:Stream) -> String {
+    let layout = self.effective_help_layout(stream);
+    match &self.precomputed {
+      Some(precomputed) if layout == precomputed.layout => precomputed.usage.replace(PROGRAM_PLACEHOLDER, program_name),
+      _ => self.render_usage(program_name, layout),
+    }
+  }
+
+  /// Returns the usage message with the layout for the help lines
+  pub(crate) fn render_usage(&self, program_name:&str, layout:HelpLayout) -> String {
+    let mut flags: Vec<char> = Vec::default();
+    let mut options: Vec<String> = Vec::default();
+    let mut requireds: Vec<String> = Vec::default();
+    let mut help_lines: Vec<(String, Option<&'static str>, String)> = Vec::default();
+    let mut max_len = 0;
+
+    for od in &self.option_defs {
+      if let (true, Some(var)) = (od.env_only, od.env) {
+        let help_options = format!("${var}");
+        max_len = max(max_len, help_options.chars().count());
+        help_lines.push((help_options, od.doc_url, message(od.description).to_string()));
+        continue;
+      }
+      let mut help_options = od.help_aliases();
+      if let Some(value_name) = od.value_name {
+        let placeholder = od.value_placeholder(value_name);
+        help_options = format!("{} {}", help_options, placeholder);
+        if od.default_value.is_some() {
+          options.push(format!("[{} {}]",od.aliases[0],placeholder));
+        } else if self.alternatives.iter().all(|(_, alias)| *alias != od.aliases[0]) {
+          // alternatives are shown with their argument
+          requireds.push(format!("{} {}",od.aliases[0],placeholder));
+        }
+      } else if od.aliases[0].starts_with(LONG_OPTION) {
+        options.push(od.aliases[0].to_string())
+      } else {
+        flags.push(od.aliases[0].chars().last().unwrap())
+      }
+      max_len = max(max_len, help_options.chars().count());
+      help_lines.push((help_options, od.doc_url, self.help_description(od, layout)));
+    }
+
+    let mut usage = T.usage(program_name);
+
+    if !flags.is_empty() {
+      flags.sort();
+      usage.push_str(&format!(" [-{}]", flags.iter().fold(String::default(),|acc, c |{acc + &c.to_string()})));
+    }
+
+    if !options.is_empty() {
+      options.sort_by(|a,b| a.trim_start_matches(SHORT_OPTION).cmp(b.trim_start_matches(SHORT_OPTION)));
+      usage.push_str(&format!(" {}", options.join(" ")));
     }
 
     let x: &[_] = &['[', '-'];
     if !requireds.is_empty() {
       requireds.sort_by(|a,b| a.trim_start_matches(x).cmp(b.trim_start_matches(x)));
-      usage.push_str(&format!(" {}", requireds.join(" ").to_string()));
+      usage.push_str(&format!(" {}", requireds.join(" ")));
+    }
+
+    let arguments:Vec<String> = self.argument_names.iter().enumerate()
+      .map(|(idx, argument_name)| match (self.variadic == Some(idx), self.alternatives.iter().find(|(argument, _)| *argument == idx)) {
+        (true, _) => format!("[<{argument_name}>...]"),
+        (false, Some((_, alias))) => format!("({alias} <{argument_name}> | <{argument_name}>)"),
+        (false, None) => format!("<{argument_name}>"),
+      })
+      .collect();
+    for argument in &arguments {
+      usage.push_str(&format!(" {argument}"));
+    }
+
+    if !self.subcommands.is_empty() {
+      usage.push_str(&format!(" {}", T.usage_command()));
+      max_len = self.subcommands.iter().map(|subcommand| subcommand.names[0].chars().count()).fold(max_len, max);
     }
 
-    if !self.argument_names.is_empty() {
-      usage.push_str(&format!(" <{}>", self.argument_names.join("> <").to_string()));
+    let options:Vec<String> = help_lines.iter()
+      .map(|(options, url, description)| layout.line(options, *url, description, max_len))
+      .collect();
+
+    let mut commands = String::default();
+    if !self.subcommands.is_empty() {
+      commands.push_str(&T.usage_commands());
+      for subcommand in &self.subcommands {
+        let summary = match self.default_subcommand == Some(subcommand.names[0]) {
+          true => format!("{} {}", message(subcommand.summary), T.help_default_command()),
+          false => message(subcommand.summary).to_string(),
+        };
+        commands.push_str(&format!("\n{}", layout.line(subcommand.names[0], None, &summary, max_len)));
+      }
     }
 
-    for (options, description) in help_lines {
-      usage.push_str(&format!("\n{:>max_len$} : {}", options, description));
+    let mut examples = String::default();
+    if !self.examples.is_empty() {
+      examples.push_str(&T.usage_examples());
+      for (args, description) in &self.examples {
+        examples.push_str(&format!("\n  {program_name} {args}\n    {}", message(description)));
+      }
     }
 
+    if let Some(template) = self.help_template {
+      let arguments:Vec<String> = arguments.iter().map(|argument| format!("  {argument}")).collect();
+      return template
+        .replace("{usage}", &usage)
+        .replace("{options}", &options.join("\n"))
+        .replace("{arguments}", &arguments.join("\n"))
+        .replace("{commands}", &commands)
+        .replace("{examples}", &examples);
+    }
+    for section in options.iter().chain([&commands, &examples]).filter(|section| !section.is_empty()) {
+      usage.push_str(&format!("\n{section}"));
+    }
     usage
   }
 
+  /// Returns the description of an option in the help, annotated with its environment variable and
+  /// default value, or `(required)` if it must be specified
+  fn help_description(&self, od:&OptionDef, layout:HelpLayout) -> String {
+    let valid_values = match od.valid_values.is_empty() {
+      true => String::default(),
+      false => T.help_valid_values(&od.valid_values.join(", ")),
+    };
+    let od_description = message(od.description);
+    let mut description = match layout.valid_values_position() {
+      ValidValuesPosition::BeforeDescription if !valid_values.is_empty() => format!("{valid_values} {od_description}"),
+      ValidValuesPosition::AfterDescription if !valid_values.is_empty() => format!("{od_description} {valid_values}"),
+      _ => od_description.to_string(),
+    };
+    if let Some(var) = od.env {
+      description.push_str(&format!(" {}", T.help_env(var)));
+    }
+    match (od.value_name, od.default_value) {
+      (Some(_), Some(default)) if !default.is_empty() => description.push_str(&format!(" {}", T.help_default(default))),
+      (Some(_), None) if self.alternatives.iter().all(|(_, alias)| *alias != od.aliases[0]) =>
+        description.push_str(&format!(" {}", T.help_required())),
+      _ => {},
+    }
+    match od.doc_url {
+      Some(url) if layout.hyperlinks_enabled() => description.push_str(&format!(" [{}]", hyperlink(url, T.help_see_docs()))),
+      Some(url) => description.push_str(&format!(" {}", T.help_docs(url))),
+      None => {},
+    }
+    description
+  }
+
+  #[inline]
+  pub(crate) fn find_option_def(&self, option:&str) -> Option<&OptionDef> {
+    let pos = self.alias_table.binary_search_by(|(a, _)| (*a).cmp(option)).ok()?;
+    Some(&self.option_defs[self.alias_table[pos].1])
+  }
+
+  /// Adds the OptionDef and its aliases
+  ///
+  /// # Panics
+  ///
+  /// * Panics if an alias is defined more than once
+  #[inline]
+  fn push_option_def(&mut self, od:OptionDef) {
+    if let Some(alias) = od.aliases.iter().find(|alias| self.find_option_def(alias).is_some()) {
+      self.def_failed(DefErrorKind::Redefined, T.option_redefined(alias));
+      return;
+    }
+    self.option_defs.push(od);
+    let od_idx = self.option_defs.len()-1;
+    for alias in &self.option_defs[od_idx].aliases {
+      let pos = self.alias_table.binary_search_by(|(a, _)| a.cmp(alias)).unwrap_or_else(|pos| pos);
+      self.alias_table.insert(pos, (alias, od_idx));
+    }
+  }
+
+  /// Adds an argument, or the paths matching it when wildcards are expanded
+  #[cfg(feature = "glob")]
+  fn push_argument(&self, arguments:&mut Vec<String>, arg:String) {
+    let paths = match self.expand_wildcards && arg.contains(['*', '?', '[']) {
+      true => glob::glob(&arg).map(|paths| paths.flatten().map(|path| path.to_string_lossy().into_owned()).collect()),
+      false => Ok(Vec::default()),
+    };
+    match paths {
+      Ok(paths) if !paths.is_empty() => arguments.extend(paths),
+      _ => arguments.push(arg),
+    }
+  }
+
+  /// Adds an argument
+  #[cfg(not(feature = "glob"))]
+  #[inline]
+  fn push_argument(&self, arguments:&mut Vec<String>, arg:String) {
+    arguments.push(arg);
+  }
+
+  /// Checks the number of arguments found, and assigns the args to the arguments. The arguments
+  /// after the variadic argument take their args first. The args of alternative arguments are
+  /// stored as the values of their options. Returns the remaining args, the name of the argument
+  /// of each, and the indexes of the args of the variadic argument.
+  fn assign_arguments(&self, found:Vec<String>, options:&mut Options, program_name:&str)
+                      -> Result<AssignedArguments, ParseError> {
+    // the arguments whose alternative option was given are not expected on the commandline
+    let expected:Vec<usize> = (0..self.argument_names.len()).filter(|idx| self.given_alternative(*idx, options).is_none()).collect();
+    let variadic = self.variadic.and_then(|idx| expected.iter().position(|expected| *expected == idx));
+    let too_many = match variadic {
+      Some(_) => false,
+      None => found.len() > expected.len(),
+    };
+    let both = (0..self.argument_names.len()).find_map(|idx| self.given_alternative(idx, options).map(|od| (idx, od)));
+    if let (true, Some((idx, od))) = (too_many, both) {
+      return Err(self.parse_error(ErrorKind::MultipleOccurrences, T.argument_alternative_both(od.aliases[0], self.argument_names[idx]), program_name).with_option(od.aliases[0]));
+    }
+    let required:Vec<&str> = expected.iter().filter(|idx| self.variadic != Some(**idx)).map(|idx| self.argument_names[*idx]).collect();
+    let extra = match variadic {
+      _ if found.len() < required.len() => {
+        let message = match &required[found.len()..] {
+          [argument] => T.argument_missing(argument),
+          missing => T.arguments_missing(&missing.iter().map(|argument| format!("<{argument}>")).collect::<Vec<String>>().join(" ")),
+        };
+        return Err(self.parse_error(ErrorKind::WrongArgumentCount, message, program_name));
+      },
+      Some(_) => found.len() - required.len(),
+      None if found.len() != expected.len() =>
+        return Err(self.parse_error(ErrorKind::WrongArgumentCount, T.argument_defined_ne_found(expected.len(), found.len()), program_name)),
+      None => 0,
+    };
+
+    let mut arguments = Vec::with_capacity(found.len());
+    let mut names = Vec::with_capacity(found.len());
+    let mut variadic_args = 0..0;
+    for (position, arg) in found.into_iter().enumerate() {
+      let idx = match variadic {
+        Some(v) if position >= v + extra => expected[position + 1 - extra],
+        Some(v) if position >= v => {
+          if position == v {
+            variadic_args.start = arguments.len();
+          }
+          variadic_args.end = arguments.len() + 1;
+          expected[v]
+        },
+        _ => expected[position],
+      };
+      match self.alternatives.iter().find(|(argument, _)| *argument == idx).and_then(|(_, alias)| self.find_option_def(alias)) {
+        Some(od) => {
+          self.check_valid_value(od, self.argument_names[idx], &arg, program_name)?;
+          Self::store(od, arg, options);
+        },
+        None => {
+          arguments.push(arg);
+          names.push(self.argument_names[idx]);
+        },
+      }
+    }
+    Ok((arguments, names, variadic_args))
+  }
+
+  /// Returns the number of arguments expected on the commandline, which does not include the
+  /// alternative arguments whose options were given
+  #[inline]
+  fn argument_count(&self, options:&Options) -> usize {
+    self.argument_names.len() - self.alternatives.iter().filter(|(_, alias)| options.contains_key(alias)).count()
+  }
+
+  /// Returns the option of the alternative argument, if the option was given on the commandline
+  #[inline]
+  fn given_alternative(&self, idx:usize, options:&Options) -> Option<&OptionDef> {
+    let (_, alias) = self.alternatives.iter().find(|(argument, _)| *argument == idx)?;
+    options.contains_key(alias).then(|| self.find_option_def(alias)).flatten()
+  }
+
+  /// Returns the help option if the arg is `/?` and `/?` requests help
+  #[inline]
+  fn find_slash_help(&self, arg:&str) -> Option<&OptionDef> {
+    match self.slash_help && arg == SLASH_HELP {
+      true => self.option_defs.iter().find(|od| od.help),
+      false => None,
+    }
+  }
+
+  /// Removes the OptionDef at the index, and its aliases
+  fn remove_option_def(&mut self, idx:usize) -> OptionDef {
+    self.alias_table.retain(|(_, i)| *i != idx);
+    for (_, i) in self.alias_table.iter_mut() {
+      if *i > idx {
+        *i -= 1;
+      }
+    }
+    self.option_defs.remove(idx)
+  }
+
+  /// Returns the OptionDef for the alias, to change its definition
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined, unless the definition was created with `builder`
+  #[inline]
+  fn option_def_mut(&mut self, alias:&'static str) -> Option<&mut OptionDef> {
+    match self.alias_table.binary_search_by(|(a, _)| (*a).cmp(alias)) {
+      Ok(pos) => Some(&mut self.option_defs[self.alias_table[pos].1]),
+      Err(_) => {
+        self.def_failed(DefErrorKind::NotDefined, T.option_not_defined(alias));
+        None
+      },
+    }
+  }
+
+  /// Panics with the message of an error in the definition, or keeps the first error for `build`
+  /// if the definition was created with `builder`
+  pub(crate) fn def_failed(&mut self, kind:DefErrorKind, message:String) {
+    match &mut self.def_error {
+      Some(_) => {},
+      error @ None if self.defer_errors => *error = Some(DefError::new(kind, message)),
+      None => panic!("{}", message),
+    }
+  }
+
+  /// Passes the warning to the warning sink
+  #[inline]
+  fn warn(&self, kind:WarningKind, message:String) {
+    let warning = Warning::new(kind, message);
+    match &self.warning_sink {
+      Some(sink) => sink(&warning),
+      #[cfg(feature = "std")]
+      None => self.print_err(&warning),
+      #[cfg(not(feature = "std"))]
+      None => {},
+    }
+  }
+
   #[inline]
-  fn find_option_def(&self, option:&str) -> Option<&OptionDef> {
-    let od_idx = self.option_def_map.get(option)?;
-    Some(&self.option_defs[*od_idx])
+  pub(crate) fn parse_error(&self, kind:ErrorKind, message:String, program_name:&str) -> ParseError {
+    let mut error = ParseError::new(kind, message, self.usage_for(program_name, Stream::Stderr));
+    if self.json_errors {
+      error = error.with_json();
+    }
+    if !self.concise_errors {
+      return error;
+    }
+    match self.option_defs.iter().find(|od| od.help) {
+      Some(od) => error.with_footer(T.error_try_help(program_name, od.canonical_alias())),
+      None => error.with_footer(String::default()),
+    }
   }
 
+  /// Adds the default values of the options not found on the commandline. Options are visited in
+  /// the order they were defined, so the error for a missing required option is deterministic.
+  /// Returns the aliases of the options whose value is their default value, and of the options whose
+  /// value was given on the commandline.
   #[inline]
-  fn add_default_options(&self, options: &mut HashMap<String, String>, usage: &str, ){
-    for option in self.option_def_map.keys() {
-      if !options.contains_key(*option) {
-        if let Some(od) = self.find_option_def(&option) {
-          let default = od.default_value.expect(&format_usage(&T.option_required(option), usage));
-          options.insert(option.to_string(), default.to_string());
+  fn add_default_options(&self, env:&Environment, options: &mut Options, program_name: &str)
+                         -> Result<(Vec<&'static str>, Vec<&'static str>), ParseError> {
+    let mut defaults = Vec::default();
+    let mut given = Vec::default();
+    for od in &self.option_defs {
+      // the value found, which is None for the value given on the commandline, as it is already stored
+      let mut value:Option<Option<Arc<str>>> = None;
+      for source in self.precedence.iter().filter(|source| !od.excluded_sources.contains(source)) {
+        if *source == Source::Default && od.default_value.is_some() {
+          defaults.extend(&od.aliases);
+        }
+        value = match source {
+          Source::CommandLine => options.contains_key(od.aliases[0]).then_some(None),
+          Source::Env => self.env_value(env, od, program_name)?.map(|value| Some(Arc::from(value))),
+          Source::Config => self.config_value(od, program_name)?.map(|value| Some(Arc::from(value))),
+          Source::Default => od.default_value.map(|value| Some(Arc::from(value))),
+        };
+        if value.is_some() {
+          break;
         }
       }
+      match (value, od.env) {
+        (Some(Some(value)), _) => { Self::store(od, value, options); },
+        (Some(None), _) => given.extend(&od.aliases),
+        (None, Some(var)) if od.env_only =>
+          return Err(self.parse_error(ErrorKind::MissingRequiredOption, T.env_var_required(var), program_name)),
+        (None, _) =>
+          return Err(self.parse_error(ErrorKind::MissingRequiredOption, T.option_required(od.aliases[0]), program_name).with_option(od.aliases[0])),
+      }
+    }
+    Ok((defaults, given))
+  }
+
+  /// Returns the option's config value, if it has one
+  #[inline]
+  fn config_value(&self, od:&OptionDef, program_name:&str) -> Result<Option<String>, ParseError> {
+    let Some(value) = self.config.get(od.key()) else {
+      return Ok(None);
+    };
+    self.check_valid_values(od, od.key(), value, program_name)?;
+    Ok(Some(value.clone()))
+  }
+
+  /// Checks that a comma separated value has a number of values within the option's arity, and that
+  /// each is valid. Options that take one value are checked as a whole.
+  #[inline]
+  fn check_valid_values(&self, od:&OptionDef, name:&str, value:&str, program_name:&str) -> Result<(), ParseError> {
+    if od.arity == (1..=1) {
+      return self.check_valid_value(od, name, value, program_name);
+    }
+    let values:Vec<&str> = if value.is_empty() { Vec::default() } else { value.split(VALUE_DELIMITER).collect() };
+    if !od.arity.contains(&values.len()) {
+      return Err(self.parse_error(ErrorKind::InvalidValue, od.arity_message(name, values.len()), program_name).with_option(name));
+    }
+    values.iter().try_for_each(|value| self.check_valid_value(od, name, value, program_name))
+  }
+
+  /// Checks that the value is one of the option's valid values, if it has any
+  #[inline]
+  fn check_valid_value(&self, od:&OptionDef, name:&str, value:&str, program_name:&str) -> Result<(), ParseError> {
+    if !od.valid_values.is_empty() && !od.valid_values.contains(&value) {
+      return Err(self.parse_error(ErrorKind::InvalidValue,
+        T.option_invalid_value(name, value, &od.valid_values.join(", ")), program_name).with_option(name));
     }
+    if let Some(Err(error)) = od.validator.as_ref().map(|validator| validator(value)) {
+      return Err(self.parse_error(ErrorKind::InvalidValue, T.option_cannot_convert(name, value, &error), program_name).with_option(name));
+    }
+    Ok(())
+  }
+
+  /// Returns the extra args from the environment variable set with `args_env_var`
+  #[inline]
+  fn env_args(&self, env:&Environment, program_name:&str) -> Result<Vec<String>, ParseError> {
+    let Some((var, line)) = self.args_env_var.and_then(|var| Some((var, env.var(var)?))) else {
+      return Ok(Vec::default());
+    };
+    split_posix(&line).ok_or_else(|| self.parse_error(ErrorKind::InvalidEnvArgs, T.env_var_unterminated(var), program_name))
   }
 
+  /// Returns the value of the option's environment variable, if it has one and it is set
   #[inline]
-  fn parse_option(&self, option: String, value: Option<&String>, usage: &str, options: &mut HashMap<String, String>) -> bool {
-    let mut skip = false;
+  fn env_value(&self, env:&Environment, od:&OptionDef, program_name:&str) -> Result<Option<String>, ParseError> {
+    let Some((var, value)) = od.env.and_then(|var| Some((var, env.var(var)?))) else {
+      return Ok(None);
+    };
+    self.check_valid_values(od, var, &value, program_name)?;
+    Ok(Some(value))
+  }
+
 
+
+  /// Parses an option, taking its values from the attached value, then the args following it.
+  /// Returns the OptionDefs found, several for a group of flags, with the alias each was given with.
+  #[inline]
+  fn parse_option(&self, option: String, attached: Option<String>, args: &mut Peekable<impl Iterator<Item=String>>, program_name: &str,
+                  options: &mut Options) -> Result<Found<'_>, ParseError> {
+    let mut found = Vec::default();
     if let Some(option_def) = self.find_option_def(&option) {
-      let val = if option_def.value_name.is_none() {
-        TRUE
+      found.push((option_def, option.clone()));
+      self.option_found(option_def, &option, program_name)?;
+      let first = if option_def.value_name.is_none() {
+        Self::store(option_def, Self::flag_value(option_def, &option, options), options)
       } else {
-        if value.is_none() {
-          panic_msg(format_usage(&T.option_value_required(&option), usage));
-        }
-        skip=true;
-        value.unwrap()
+        let values = self.take_values(option_def, &option, attached, args, program_name)?;
+        Self::store_values(option_def, values, options)
       };
-      for alias in &option_def.aliases {
-        if options.insert(alias.to_string(), val.to_string()).is_some() {
-          panic_msg(format_usage(&T.option_multiple_found(alias), usage));
-        }
+      if !first && !option_def.repeatable() {
+        return Err(self.parse_error(ErrorKind::MultipleOccurrences, T.option_multiple_found(option_def.aliases[0]), program_name).with_option(&option));
       }
     } else if !option.starts_with(LONG_OPTION) && option.starts_with(SHORT_OPTION){
+      // short options are ASCII, so a group with any other character can only be split into
+      // undefined flags, possibly in the middle of a grapheme cluster
+      if !option.is_ascii() {
+        return Err(self.parse_error(ErrorKind::NonAsciiFlags, T.option_non_ascii_flags(&option), program_name));
+      }
       let flags = option.trim_start_matches(SHORT_OPTION);
-      for f in flags.chars() {
+      for (idx, f) in flags.char_indices() {
         let flag = format!("-{f}");
-        let flag_def = self.find_option_def(&flag).expect(&format_usage(&T.option_not_defined(&flag), usage));
-          if flag_def.value_name.is_none() {
-            if options.insert(flag, TRUE.to_string()).is_some() {
-              panic_msg(format_usage(&T.option_multiple_flags(f),usage));
-            }
-          } else {
-            panic_msg(format_usage(&T.option_invalid_flag(&flag),usage));
+        let flag_def = self.find_option_def(&flag)
+          .ok_or_else(|| self.parse_error(ErrorKind::OptionNotDefined, T.option_not_defined(&flag), program_name).with_option(&flag))?;
+        self.option_found(flag_def, &flag, program_name)?;
+        found.push((flag_def, flag.clone()));
+        if flag_def.value_name.is_none() {
+          let val = Self::flag_value(flag_def, &flag, options);
+          if !Self::store(flag_def, val, options) && !flag_def.repeatable() {
+            return Err(self.parse_error(ErrorKind::MultipleOccurrences, T.option_multiple_flags(f), program_name).with_option(&format!("-{f}")));
+          }
+        } else {
+          // like tar and getopt, an option that takes a value ends the group, and takes the rest of
+          // the group as its value, or the args following it, e.g. -xvf file or -xvffile
+          let rest = &flags[idx + 1..];
+          let attached = if rest.is_empty() { attached } else { Some(rest.to_string()) };
+          let values = self.take_values(flag_def, &flag, attached, args, program_name)?;
+          if !Self::store_values(flag_def, values, options) {
+            return Err(self.parse_error(ErrorKind::MultipleOccurrences, T.option_multiple_found(flag_def.aliases[0]), program_name).with_option(&flag));
           }
+          break;
+        }
       }
     } else {
-      panic_msg(format_usage(&T.option_not_defined(&option), usage));
+      return Err(self.parse_error(ErrorKind::OptionNotDefined, T.option_not_defined(&option), program_name).with_option(&option));
     }
-    skip
+    Ok(found)
+  }
+
+  /// Takes the option's values from the attached value, then the args following it, checking each
+  /// is valid
+  #[inline]
+  fn take_values(&self, option_def:&OptionDef, option:&str, attached:Option<String>, args:&mut Peekable<impl Iterator<Item=String>>,
+                 program_name:&str) -> Result<Vec<String>, ParseError> {
+    if option_def.optional_value {
+      // like getopt, an optional value is never taken from the next arg
+      let value = attached.unwrap_or_default();
+      if !value.is_empty() {
+        self.check_valid_value(option_def, option, &value, program_name)?;
+      }
+      return Ok(vec![value]);
+    }
+    let min = *option_def.arity.start();
+    let max = if option_def.lazy { min } else { *option_def.arity.end() };
+    // `--name=` is likely a mistake, but `--name ''` is an explicit empty value
+    if !option_def.allow_empty && attached.as_deref() == Some("") {
+      return Err(self.parse_error(ErrorKind::MissingValue, T.option_value_empty(option), program_name).with_option(option));
+    }
+    let mut values:Vec<String> = attached.into_iter().collect();
+    // a fixed or lazy number of values are taken whatever they are, and a range stops at the next
+    // option. All of them stop at `--`, which ends the options.
+    while let Some(value) = args.next_if(|arg| values.len() < max && arg != LONG_OPTION
+                                                && (min == max || !self.is_defined_option(arg))) {
+      values.push(value);
+    }
+    if values.len() < min {
+      return Err(self.parse_error(ErrorKind::MissingValue, option_def.arity_message(option, values.len()), program_name).with_option(option));
+    }
+    for value in &values {
+      self.check_valid_value(option_def, option, value, program_name)?;
+    }
+    Ok(values)
+  }
+
+  /// Returns true if the arg is a defined option, with or without an attached value
+  fn is_defined_option(&self, arg:&str) -> bool {
+    self.find_option_def(arg).or_else(|| self.find_slash_help(arg)).is_some()
+      || self.split_attached_value(arg).is_some_and(|(option, _)| self.find_option_def(&option).is_some())
+  }
+
+  /// Replaces an abbreviated long option with the option it is a prefix of, keeping an attached
+  /// value, when long options can be abbreviated. The aliases are kept sorted, so the options that
+  /// start with the abbreviation are found with a binary search instead of scanning the aliases.
+  fn expand_abbreviation(&self, arg:String, program_name:&str) -> Result<String, ParseError> {
+    if !self.infer_long_options || !arg.starts_with(LONG_OPTION) || arg == LONG_OPTION || self.find_option_def(&arg).is_some() {
+      return Ok(arg);
+    }
+    let (name, value) = match self.split_attached_value(&arg) {
+      Some((name, _)) => arg.split_at(name.len()),
+      None => (arg.as_str(), ""),
+    };
+    let start = self.alias_table.partition_point(|(alias, _)| *alias < name);
+    let mut candidates:Vec<(&str, usize)> = Vec::default();
+    for (alias, od_idx) in self.alias_table[start..].iter().take_while(|(alias, _)| alias.starts_with(name)) {
+      if candidates.iter().all(|(_, idx)| idx != od_idx) {
+        candidates.push((alias, *od_idx));
+      }
+    }
+    match candidates.as_slice() {
+      [] => Ok(arg),
+      [(alias, _)] => Ok(format!("{alias}{value}")),
+      _ => {
+        let aliases:Vec<&str> = candidates.iter().map(|(alias, _)| *alias).collect();
+        Err(self.parse_error(ErrorKind::AmbiguousOption, T.option_ambiguous(name, &aliases.join(", ")), program_name)
+          .with_option(name).with_suggestions(&aliases))
+      },
+    }
+  }
+
+  /// Returns true if the arg is a negative number that is an argument rather than an option
+  fn is_negative_number(&self, arg:&str) -> bool {
+    let Some(number) = arg.strip_prefix(SHORT_OPTION) else {
+      return false;
+    };
+    self.negative_numbers
+      && number.starts_with(|c:char| c.is_ascii_digit() || c == '.')
+      && number.parse::<f64>().is_ok()
+      && self.find_option_def(arg).is_none()
+      && self.find_option_def(&arg[..2]).is_none()
+  }
+
+  /// Splits an arg into an option and the value attached to it, such as `--file=in.txt`. Returns
+  /// `None` if the arg is not an option with an attached value.
+  fn split_attached_value(&self, arg:&str) -> Option<(String, String)> {
+    let separators:&[char] = match self.colon_separator {
+      true => &[VALUE_SEPARATOR, COLON_VALUE_SEPARATOR],
+      false => &[VALUE_SEPARATOR],
+    };
+    if arg.starts_with(LONG_OPTION) && self.find_option_def(arg).is_none() {
+      let (option, value) = arg.split_once(separators)?;
+      return Some((option.to_string(), value.to_string()));
+    }
+    let (name, value) = arg.strip_prefix(SLASH_OPTION).filter(|_| self.colon_separator)?.split_once(separators)?;
+    let option = match name.chars().count() {
+      1 => format!("{SHORT_OPTION}{name}"),
+      _ => format!("{LONG_OPTION}{name}"),
+    };
+    self.find_option_def(&option)?;
+    Some((option, value.to_string()))
+  }
+
+  /// Parses a group of toggles with the `+` prefix, such as `+xv`. Returns None if the arg is not
+  /// a group of toggles, because its first character is not a toggle.
+  fn parse_plus_toggles(&self, arg:&str, program_name:&str, options:&mut Options)
+                        -> Result<Option<Found<'_>>, ParseError> {
+    let Some(toggles) = arg.strip_prefix(PLUS_OPTION) else {
+      return Ok(None);
+    };
+    let mut found = Vec::default();
+    for t in toggles.chars() {
+      let toggle = format!("{PLUS_OPTION}{t}");
+      let toggle_def = match self.find_option_def(&format!("{SHORT_OPTION}{t}")) {
+        Some(od) if od.toggle.is_some() => od,
+        _ if found.is_empty() => return Ok(None),
+        _ => return Err(self.parse_error(ErrorKind::OptionNotDefined, T.option_not_defined(&toggle), program_name).with_option(&toggle)),
+      };
+      self.option_found(toggle_def, &toggle, program_name)?;
+      if !Self::store(toggle_def, toggle_def.toggle.unwrap_or_default().to_string(), options) {
+        return Err(self.parse_error(ErrorKind::MultipleOccurrences, T.option_multiple_found(toggle_def.aliases[0]), program_name).with_option(&toggle));
+      }
+      found.push((toggle_def, toggle));
+    }
+    Ok(Some(found).filter(|found| !found.is_empty()))
+  }
+
+  /// Parses an option with an attached value. Flags do not take a value.
+  fn parse_attached_option(&self, option:String, value:String, args:&mut Peekable<impl Iterator<Item=String>>, program_name:&str,
+                           options:&mut Options) -> Result<Found<'_>, ParseError> {
+    match self.find_option_def(&option) {
+      Some(od) if od.value_name.is_none() => Err(self.parse_error(ErrorKind::InvalidValue, T.option_value_not_allowed(&option), program_name).with_option(&option)),
+      _ => self.parse_option(option, Some(value), args, program_name, options),
+    }
+  }
+
+  /// Rejects options that can only be set from the environment, then raises the warnings and runs
+  /// the action for an option found on the commandline
+  #[inline]
+  fn option_found(&self, option_def:&OptionDef, option:&str, program_name:&str) -> Result<(), ParseError> {
+    if let (true, Some(var)) = (option_def.env_only, option_def.env) {
+      return Err(self.parse_error(ErrorKind::EnvOnlyOption, T.option_env_only(option, var), program_name).with_option(option));
+    }
+    if option_def.deprecated {
+      self.warn(WarningKind::DeprecatedOption, T.option_deprecated(option));
+    }
+    if let Some(action) = &option_def.action {
+      action(self, program_name);
+    }
+    Ok(())
+  }
+
+  /// Returns the value of a flag found on the commandline. Counters are incremented, toggles have
+  /// the value of their `-` prefix, paired flags are false if given with their disabling alias,
+  /// and other flags are true.
+  #[inline]
+  fn flag_value(option_def:&OptionDef, option:&str, options:&Options) -> String {
+    if option_def.counting {
+      let count = options.get(option_def.aliases[0]).and_then(|count| count.parse::<u32>().ok()).unwrap_or(0);
+      count.saturating_add(1).to_string()
+    } else if let Some(plus) = option_def.toggle {
+      (!plus).to_string()
+    } else if let Some(disable) = option_def.disable {
+      (disable != option).to_string()
+    } else {
+      TRUE.to_string()
+    }
+  }
+
+  /// Stores the values of an option that takes values. The values of an option that takes several
+  /// are also stored as a list, so a value can contain `VALUE_DELIMITER`. Returns false if the
+  /// option was already found.
+  #[inline]
+  fn store_values(option_def:&OptionDef, mut values:Vec<String>, options:&mut Options) -> bool {
+    if option_def.arity == (1..=1) {
+      return Self::store(option_def, values.pop().unwrap_or_default(), options);
+    }
+    let value:Arc<str> = Arc::from(values.join(&VALUE_DELIMITER.to_string()));
+    let values:Values = values.into_iter().map(Arc::from).collect();
+    let first = !options.contains_key(option_def.aliases[0]);
+    for alias in &option_def.aliases {
+      options.insert_list(alias.to_string(), value.clone(), values.clone());
+    }
+    first
   }
-}
 
+  /// Stores the value once, shared by each alias of the option. Returns false if the option was
+  /// already found.
+  #[inline]
+  fn store(option_def:&OptionDef, val:impl Into<Arc<str>>, options:&mut Options) -> bool {
+    let val = val.into();
+    let first = !options.contains_key(option_def.aliases[0]);
+    for alias in &option_def.aliases {
+      options.insert(alias.to_string(), val.clone());
+    }
+    first
+  }
+}
@@ -0,0 +1,149 @@
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use clap::{Arg, ArgAction, Command};
+use super::cl_def::CommandLineDef;
+use super::{leak, LONG_OPTION};
+
+/// Conversions between a CommandLineDef and the subset of clap's `Command` model that it can
+/// express: flags, counters, options with a single value, and required positional arguments.
+impl CommandLineDef {
+  /// Creates a clap `Command` from this definition, so clap ecosystem tooling such as
+  /// `clap_complete` and `clap_mangen` can be used with it. The built-in help option is left to
+  /// clap.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the program
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let command = CommandLineDef::new()
+  ///   .add_flag(vec!["-v","--verbose"], "Verbose output")
+  ///   .add_option(vec!["-o","--output"], Some("file"), Some("out.txt"), "The output file")
+  ///   .add_argument("input")
+  ///   .to_clap_command("program");
+  ///
+  /// let matches = command.get_matches_from(vec!["program", "-v", "in.txt"]);
+  /// assert_eq!(matches.get_flag("verbose"), true);
+  /// assert_eq!(matches.get_one::<String>("output").unwrap(), "out.txt");
+  /// assert_eq!(matches.get_one::<String>("input").unwrap(), "in.txt");
+  /// ```
+  pub fn to_clap_command(&self, name:&'static str) -> Command {
+    let mut command = Command::new(name);
+    for od in &self.option_defs {
+      if od.help {
+        continue;
+      }
+      let longs:Vec<&'static str> = od.aliases.iter().filter_map(|alias| alias.strip_prefix(LONG_OPTION)).collect();
+      let shorts:Vec<char> = od.aliases.iter()
+        .filter(|alias| !alias.starts_with(LONG_OPTION))
+        .filter_map(|alias| alias.chars().nth(1))
+        .collect();
+      let id = longs.first().copied().unwrap_or_else(|| &od.aliases[0][1..]);
+
+      let mut arg = Arg::new(id).help(od.description);
+      if let Some((short, short_aliases)) = shorts.split_first() {
+        arg = arg.short(*short).visible_short_aliases(short_aliases.iter().copied());
+      }
+      if let Some((long, long_aliases)) = longs.split_first() {
+        arg = arg.long(*long).visible_aliases(long_aliases.iter().copied());
+      }
+      arg = match od.value_name {
+        None if od.counting => arg.action(ArgAction::Count),
+        None => arg.action(ArgAction::SetTrue),
+        Some(value_name) => {
+          let arg = arg.action(ArgAction::Set).value_name(value_name).num_args(od.arity.clone());
+          match od.default_value {
+            Some(default) => arg.default_value(default),
+            None => arg.required(true),
+          }
+        }
+      };
+      command = command.arg(arg);
+    }
+    for argument_name in &self.argument_names {
+      command = command.arg(Arg::new(*argument_name).required(true));
+    }
+    command
+  }
+
+  /// Creates a CommandLineDef from a clap `Command`. Flags, counters and options are converted, and
+  /// positional arguments are converted to required arguments. Options that are neither required
+  /// nor have a default value default to an empty value. clap's own help and version arguments are
+  /// skipped.
+  ///
+  /// Arguments whose action is not supported are skipped too, so giving them is an
+  /// [`ErrorKind::OptionNotDefined`](crate::ErrorKind::OptionNotDefined):
+  ///
+  /// * `ArgAction::SetFalse`, as a flag is always false when it is not given, and true when it is
+  /// * `ArgAction::Append`, as an option is given at most once
+  ///
+  /// # Arguments
+  ///
+  /// * `command` - The clap command to convert
+  ///
+  /// # Panics
+  ///
+  /// * Panics if an alias is defined more than once, including `-h` and `--help`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use clap::{Arg, ArgAction, Command};
+  /// use cl_parse::CommandLineDef;
+  /// let command = Command::new("program")
+  ///   .arg(Arg::new("verbose").short('v').long("verbose").action(ArgAction::SetTrue))
+  ///   .arg(Arg::new("output").short('o').value_name("file").default_value("out.txt"))
+  ///   .arg(Arg::new("input").required(true));
+  ///
+  /// let args=vec!["program".to_string(), "in.txt".to_string(), "--verbose".to_string()];
+  /// let cl = CommandLineDef::from_clap_command(&command).parse(args.into_iter());
+  ///
+  /// let verbose:bool = cl.option("-v");
+  /// assert_eq!(verbose, true);
+  ///
+  /// let output:String = cl.option("-o");
+  /// assert_eq!(output, "out.txt");
+  ///
+  /// let input:String = cl.argument(0);
+  /// assert_eq!(input, "in.txt");
+  /// ```
+  pub fn from_clap_command(command:&Command) -> Self {
+    let mut cl_def = CommandLineDef::new();
+    for arg in command.get_arguments() {
+      if arg.is_positional() || matches!(arg.get_action(), ArgAction::Help | ArgAction::HelpShort | ArgAction::HelpLong | ArgAction::Version
+        | ArgAction::SetFalse | ArgAction::Append) {
+        continue;
+      }
+      let mut aliases:Vec<&'static str> = Vec::default();
+      for short in arg.get_short().into_iter().chain(arg.get_all_short_aliases().unwrap_or_default()) {
+        aliases.push(leak(format!("-{short}")));
+      }
+      for long in arg.get_long().into_iter().chain(arg.get_all_aliases().unwrap_or_default()) {
+        aliases.push(leak(format!("--{long}")));
+      }
+      let description = leak(arg.get_help().map(|help| help.to_string()).unwrap_or_default());
+
+      if matches!(arg.get_action(), ArgAction::Count) {
+        cl_def.add_counter(aliases, description);
+      } else if matches!(arg.get_action(), ArgAction::SetTrue) {
+        cl_def.add_flag(aliases, description);
+      } else {
+        let value_name = arg.get_value_names()
+          .and_then(|value_names| value_names.first())
+          .map(|value_name| value_name.as_str())
+          .unwrap_or_else(|| arg.get_id().as_str());
+        let default = arg.get_default_values().first().and_then(|default| default.to_str())
+          .or(if arg.is_required_set() { None } else { Some("") });
+        cl_def.add_option(aliases, Some(leak(value_name.to_string())), default.map(|d| leak(d.to_string())), description);
+      }
+    }
+    for arg in command.get_positionals() {
+      cl_def.add_argument(leak(arg.get_id().to_string()));
+    }
+    cl_def
+  }
+}
@@ -0,0 +1,116 @@
+use alloc::vec;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use super::cl_def::CommandLineDef;
+use super::command_line::CommandLine;
+use crate::text::T;
+
+const LONG_COLOR: &str = "--color";
+const AUTO: &str = "auto";
+const ALWAYS: &str = "always";
+const NEVER: &str = "never";
+
+/// When to color the output, as chosen with the `--color` option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+  /// Color the output when it is written to a terminal, unless `NO_COLOR` is set
+  #[default]
+  Auto,
+  /// Always color the output
+  Always,
+  /// Never color the output
+  Never,
+}
+
+impl ColorChoice {
+  /// Returns true if the output should be colored. `Auto` colors the output when stdout is a
+  /// terminal and the `NO_COLOR` environment variable is not set, or when `CLICOLOR_FORCE` is set.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::ColorChoice;
+  /// assert_eq!(ColorChoice::Always.enabled(), true);
+  /// assert_eq!(ColorChoice::Never.enabled(), false);
+  /// ```
+  #[cfg(feature = "std")]
+  pub fn enabled(self) -> bool {
+    use std::io::IsTerminal;
+    let set = |var| std::env::var_os(var).is_some_and(|value| !value.is_empty() && value != "0");
+    match self {
+      ColorChoice::Always => true,
+      ColorChoice::Never => false,
+      ColorChoice::Auto => set("CLICOLOR_FORCE") || (!set("NO_COLOR") && std::io::stdout().is_terminal()),
+    }
+  }
+}
+
+impl FromStr for ColorChoice {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      AUTO => Ok(ColorChoice::Auto),
+      ALWAYS => Ok(ColorChoice::Always),
+      NEVER => Ok(ColorChoice::Never),
+      _ => Err(()),
+    }
+  }
+}
+
+impl Display for ColorChoice {
+  fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+    match self {
+      ColorChoice::Auto => write!(f, "{AUTO}"),
+      ColorChoice::Always => write!(f, "{ALWAYS}"),
+      ColorChoice::Never => write!(f, "{NEVER}"),
+    }
+  }
+}
+
+impl CommandLineDef {
+  /// Adds the standard `--color <when>` option, accepting `auto`, `always` or `never` and
+  /// defaulting to `auto`. Use [`CommandLine::color`] to get the choice.
+  ///
+  /// # Panics
+  ///
+  /// * Panics if `--color` is already defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{ColorChoice, CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.add_color();
+  ///
+  /// let args=vec!["program".to_string(), "--color".to_string(), "never".to_string()];
+  /// assert_eq!(cl_def.parse(args.into_iter()).color(), ColorChoice::Never);
+  ///
+  /// let args=vec!["program".to_string(), "--color".to_string(), "sometimes".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  /// ```
+  #[inline]
+  pub fn add_color(&mut self) -> &mut Self {
+    self.add_option(vec![LONG_COLOR], Some(T.color_value_name()), Some(AUTO), T.color_description())
+      .valid_values(LONG_COLOR, vec![AUTO, ALWAYS, NEVER])
+  }
+}
+
+impl CommandLine {
+  /// Returns the choice made with the `--color` option added by `CommandLineDef::add_color`, or
+  /// `Auto` if the option is not defined.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{ColorChoice, CommandLineDef};
+  /// let args=vec!["program".to_string()];
+  /// let cl = CommandLineDef::new().add_color().parse(args.into_iter());
+  /// assert_eq!(cl.color(), ColorChoice::Auto);
+  /// ```
+  #[inline]
+  pub fn color(&self) -> ColorChoice {
+    self.value(LONG_COLOR).and_then(|when| when.parse().ok()).unwrap_or_default()
+  }
+}
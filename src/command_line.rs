@@ -1,15 +1,119 @@
-use std::collections::HashMap;
-use std::str::FromStr;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::ops::Range;
+use core::str::FromStr;
+use crate::shell::quote_posix;
 use crate::text::T;
+use crate::{from_str_lenient, program_stem, LONG_OPTION, LONG_QUIET, LONG_VERBOSE, PLUS_OPTION, SECTION_SEPARATOR, SHORT_OPTION, TRUE,
+            VALUE_DELIMITER};
+#[cfg(feature = "std")]
+use crate::VALUE_SEPARATOR;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::process::Command;
+
+/// The values of an option that takes several values, in the order they were given
+pub(crate) type Values = Arc<[Arc<str>]>;
+
+/// The options parsed from the commandline by alias. The aliases of an option share its value, so
+/// the value is stored once however many aliases the option has. The values of an option given
+/// several values on the commandline are also kept as a list, so a value can contain
+/// `VALUE_DELIMITER`.
+#[derive(Debug, Default)]
+pub(crate) struct Options {
+  /// The value of each option, with several values joined by `VALUE_DELIMITER`
+  values:BTreeMap<String, Arc<str>>,
+  /// The values of each option given several values on the commandline
+  lists:BTreeMap<String, Values>,
+}
+
+impl Options {
+  /// Returns the value of the option
+  #[inline]
+  pub(crate) fn get(&self, alias:&str) -> Option<&Arc<str>> {
+    self.values.get(alias)
+  }
+
+  /// Returns true if the option has a value
+  #[inline]
+  pub(crate) fn contains_key(&self, alias:&str) -> bool {
+    self.values.contains_key(alias)
+  }
+
+  /// Sets the value of the option, replacing its values if it had several
+  #[inline]
+  pub(crate) fn insert(&mut self, alias:String, value:Arc<str>) {
+    self.lists.remove(&alias);
+    self.values.insert(alias, value);
+  }
+
+  /// Sets the values of an option given several values, with the values joined by
+  /// `VALUE_DELIMITER` as its value
+  #[inline]
+  pub(crate) fn insert_list(&mut self, alias:String, value:Arc<str>, values:Values) {
+    self.values.insert(alias.clone(), value);
+    self.lists.insert(alias, values);
+  }
+}
+
+/// How a defined option is written on the commandline, for rendering the commandline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OptionForm {
+  /// The option is followed by its value
+  Value,
+  /// The optional value is attached to the option, which is written alone when its value is empty
+  Attached,
+  /// The flag is written when it is true
+  Flag,
+  /// The flag is written once for each time it was counted
+  Counter,
+  /// The first alias is written when the flag is true, and the disabling alias when it is false
+  Pair(&'static str),
+  /// The toggle is written with a `+` prefix when its value is the value given by `+`, which is
+  /// held, and with its `-` alias otherwise
+  Toggle(bool),
+}
 
 /// Stores the parsed command line
+#[derive(Debug)]
 pub struct CommandLine {
   /// Commandline argument 0 the program name
   program_name: String,
+  /// The path of the program, argument 0 of the program's commandline. For a subcommand this is the
+  /// path of the program, not the subcommand's program name.
+  program_path: String,
   /// The options and values parsed from the command line
-  options: HashMap<String, String>,
+  options: Options,
   /// The remaining non-option arguments
   arguments: Vec<String>,
+  /// The name of the argument each of the arguments was given for
+  argument_names: Vec<&'static str>,
+  /// The canonical name of each option with a value, in the order the options were defined
+  option_names: Vec<String>,
+  /// The canonical name and form of each defined option, in the order they were defined
+  option_forms: Vec<(&'static str, OptionForm)>,
+  /// The indexes of the arguments given for the variadic argument, if one is defined
+  variadic: Range<usize>,
+  /// The args after `--`, as they were given
+  rest: Vec<String>,
+  /// The canonical name and commandline of the subcommand, if one was specified
+  subcommand: Option<(&'static str, Box<CommandLine>)>,
+  /// The names of the options set with `set_option`, whose aliases have not been set yet
+  set_names: Vec<String>,
+  /// The aliases of the options whose value is their default value
+  defaults: Vec<&'static str>,
+  /// The aliases of the options whose value was given on the commandline
+  given: Vec<&'static str>,
+  /// The positions of the args of each option's occurrences, by alias
+  indices: BTreeMap<&'static str, Vec<usize>>,
+  /// The alias each option was last given with on the commandline, by alias
+  matched: BTreeMap<&'static str, Arc<str>>,
 }
 
 impl CommandLine {
@@ -34,11 +138,23 @@ impl CommandLine {
   ///   assert_eq!(true, cl.program_name().is_empty());
   /// ```
   #[inline]
-  pub(crate) fn new(program_name: String, options: HashMap<String, String>, arguments: Vec<String>) -> Self {
+  pub(crate) fn new(program_name: String, options: Options, arguments: Vec<String>) -> Self {
     CommandLine {
+      program_path: program_name.clone(),
       program_name,
       options,
       arguments,
+      argument_names: Vec::default(),
+      option_names: Vec::default(),
+      option_forms: Vec::default(),
+      variadic: 0..0,
+      rest: Vec::default(),
+      subcommand: None,
+      set_names: Vec::default(),
+      defaults: Vec::default(),
+      given: Vec::default(),
+      indices: BTreeMap::default(),
+      matched: BTreeMap::default(),
     }
   }
 
@@ -63,7 +179,7 @@ impl CommandLine {
   /// ```
   #[inline]
   pub fn options(self) -> usize {
-    self.options.len()
+    self.options.values.len()
   }
 
   /// Returns the option for the option key specified
@@ -86,15 +202,276 @@ impl CommandLine {
   ///   // Test Program Name
   ///   assert_eq!(filename, "/file/path".to_string());
   /// ```
-
   #[inline]
   pub fn option<T>(&self, name:&str) -> T
-  where T: FromStr {
-    let option = self.options.get(name).expect(&T.option_not_found(name));
-    match T::from_str(option) {
+  where T: FromStr, T::Err: Display {
+    let option = self.options.get(name).unwrap_or_else(|| panic!("{}", T.option_not_found(name)));
+    match from_str_lenient(option) {
       Ok(t) => t,
-      Err(_) => panic!("{}",T.option_cannot_convert(name, option))
+      Err(e) => panic!("{}",T.option_cannot_convert(name, option, &e.to_string()))
+    }
+  }
+
+  /// Returns the value of the option as it was given, borrowed from the CommandLine, without
+  /// converting it
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - A string slice that holds the name of the option
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("-f"), String::from("/file/path")];
+  ///  let cl = CommandLineDef::new().add_option(vec!["-f","--filename"], Some("filepath"),
+  ///      None, "The file to be parsed").parse(env_args.into_iter());
+  ///
+  ///  assert_eq!(cl.option_str("--filename"), "/file/path");
+  /// ```
+  #[inline]
+  pub fn option_str(&self, name:&str) -> &str {
+    self.value(name).unwrap_or_else(|| panic!("{}", T.option_not_found(name)))
+  }
+
+  /// Returns the elements of a comma separated option value, or the values of an option given
+  /// several values, each converted to the target type. An empty value has no elements.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - A string slice that holds the name of the option
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  /// * Panics if an element cannot be converted, naming the element, counting from 1, and the
+  ///   conversion error
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("--ports"), String::from("80,443")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-p","--ports"], Some("ports"), Some(""), "The ports to listen on")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  let ports:Vec<u16> = cl.values("--ports");
+  ///  assert_eq!(ports, vec![80, 443]);
+  /// ```
+  pub fn values<T>(&self, name:&str) -> Vec<T>
+  where T: FromStr, T::Err: Display {
+    let option = self.options.get(name).unwrap_or_else(|| panic!("{}", T.option_not_found(name)));
+    if option.is_empty() {
+      return Vec::default();
     }
+    self.value_list(name).unwrap_or_default().into_iter().enumerate()
+      .map(|(idx, element)| match from_str_lenient::<T>(element) {
+        Ok(t) => t,
+        Err(e) => panic!("{}", T.option_element_cannot_convert(idx + 1, name, element, &e.to_string())),
+      })
+      .collect()
+  }
+
+  /// Returns the entries of a comma separated list of `key=value` entries, with each value
+  /// converted to the target type. An empty value has no entries.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - A string slice that holds the name of the option
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  /// * Panics if an entry is not written `key=value`
+  /// * Panics if a key is repeated
+  /// * Panics if a value cannot be converted, naming its key and the conversion error
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use std::collections::HashMap;
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("--limits"), String::from("cpu=2,memory=512")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-l","--limits"], Some("limits"), Some(""), "The resource limits")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  let limits:HashMap<String, u32> = cl.map("--limits");
+  ///  assert_eq!(limits["cpu"], 2);
+  ///  assert_eq!(limits["memory"], 512);
+  /// ```
+  #[cfg(feature = "std")]
+  pub fn map<V>(&self, name:&str) -> HashMap<String, V>
+  where V: FromStr, V::Err: Display {
+    let option = self.options.get(name).unwrap_or_else(|| panic!("{}", T.option_not_found(name)));
+    let mut map = HashMap::new();
+    for entry in self.value_list(name).unwrap_or_default().into_iter().filter(|_| !option.is_empty()) {
+      let (key, value) = entry.split_once(VALUE_SEPARATOR).unwrap_or_else(|| panic!("{}", T.option_entry_invalid(name, entry)));
+      let value = from_str_lenient::<V>(value)
+        .unwrap_or_else(|e| panic!("{}", T.option_entry_cannot_convert(key, name, value, &e.to_string())));
+      if map.insert(String::from(key), value).is_some() {
+        panic!("{}", T.option_key_duplicated(name, key));
+      }
+    }
+    map
+  }
+
+  /// Sets the value of an option, replacing the parsed value. Used by the closure set with
+  /// `CommandLineDef::finalize` to store values derived from the parsed ones, so they can be read
+  /// like any other option. When called by that closure, the value is also set for the other
+  /// aliases of a defined option. The name does not need to be a defined option.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the option. e.g. `--jobs`
+  /// * `value` - The value of the option
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program")];
+  ///  let mut cl = CommandLineDef::new().parse(env_args.into_iter());
+  ///  cl.set_option("--jobs", 4);
+  ///
+  ///  let jobs:u32 = cl.option("--jobs");
+  ///  assert_eq!(jobs, 4);
+  /// ```
+  pub fn set_option(&mut self, name:&str, value:impl ToString) {
+    self.options.insert(String::from(name), Arc::from(value.to_string()));
+    self.defaults.retain(|alias| *alias != name);
+    self.given.retain(|alias| *alias != name);
+    self.set_names.push(String::from(name));
+  }
+
+  /// Sets the value of each option set with `set_option` for the option's other aliases, and names
+  /// the options that are not defined
+  pub(crate) fn set_aliases<'a>(&mut self, aliases:impl Fn(&str) -> Option<&'a [&'static str]>) {
+    for name in core::mem::take(&mut self.set_names) {
+      if let (Some(aliases), Some(value)) = (aliases(&name), self.options.get(&name).cloned()) {
+        for alias in aliases {
+          self.options.insert(String::from(*alias), value.clone());
+        }
+        self.defaults.retain(|alias| !aliases.contains(alias));
+        self.given.retain(|alias| !aliases.contains(alias));
+      } else if !self.option_names.contains(&name) {
+        self.option_names.push(name);
+      }
+    }
+  }
+
+  /// Returns true if the option's value is its default value, because it was not given on the
+  /// commandline, in its environment variable or in the config. A value given explicitly is not a
+  /// default value, even if it equals the default value.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - A string slice that holds the name of the option
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("--threads"), String::from("4")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-t","--threads"], Some("count"), Some("4"), "The number of threads")
+  ///   .add_option(vec!["--level"], Some("level"), Some("1"), "The level")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  assert_eq!(cl.is_default("-t"), false);
+  ///  assert_eq!(cl.is_default("--level"), true);
+  /// ```
+  #[inline]
+  pub fn is_default(&self, name:&str) -> bool {
+    self.defaults.contains(&name)
+  }
+
+  /// Returns the positions of the args where the option occurs, in the order they occur. The
+  /// program name is at position 0, and the args of the environment variable set with
+  /// `CommandLineDef::args_env_var` come before the commandline args. The position of a value is
+  /// not included, and the flags of a group, such as `-vvq`, share its position. The slice is
+  /// empty if the option does not occur on the commandline.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - A string slice that holds the name of the option
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args:Vec<String>=["program", "-v", "--level", "2", "-vq"].iter().map(|s| s.to_string()).collect();
+  ///  let cl = CommandLineDef::new()
+  ///   .add_counter(vec!["-v","--verbose"], "Increase the verbosity")
+  ///   .add_flag(vec!["-q"], "Quiet output")
+  ///   .add_option(vec!["--level"], Some("level"), Some("1"), "The level")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  assert_eq!(cl.indices_of("--verbose"), &[1, 4]);
+  ///  assert_eq!(cl.indices_of("--level"), &[2]);
+  ///  assert_eq!(cl.indices_of("-q"), &[4]);
+  ///  assert!(cl.indices_of("-h").is_empty());
+  /// ```
+  #[inline]
+  pub fn indices_of(&self, name:&str) -> &[usize] {
+    self.indices.get(name).map_or(&[], Vec::as_slice)
+  }
+
+  /// Sets the positions of the args of each option's occurrences
+  #[inline]
+  pub(crate) fn set_indices(&mut self, indices:BTreeMap<&'static str, Vec<usize>>) {
+    self.indices = indices;
+  }
+
+  /// Returns the alias the option was given with on the commandline, such as `-f` or `--file`, so
+  /// messages can use the spelling the user typed. A toggle given with its `+` prefix returns
+  /// `+x`, and an abbreviated long option returns the option it was expanded to. If the option
+  /// was given several times, the last alias is returned. Returns None if the option was not given
+  /// on the commandline.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - Any alias of the option
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("-f"), String::from("in.txt")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-f","--file"], Some("file"), None, "The input file")
+  ///   .add_flag(vec!["-v","--verbose"], "Verbose output")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  assert_eq!(cl.matched_alias("--file"), Some("-f"));
+  ///  assert_eq!(cl.matched_alias("--verbose"), None);
+  /// ```
+  #[inline]
+  pub fn matched_alias(&self, name:&str) -> Option<&str> {
+    self.matched.get(name).map(|alias| &**alias)
+  }
+
+  /// Sets the alias each option was given with, by alias
+  #[inline]
+  pub(crate) fn set_matched(&mut self, matched:BTreeMap<&'static str, Arc<str>>) {
+    self.matched = matched;
+  }
+
+  /// Sets the aliases of the options whose value is their default value
+  #[inline]
+  pub(crate) fn set_defaults(&mut self, defaults:Vec<&'static str>) {
+    self.defaults = defaults;
+  }
+
+  /// Sets the aliases of the options whose value was given on the commandline
+  #[inline]
+  pub(crate) fn set_given(&mut self, given:Vec<&'static str>) {
+    self.given = given;
   }
 
   /// Returns the number of arguments parsed
@@ -139,14 +516,281 @@ impl CommandLine {
   /// ```
   #[inline]
   pub fn argument<T>(&self, index:usize)  -> T
-  where T: FromStr {
-    let argument = self.arguments.get(index).expect(&T.argument_invalid_index(index));
-    match T::from_str(argument) {
+  where T: FromStr, T::Err: Display {
+    let argument = self.arguments.get(index).unwrap_or_else(|| panic!("{}", T.argument_invalid_index(index)));
+    match from_str_lenient(argument) {
       Ok(t) => t,
-      Err(_) => panic!("{}",T.argument_cannot_convert(index, argument))
+      Err(e) => panic!("{}",T.argument_cannot_convert(index, argument, &e.to_string()))
     }
   }
 
+  /// Returns the argument by index as it was given, borrowed from the CommandLine, without
+  /// converting it
+  ///
+  /// # Panics
+  ///
+  /// * Panics if there is no argument at the index
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("arg1")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_argument("arg-0")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  assert_eq!(cl.argument_str(0), "arg1");
+  /// ```
+  #[inline]
+  pub fn argument_str(&self, index:usize) -> &str {
+    self.arguments.get(index).unwrap_or_else(|| panic!("{}", T.argument_invalid_index(index)))
+  }
+
+  /// Returns the value of the option, or of the first argument with the name, as it was given, or
+  /// None if neither was parsed. Unlike `option` and `argument`, it does not panic, so generic code
+  /// can probe the parsed commandline.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of an option, or of an argument as it was defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("-f"), String::from("/file/path"), String::from("in.txt")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-f","--filename"], Some("filepath"), None, "The file to be parsed")
+  ///   .add_argument("input")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  assert_eq!(cl.get_raw("--filename"), Some("/file/path"));
+  ///  assert_eq!(cl.get_raw("input"), Some("in.txt"));
+  ///  assert_eq!(cl.get_raw("output"), None);
+  /// ```
+  pub fn get_raw(&self, name:&str) -> Option<&str> {
+    self.value(name).or_else(|| {
+      let index = self.argument_names.iter().position(|argument| *argument == name)?;
+      Some(&self.arguments[index])
+    })
+  }
+
+  /// Returns true if an arg was given for the argument with the name. An argument given with its
+  /// alternative option is an option, not an argument.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the argument as it was defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("in.txt")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_argument("input")
+  ///   .add_variadic_argument("outputs")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  assert!(cl.contains_argument("input"));
+  ///  assert!(!cl.contains_argument("outputs"));
+  /// ```
+  #[inline]
+  pub fn contains_argument(&self, name:&str) -> bool {
+    self.argument_names.contains(&name)
+  }
+
+  /// Returns the names of the arguments given on the commandline, in the order they were defined.
+  /// A variadic argument is named once, however many args were given for it.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("a.txt"), String::from("b.txt")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_variadic_argument("files")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  assert_eq!(cl.argument_names(), vec!["files"]);
+  /// ```
+  pub fn argument_names(&self) -> Vec<&str> {
+    let mut names = self.argument_names.clone();
+    names.dedup();
+    names
+  }
+
+  /// Returns the canonical name of each option with a value, from the commandline, the
+  /// environment, a config file or its default, in the order the options were defined. The
+  /// canonical name is the first long alias, or the first alias if the option has no long alias.
+  /// Use `get_raw` or `option` to read the values.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("-t"), String::from("4")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-t","--threads"], Some("count"), Some("1"), "The number of threads")
+  ///   .add_flag(vec!["-v"], "Verbose output")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  assert_eq!(cl.option_names(), vec!["--help", "--threads", "-v"]);
+  /// ```
+  pub fn option_names(&self) -> Vec<&str> {
+    self.option_names.iter().map(String::as_str).collect()
+  }
+
+  /// Sets the canonical name of each option with a value
+  #[inline]
+  pub(crate) fn set_option_names(&mut self, names:Vec<String>) {
+    self.option_names = names;
+  }
+
+  /// Sets the canonical name and form of each defined option
+  #[inline]
+  pub(crate) fn set_option_forms(&mut self, forms:Vec<(&'static str, OptionForm)>) {
+    self.option_forms = forms;
+  }
+
+  /// Returns the args that reproduce the commandline after the program name, quoted for a POSIX
+  /// shell. The options whose value was given on the commandline come first, named by their
+  /// canonical alias, then the arguments, the subcommand and the args after `--`.
+  fn canonical_args(&self) -> Vec<String> {
+    let mut args = Vec::default();
+    for (name, form) in self.option_forms.iter().filter(|(name, _)| self.given.contains(name)) {
+      match (form, self.value(name)) {
+        // each value of an option with an arity is its own arg, so it keeps its commas
+        (OptionForm::Value, Some(value)) => {
+          args.push(name.to_string());
+          match self.options.lists.get(*name) {
+            Some(values) => args.extend(values.iter().map(|value| quote_posix(value))),
+            None => args.push(quote_posix(value)),
+          }
+        },
+        (OptionForm::Attached, Some("")) => args.push(name.to_string()),
+        (OptionForm::Attached, Some(value)) if name.starts_with(LONG_OPTION) => args.push(format!("{name}={}", quote_posix(value))),
+        (OptionForm::Attached, Some(value)) => args.push(format!("{name}{}", quote_posix(value))),
+        (OptionForm::Flag, Some(TRUE)) => args.push(name.to_string()),
+        (OptionForm::Counter, _) => args.extend((0..self.count(name)).map(|_| name.to_string())),
+        (OptionForm::Pair(_), Some(TRUE)) => args.push(name.to_string()),
+        (OptionForm::Pair(disable), Some(_)) => args.push(disable.to_string()),
+        (OptionForm::Toggle(plus), Some(value)) if value.parse() == Ok(*plus) =>
+          args.push(format!("{PLUS_OPTION}{}", name.trim_start_matches(SHORT_OPTION))),
+        (OptionForm::Toggle(_), Some(_)) => args.push(name.to_string()),
+        _ => {},
+      }
+    }
+    // the args after `--` are also the last arguments
+    let given = match self.arguments.len().checked_sub(self.rest.len()) {
+      Some(given) if self.arguments[given..] == self.rest => given,
+      _ => self.arguments.len(),
+    };
+    args.extend(self.arguments[..given].iter().map(|argument| quote_posix(argument)));
+    if let Some((name, subcommand)) = &self.subcommand {
+      args.push(quote_posix(name));
+      args.extend(subcommand.canonical_args());
+    }
+    if !self.rest.is_empty() {
+      args.push(String::from(LONG_OPTION));
+      args.extend(self.rest.iter().map(|arg| quote_posix(arg)));
+    }
+    args
+  }
+
+  /// Sets the name of the argument each of the arguments was given for
+  #[inline]
+  pub(crate) fn set_argument_names(&mut self, names:Vec<&'static str>) {
+    self.argument_names = names;
+  }
+
+  /// Returns the arguments given for the variadic argument, each converted to the target type.
+  /// Returns an empty Vec if the definition has no variadic argument.
+  ///
+  /// # Panics
+  ///
+  /// * Panics if an argument cannot be converted, naming the index of the argument
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("3"), String::from("1"), String::from("4")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_argument("first")
+  ///   .add_variadic_argument("rest")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  let rest:Vec<u8> = cl.variadic();
+  ///  assert_eq!(rest, vec![1, 4]);
+  /// ```
+  pub fn variadic<T>(&self) -> Vec<T>
+  where T: FromStr, T::Err: Display {
+    self.variadic.clone().map(|index| self.argument(index)).collect()
+  }
+
+  /// Sets the indexes of the arguments given for the variadic argument
+  #[inline]
+  pub(crate) fn set_variadic(&mut self, variadic:Range<usize>) {
+    self.variadic = variadic;
+  }
+
+  /// Returns the args after `--`, exactly as they were given, for tools that pass them on to
+  /// another program. The args are also arguments of the CommandLine, and are empty if there is no
+  /// `--`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("timeout"), String::from("-s"), String::from("KILL"), String::from("--"),
+  ///                    String::from("sleep"), String::from("-x"), String::from("*.txt")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-s","--signal"], Some("signal"), Some("TERM"), "The signal to send")
+  ///   .add_variadic_argument("command")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  assert_eq!(cl.rest(), ["sleep", "-x", "*.txt"]);
+  ///  let signal:String = cl.option("--signal");
+  ///  assert_eq!(signal, "KILL");
+  /// ```
+  #[inline]
+  pub fn rest(&self) -> &[String] {
+    &self.rest
+  }
+
+  /// Returns a Command that runs the args after `--`, with the first as the program and the others
+  /// as its args, for wrappers that run another program. The args are passed as they were given,
+  /// without a shell, so they are never split or expanded again. Returns `None` if there are no
+  /// args after `--`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("nice"), String::from("--"), String::from("cargo"), String::from("build"),
+  ///                    String::from("--release")];
+  ///  let cl = CommandLineDef::new().add_variadic_argument("command").parse(env_args.into_iter());
+  ///
+  ///  let command = cl.rest_command().unwrap();
+  ///  assert_eq!(command.get_program(), "cargo");
+  ///  assert_eq!(command.get_args().collect::<Vec<_>>(), ["build", "--release"]);
+  /// ```
+  #[cfg(feature = "std")]
+  pub fn rest_command(&self) -> Option<Command> {
+    let (program, args) = self.rest.split_first()?;
+    let mut command = Command::new(program);
+    command.args(args);
+    Some(command)
+  }
+
+  /// Sets the args after `--`
+  #[inline]
+  pub(crate) fn set_rest(&mut self, rest:Vec<String>) {
+    self.rest = rest;
+  }
+
   /// Returns the program name specified on the command line
   ///
   /// # Examples
@@ -167,4 +811,278 @@ impl CommandLine {
   pub fn program_name(&self) -> &str {
     &self.program_name
   }
+
+  /// Returns the path the program was run with, argument 0 of the commandline. For a subcommand,
+  /// this is the path of the program, while the program name includes the subcommand.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("/usr/bin/git"), String::from("status")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_subcommand(vec!["status"], "Show the status", CommandLineDef::new())
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  let (_, status) = cl.subcommand().unwrap();
+  ///  assert_eq!(status.program_name(), "/usr/bin/git status");
+  ///  assert_eq!(status.program_path(), "/usr/bin/git");
+  /// ```
+  #[inline]
+  pub fn program_path(&self) -> &str {
+    &self.program_path
+  }
+
+  /// Returns the file name of the program path without its extension, which is usually the name
+  /// to show in messages or to dispatch on
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("C:\\tools\\gunzip.exe")];
+  ///  let cl = CommandLineDef::new().parse(env_args.into_iter());
+  ///
+  ///  assert_eq!(cl.program_stem(), "gunzip");
+  /// ```
+  #[inline]
+  pub fn program_stem(&self) -> &str {
+    program_stem(&self.program_path)
+  }
+
+  /// Returns the canonical name and the commandline of the subcommand, if one was specified. The
+  /// canonical name is returned even when an alias of the subcommand was used.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("co"), String::from("main")];
+  ///  let mut checkout = CommandLineDef::new();
+  ///  checkout.add_argument("branch");
+  ///  let cl = CommandLineDef::new()
+  ///   .add_subcommand(vec!["checkout","co"], "Switch branches", checkout)
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  let (name, checkout) = cl.subcommand().unwrap();
+  ///  assert_eq!(name, "checkout");
+  ///  let branch:String = checkout.argument(0);
+  ///  assert_eq!(branch, "main");
+  /// ```
+  #[inline]
+  pub fn subcommand(&self) -> Option<(&str, &CommandLine)> {
+    self.subcommand.as_ref().map(|(name, cl)| (*name, cl.as_ref()))
+  }
+
+  /// Sets the subcommand
+  #[inline]
+  pub(crate) fn set_subcommand(&mut self, name:&'static str, mut cl:CommandLine) {
+    cl.set_program_path(&self.program_path);
+    self.subcommand = Some((name, Box::new(cl)));
+  }
+
+  /// Sets the program path of this commandline and its subcommands
+  pub(crate) fn set_program_path(&mut self, program_path:&str) {
+    self.program_path = String::from(program_path);
+    if let Some((_, cl)) = &mut self.subcommand {
+      cl.set_program_path(program_path);
+    }
+  }
+
+  /// Merges another CommandLine into this one, the other taking precedence, for layered parsing
+  /// such as system-wide args, then user args, then the invocation's args. An option given in the
+  /// other CommandLine replaces the value in this one, while an option whose value is its default
+  /// in the other keeps the value in this one. The arguments, the subcommand and the args after
+  /// `--` are taken from the other if it has any, and subcommands with the same name are merged.
+  ///
+  /// # Arguments
+  ///
+  /// * `other` - The CommandLine whose values take precedence
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let mut cl_def = CommandLineDef::new();
+  ///  cl_def
+  ///   .add_option(vec!["-j","--jobs"], Some("jobs"), Some("1"), "The number of jobs")
+  ///   .add_option(vec!["--color"], Some("when"), Some("auto"), "When to color the output");
+  ///  let parse = |args:&[&str]| cl_def.parse(args.iter().map(|arg| arg.to_string()));
+  ///
+  ///  let system = parse(&["make", "--color", "never", "-j", "2"]);
+  ///  let user = parse(&["make", "-j", "8"]);
+  ///  let cl = system.merged_with(user);
+  ///
+  ///  assert_eq!(cl.option::<u8>("--jobs"), 8);
+  ///  assert_eq!(cl.option_str("--color"), "never");
+  ///  assert_eq!(cl.is_default("--color"), false);
+  /// ```
+  pub fn merged_with(mut self, other:CommandLine) -> CommandLine {
+    let Options { values, mut lists } = other.options;
+    for (name, value) in values {
+      match other.defaults.iter().find(|alias| **alias == name) {
+        Some(_) if self.options.contains_key(&name) => continue,
+        Some(alias) => self.defaults.push(alias),
+        None => self.defaults.retain(|alias| *alias != name),
+      }
+      match other.given.iter().find(|alias| **alias == name) {
+        Some(alias) => self.given.push(alias),
+        None => self.given.retain(|alias| *alias != name),
+      }
+      match lists.remove(&name) {
+        Some(list) => self.options.insert_list(name, value, list),
+        None => self.options.insert(name, value),
+      }
+    }
+    self.indices.extend(other.indices);
+    self.matched.extend(other.matched);
+    for name in other.option_names {
+      if !self.option_names.contains(&name) {
+        self.option_names.push(name);
+      }
+    }
+    for form in other.option_forms {
+      if !self.option_forms.iter().any(|(name, _)| *name == form.0) {
+        self.option_forms.push(form);
+      }
+    }
+    if !other.arguments.is_empty() {
+      self.arguments = other.arguments;
+      self.argument_names = other.argument_names;
+      self.variadic = other.variadic;
+    }
+    if !other.rest.is_empty() {
+      self.rest = other.rest;
+    }
+    self.subcommand = match (self.subcommand, other.subcommand) {
+      (Some((name, subcommand)), Some((other_name, other_subcommand))) if name == other_name =>
+        Some((name, Box::new(subcommand.merged_with(*other_subcommand)))),
+      (subcommand, None) => subcommand,
+      (_, subcommand) => subcommand,
+    };
+    if !other.program_name.is_empty() {
+      self.program_name = other.program_name;
+      self.program_path = other.program_path;
+    }
+    self
+  }
+
+  /// Returns the options in a section, for options with dotted long names such as `--db.host`
+  /// and `--db.port`. The options in the section are named without the section, so `--db.host`
+  /// is `--host` in the `db` section. Nested sections such as `--db.pool.size` are sections of the
+  /// section. The section has the same program name, and no arguments.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the section. e.g. `db`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("--db.host"), String::from("example.com")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_option(vec!["--db.host"], Some("host"), Some("localhost"), "The database host")
+  ///   .add_option(vec!["--db.port"], Some("port"), Some("5432"), "The database port")
+  ///   .add_option(vec!["--db.pool.size"], Some("size"), Some("4"), "The connection pool size")
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  let db = cl.section("db");
+  ///  let host:String = db.option("--host");
+  ///  assert_eq!(host, "example.com");
+  ///  let port:u16 = db.option("--port");
+  ///  assert_eq!(port, 5432);
+  ///
+  ///  let size:u8 = db.section("pool").option("--size");
+  ///  assert_eq!(size, 4);
+  /// ```
+  pub fn section(&self, name:&str) -> CommandLine {
+    let prefix = format!("{LONG_OPTION}{name}{SECTION_SEPARATOR}");
+    let in_section = |alias:&String| Some(format!("{LONG_OPTION}{}", alias.strip_prefix(&prefix)?));
+    let options = Options {
+      values:self.options.values.iter().filter_map(|(alias, value)| Some((in_section(alias)?, value.clone()))).collect(),
+      lists:self.options.lists.iter().filter_map(|(alias, values)| Some((in_section(alias)?, values.clone()))).collect(),
+    };
+    let mut section = CommandLine::new(self.program_name.clone(), options, Vec::default());
+    section.program_path.clone_from(&self.program_path);
+    section.option_names = self.option_names.iter()
+      .filter_map(|name| Some(format!("{LONG_OPTION}{}", name.strip_prefix(&prefix)?)))
+      .collect();
+    section
+  }
+
+  /// Returns the verbosity level set with the options added by `CommandLineDef::add_verbosity`.
+  /// Each `-v` increases the level by one and each `-q` decreases it by one, so the default level
+  /// is 0 and a negative level is quieter than the default.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("-vvv"), String::from("--quiet")];
+  ///  let cl = CommandLineDef::new().add_verbosity().parse(env_args.into_iter());
+  ///
+  ///  assert_eq!(cl.verbosity(), 2);
+  /// ```
+  #[inline]
+  pub fn verbosity(&self) -> i8 {
+    let verbosity = self.count(LONG_VERBOSE) - self.count(LONG_QUIET);
+    verbosity.clamp(i8::MIN as i64, i8::MAX as i64) as i8
+  }
+
+  /// Returns the value of the option, if it is defined
+  #[inline]
+  pub(crate) fn value(&self, name:&str) -> Option<&str> {
+    self.options.get(name).map(|value| &**value)
+  }
+
+  /// Returns the values of the option, if it is defined. The values of an option given several
+  /// values on the commandline are returned as they were given, and any other value is split at
+  /// `VALUE_DELIMITER`.
+  pub(crate) fn value_list(&self, name:&str) -> Option<Vec<&str>> {
+    match self.options.lists.get(name) {
+      Some(values) => Some(values.iter().map(|value| &**value).collect()),
+      None => Some(self.value(name)?.split(VALUE_DELIMITER).collect()),
+    }
+  }
+
+  /// Returns the number of times a flag was specified
+  #[inline]
+  fn count(&self, name:&str) -> i64 {
+    match self.value(name) {
+      Some(TRUE) => 1,
+      Some(count) => count.parse().unwrap_or(0),
+      None => 0,
+    }
+  }
+}
+
+impl Display for CommandLine {
+  /// Writes a canonical commandline, quoted for a POSIX shell. The options given on the commandline
+  /// come first, named by their first long alias, then the arguments, the subcommand and the args
+  /// after `--`. e.g. `mytool --file 'a b.txt' build`. The values from the environment, the config
+  /// or `set_option` are left out, so secrets in the environment are not shown, and the commandline
+  /// only reproduces the parsed values in the same environment.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=["mytool", "-vv", "build", "-f", "a b.txt"].map(String::from);
+  ///  let mut build = CommandLineDef::new();
+  ///  build.add_option(vec!["-f","--file"], Some("file"), Some("Makefile"), "The build file");
+  ///  let cl = CommandLineDef::new()
+  ///   .add_counter(vec!["-v","--verbose"], "Verbose output")
+  ///   .add_subcommand(vec!["build"], "Build the project", build)
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  assert_eq!(cl.to_string(), "mytool --verbose --verbose build --file 'a b.txt'");
+  /// ```
+  fn fmt(&self, f:&mut Formatter<'_>) -> core::fmt::Result {
+    write!(f, "{}", quote_posix(&self.program_path))?;
+    for arg in self.canonical_args() {
+      write!(f, " {arg}")?;
+    }
+    Ok(())
+  }
 }
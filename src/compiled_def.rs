@@ -0,0 +1,198 @@
+use alloc::format;
+use alloc::string::String;
+use core::fmt::{Debug, Formatter};
+use super::cl_def::CommandLineDef;
+use super::command_line::CommandLine;
+use super::def_error::{DefError, DefErrorKind};
+use super::environment::Environment;
+use super::help_layout::HelpLayout;
+use super::option_def::OptionDef;
+use super::parse_error::ParseError;
+use crate::text::T;
+use crate::VALUE_DELIMITER;
+
+/// Stands for the program name in the precomputed usage message. Program names cannot contain it,
+/// as args are C strings.
+pub(crate) const PROGRAM_PLACEHOLDER: char = '\0';
+
+/// A commandline definition that has been checked by `CommandLineDef::build`, and can no longer be
+/// changed. It parses commandlines like the CommandLineDef it was built from, and is meant for
+/// programs such as daemons and shells that parse many commandlines with the same definition:
+///
+/// * The usage message is built once, and only the program name is filled in when it is needed
+/// * The .env file, with the `dotenv` feature, is read once when the definition is built. The
+///   process environment is still read when parsing.
+/// * The aliases are static strings kept in a sorted table, so looking up an option does not
+///   allocate
+pub struct CompiledDef {
+  /// The checked definition
+  cl_def:CommandLineDef,
+}
+
+/// The parts of a definition computed once by `build`
+pub(crate) struct Precomputed {
+  /// The usage message, with `PROGRAM_PLACEHOLDER` for the program name
+  pub(crate) usage:String,
+  /// The layout of the usage message, which has no hyperlinks unless the layout enables them
+  pub(crate) layout:HelpLayout,
+  /// The environment, with the variables of the .env file, unless the file cannot be read
+  pub(crate) environment:Option<Environment>,
+}
+
+impl CommandLineDef {
+  /// Creates a new CommandLineDef whose definition errors, such as an invalid alias or an option
+  /// defined twice, are returned by `build` instead of panicking when the option is added. The
+  /// methods that find an error leave the definition unchanged.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, DefErrorKind};
+  /// let err = CommandLineDef::builder()
+  ///   .add_flag(vec!["verbose"], "Verbose output")
+  ///   .build()
+  ///   .unwrap_err();
+  ///
+  /// assert_eq!(err.kind(), DefErrorKind::InvalidName);
+  /// ```
+  #[inline]
+  pub fn builder() -> Self {
+    let mut cl_def = CommandLineDef::new();
+    cl_def.defer_errors = true;
+    cl_def
+  }
+
+  /// Checks the definition, and returns it as a CompiledDef that can no longer be changed. The
+  /// definition is moved into the CompiledDef, leaving a new definition in its place.
+  ///
+  /// # Errors
+  ///
+  /// * The first error found while the definition was built, for a definition created with
+  ///   `builder`
+  /// * [`DefErrorKind::InvalidDefault`] if an option's default value is not one of its valid values,
+  ///   or cannot be converted to its type
+  /// * The first error in a subcommand's definition, with the message prefixed by the subcommand's
+  ///   path. e.g. `In command 'remote add': ...`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, DefErrorKind};
+  /// let compiled = CommandLineDef::builder()
+  ///   .add_option(vec!["-l","--level"], Some("level"), Some("low"), "The level")
+  ///   .valid_values("-l", vec!["low","high"])
+  ///   .build()
+  ///   .unwrap();
+  ///
+  /// let args=vec!["program".to_string(), "-l".to_string(), "high".to_string()];
+  /// let level:String = compiled.parse(args.into_iter()).option("--level");
+  /// assert_eq!(level, "high");
+  ///
+  /// let err = CommandLineDef::builder()
+  ///   .add_option(vec!["-l","--level"], Some("level"), Some("max"), "The level")
+  ///   .valid_values("-l", vec!["low","high"])
+  ///   .build()
+  ///   .unwrap_err();
+  /// assert_eq!(err.kind(), DefErrorKind::InvalidDefault);
+  /// assert_eq!(err.message(), "Invalid default value 'max' for option '-l'. Valid values are: low, high");
+  /// ```
+  pub fn build(&mut self) -> Result<CompiledDef, DefError> {
+    self.check("")?;
+    let mut cl_def = core::mem::take(self);
+    cl_def.precompute();
+    Ok(CompiledDef {
+      cl_def,
+    })
+  }
+
+  /// Returns the first error in the definition, then in its subcommands. The path names the
+  /// subcommand being checked, and is empty for the top level definition.
+  fn check(&self, path:&str) -> Result<(), DefError> {
+    let error = self.def_error.clone().or_else(|| self.option_defs.iter().find_map(Self::check_default));
+    if let Some(error) = error {
+      return Err(match path {
+        "" => error,
+        path => DefError::new(error.kind(), T.subcommand_def_error(path, error.message())),
+      });
+    }
+    for subcommand in &self.subcommands {
+      let name = subcommand.names[0];
+      subcommand.cl_def.check(&if path.is_empty() { String::from(name) } else { format!("{path} {name}") })?;
+    }
+    Ok(())
+  }
+
+  /// Returns the error for an option's default value that is not one of its valid values, or cannot
+  /// be converted to its type. Each of the comma separated values of an option with an arity is
+  /// converted on its own.
+  fn check_default(od:&OptionDef) -> Option<DefError> {
+    let default = od.default_value?;
+    if !od.valid_values.is_empty() && !od.valid_values.contains(&default) {
+      return Some(DefError::new(DefErrorKind::InvalidDefault,
+                                T.option_invalid_default(od.aliases[0], default, &od.valid_values.join(", "))));
+    }
+    let validator = od.validator.as_ref()?;
+    let error = if od.arity == (1..=1) {
+      validator(default).err()
+    } else {
+      default.split(VALUE_DELIMITER).filter(|_| !default.is_empty()).find_map(|value| validator(value).err())
+    }?;
+    Some(DefError::new(DefErrorKind::InvalidDefault, T.option_cannot_convert(od.aliases[0], default, &error)))
+  }
+
+  /// Computes the usage message and environment of the definition and its subcommands. The
+  /// environment is loaded when parsing instead if the .env file cannot be read.
+  fn precompute(&mut self) {
+    for subcommand in &mut self.subcommands {
+      subcommand.cl_def.precompute();
+    }
+    let layout = self.help_layout.for_stream(None);
+    self.precomputed = Some(Precomputed {
+      usage:self.render_usage(&String::from(PROGRAM_PLACEHOLDER), layout),
+      layout,
+      environment:Environment::load(self, "").ok(),
+    });
+  }
+}
+
+impl Debug for CompiledDef {
+  fn fmt(&self, f:&mut Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("CompiledDef").finish_non_exhaustive()
+  }
+}
+
+impl CompiledDef {
+  /// Parses the args with the definition, as `CommandLineDef::parse` does
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - The commandline args, starting with the program name
+  #[inline]
+  pub fn parse(&self, args:impl Iterator<Item=String>) -> CommandLine {
+    self.cl_def.parse(args)
+  }
+
+  /// Parses the args with the definition, as `CommandLineDef::try_parse` does
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - The commandline args, starting with the program name
+  ///
+  /// # Errors
+  ///
+  /// Returns a ParseError if the commandline does not match the definition
+  #[inline]
+  pub fn try_parse(&self, args:impl Iterator<Item=String>) -> Result<CommandLine, ParseError> {
+    self.cl_def.try_parse(args)
+  }
+
+  /// Returns the usage message, as `CommandLineDef::usage` does
+  ///
+  /// # Arguments
+  ///
+  /// * `program_name` - The name of the program shown in the usage line
+  #[inline]
+  pub fn usage(&self, program_name:&str) -> String {
+    self.cl_def.usage(program_name)
+  }
+}
@@ -0,0 +1,108 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use super::cl_def::CommandLineDef;
+use super::json::json_string;
+
+impl CommandLineDef {
+  /// Returns a [Fig](https://fig.io) autocomplete spec for this definition, so users of Fig
+  /// compatible terminals get completions without a hand maintained spec. The valid values of
+  /// options are suggested.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the program
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let spec = CommandLineDef::new()
+  ///   .add_option(vec!["-f","--file"], Some("path"), None, "The file to read")
+  ///   .add_option(vec!["-l","--level"], Some("level"), Some("low"), "The level")
+  ///   .valid_values("--level", vec!["low","high"])
+  ///   .add_argument("dest")
+  ///   .to_fig_spec("program");
+  ///
+  /// assert!(spec.starts_with("const completionSpec: Fig.Spec = {\n  name: \"program\","));
+  /// assert!(spec.contains(r#"{ name: ["-f", "--file"], description: "The file to read", isRequired: true, args: { name: "path" } },"#));
+  /// assert!(spec.contains(r#"args: { name: "level", default: "low", suggestions: ["low", "high"] }"#));
+  /// assert!(spec.contains(r#"{ name: "dest" },"#));
+  /// ```
+  pub fn to_fig_spec(&self, name:&str) -> String {
+    let mut spec = format!("const completionSpec: Fig.Spec = {{\n  name: {},\n  options: [\n", json_string(name));
+    for od in &self.option_defs {
+      let names = od.aliases.iter().map(|alias| json_string(alias)).collect::<Vec<String>>().join(", ");
+      spec.push_str(&format!("    {{ name: [{names}], description: {}", json_string(od.description)));
+      if let Some(value_name) = od.value_name {
+        let suggestions = match od.valid_values.is_empty() {
+          true => String::default(),
+          false => format!(", suggestions: [{}]", json_strings(&od.valid_values)),
+        };
+        match od.default_value {
+          Some(default) => spec.push_str(&format!(", args: {{ name: {}, default: {}{suggestions} }}", json_string(value_name), json_string(default))),
+          None => spec.push_str(&format!(", isRequired: true, args: {{ name: {}{suggestions} }}", json_string(value_name))),
+        }
+      }
+      spec.push_str(" },\n");
+    }
+    spec.push_str("  ],\n  args: [\n");
+    for (idx, argument_name) in self.argument_names.iter().enumerate() {
+      match self.variadic == Some(idx) {
+        true => spec.push_str(&format!("    {{ name: {}, isVariadic: true, isOptional: true }},\n", json_string(argument_name))),
+        false => spec.push_str(&format!("    {{ name: {} }},\n", json_string(argument_name))),
+      }
+    }
+    spec.push_str("  ],\n};\nexport default completionSpec;\n");
+    spec
+  }
+
+  /// Returns a [carapace](https://carapace.sh) spec for this definition, so users of carapace get
+  /// completions without a hand maintained spec. The valid values of options are completed.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the program
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let spec = CommandLineDef::new()
+  ///   .add_flag(vec!["-v","--verbose"], "Verbose output")
+  ///   .add_option(vec!["-f","--file"], Some("path"), None, "The file to read")
+  ///   .add_option(vec!["-l","--level"], Some("level"), Some("low"), "The level")
+  ///   .valid_values("--level", vec!["low","high"])
+  ///   .to_carapace_spec("program");
+  ///
+  /// assert!(spec.contains("  -v, --verbose: \"Verbose output\"\n"));
+  /// assert!(spec.contains("  -f, --file=!: \"The file to read\"\n"));
+  /// assert!(spec.ends_with("completion:\n  flag:\n    level: [\"low\", \"high\"]\n"));
+  /// ```
+  pub fn to_carapace_spec(&self, name:&str) -> String {
+    let mut spec = format!("name: {}\nflags:\n", json_string(name));
+    for od in &self.option_defs {
+      let modifier = match (od.value_name, od.default_value) {
+        (None, _) => "",
+        (Some(_), Some(_)) => "=",
+        (Some(_), None) => "=!",
+      };
+      spec.push_str(&format!("  {}{modifier}: {}\n", od.aliases.join(", "), json_string(od.description)));
+    }
+    let completions:Vec<String> = self.option_defs.iter()
+      .filter(|od| od.value_name.is_some() && !od.valid_values.is_empty())
+      .map(|od| format!("    {}: [{}]\n", od.key(), json_strings(&od.valid_values)))
+      .collect();
+    if !completions.is_empty() {
+      spec.push_str("completion:\n  flag:\n");
+      spec.push_str(&completions.concat());
+    }
+    spec
+  }
+}
+
+/// Returns the values as a comma separated list of JSON strings
+#[inline]
+fn json_strings(values:&[&str]) -> String {
+  values.iter().map(|value| json_string(value)).collect::<Vec<String>>().join(", ")
+}
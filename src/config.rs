@@ -0,0 +1,94 @@
+//! Config files for commandline definitions
+//!
+//! # Examples
+//!
+//! ```
+//! use cl_parse::CommandLineDef;
+//! use cl_parse::config::Format;
+//! let mut config = Vec::new();
+//! CommandLineDef::new()
+//!   .add_option(vec!["-o","--output"], Some("file"), Some("out.txt"), "The output file")
+//!   .write_default_config(&mut config, Format::Toml)
+//!   .unwrap();
+//!
+//! assert_eq!(String::from_utf8(config).unwrap(), "# The output file\noutput = \"out.txt\"\n");
+//! ```
+use std::io::{Result, Write};
+use super::cl_def::CommandLineDef;
+use super::json::json_string;
+
+/// The formats of config files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Format {
+  /// [TOML](https://toml.io)
+  Toml,
+  /// [YAML](https://yaml.org)
+  Yaml,
+}
+
+impl Format {
+  /// Returns the separator between a key and its value
+  #[inline]
+  fn separator(self) -> &'static str {
+    match self {
+      Format::Toml => " = ",
+      Format::Yaml => ": ",
+    }
+  }
+}
+
+impl CommandLineDef {
+  /// Writes a config file with every option, its description and its default value, so a command
+  /// such as `--init-config` can be implemented in one line. Each option is named after its first
+  /// long alias without the leading dashes, or its short alias if it has no long alias. Required
+  /// options are written commented out, as they have no default. The help and version options,
+  /// actions and options that can only be set from the environment are not included.
+  ///
+  /// # Arguments
+  ///
+  /// * `out` - Where the config file is written
+  /// * `format` - The format of the config file
+  ///
+  /// # Errors
+  ///
+  /// * Returns the error from `out` if the config file cannot be written
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// use cl_parse::config::Format;
+  /// let mut config = Vec::new();
+  /// CommandLineDef::new()
+  ///   .add_flag(vec!["-v","--verbose"], "Verbose output")
+  ///   .add_option(vec!["--host"], Some("host"), None, "The host to connect to")
+  ///   .write_default_config(&mut config, Format::Yaml)
+  ///   .unwrap();
+  ///
+  /// assert_eq!(String::from_utf8(config).unwrap(),
+  ///   "# Verbose output\nverbose: false\n\n# The host to connect to\n# host: \"\" (required)\n");
+  /// ```
+  pub fn write_default_config(&self, out:&mut impl Write, format:Format) -> Result<()> {
+    let mut first = true;
+    for od in &self.option_defs {
+      if od.help || od.version || od.action.is_some() || od.env_only {
+        continue;
+      }
+      if !first {
+        writeln!(out)?;
+      }
+      first = false;
+      for line in od.description.lines() {
+        writeln!(out, "# {line}")?;
+      }
+      let separator = format.separator();
+      match (od.value_name, od.default_value) {
+        (None, Some(default)) => writeln!(out, "{}{separator}{default}", od.key())?,
+        (Some(_), Some(default)) => writeln!(out, "{}{separator}{}", od.key(), json_string(default))?,
+        (_, None) => writeln!(out, "# {}{separator}\"\" (required)", od.key())?,
+      }
+    }
+    Ok(())
+  }
+}
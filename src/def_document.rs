@@ -0,0 +1,144 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Deserialize;
+use super::cl_def::CommandLineDef;
+use super::leak;
+
+/// A commandline definition loaded from a YAML or TOML document
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DefDocument {
+  /// The options, in the order they are added
+  #[serde(default)]
+  options: Vec<OptionDocument>,
+  /// The argument names, in order
+  #[serde(default)]
+  arguments: Vec<String>,
+}
+
+/// An option definition loaded from a YAML or TOML document
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OptionDocument {
+  /// The aliases for this option. e.g. -f --filename
+  aliases: Vec<String>,
+  /// The name for the value associated with the option. If missing, the option is a flag
+  value_name: Option<String>,
+  /// The value to use if one is not supplied. If missing, the option is required
+  default: Option<String>,
+  /// The description of this option
+  #[serde(default)]
+  description: String,
+}
+
+impl From<DefDocument> for CommandLineDef {
+  fn from(document: DefDocument) -> Self {
+    let mut cl_def = CommandLineDef::new();
+    for option in document.options {
+      cl_def.add_option(
+        option.aliases.into_iter().map(leak).collect(),
+        option.value_name.map(leak),
+        option.default.map(leak),
+        leak(option.description));
+    }
+    for argument in document.arguments {
+      cl_def.add_argument(leak(argument));
+    }
+    cl_def
+  }
+}
+
+impl CommandLineDef {
+  /// Creates a new CommandLineDef from a TOML document, so the commandline can be edited without
+  /// changing any Rust code. Options are added in the order they appear in the document.
+  ///
+  /// # Arguments
+  ///
+  /// * `document` - The TOML document. Each `[[options]]` table has `aliases`, and optionally a
+  ///   `value_name`, `default` and `description`. `arguments` lists the argument names.
+  ///
+  /// # Errors
+  ///
+  /// * Returns an error if the document is not valid TOML, or does not match the definition format
+  ///
+  /// # Panics
+  ///
+  /// * Panics if an alias is not a valid option name, or is defined more than once
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let def = CommandLineDef::from_toml(r#"
+  ///   arguments = ["input"]
+  ///
+  ///   [[options]]
+  ///   aliases = ["-v", "--verbose"]
+  ///   description = "Verbose output"
+  ///
+  ///   [[options]]
+  ///   aliases = ["-o", "--output"]
+  ///   value_name = "file"
+  ///   default = "out.txt"
+  ///   description = "The output file"
+  /// "#).unwrap();
+  ///
+  /// let args=vec!["program".to_string(), "-v".to_string(), "in.txt".to_string()];
+  /// let cl = def.parse(args.into_iter());
+  ///
+  /// let verbose:bool = cl.option("--verbose");
+  /// assert_eq!(verbose, true);
+  ///
+  /// let output:String = cl.option("-o");
+  /// assert_eq!(output, "out.txt");
+  /// ```
+  #[cfg(feature = "toml")]
+  pub fn from_toml(document:&str) -> Result<Self, toml::de::Error> {
+    toml::from_str::<DefDocument>(document).map(CommandLineDef::from)
+  }
+
+  /// Creates a new CommandLineDef from a YAML document, so the commandline can be edited without
+  /// changing any Rust code. Options are added in the order they appear in the document.
+  ///
+  /// # Arguments
+  ///
+  /// * `document` - The YAML document. Each entry in `options` has `aliases`, and optionally a
+  ///   `value_name`, `default` and `description`. `arguments` lists the argument names.
+  ///
+  /// # Errors
+  ///
+  /// * Returns an error if the document is not valid YAML, or does not match the definition format
+  ///
+  /// # Panics
+  ///
+  /// * Panics if an alias is not a valid option name, or is defined more than once
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let def = CommandLineDef::from_yaml("
+  /// options:
+  ///   - aliases: [-v, --verbose]
+  ///     description: Verbose output
+  ///   - aliases: [-o, --output]
+  ///     value_name: file
+  ///     default: out.txt
+  ///     description: The output file
+  /// arguments: [input]
+  /// ").unwrap();
+  ///
+  /// let args=vec!["program".to_string(), "-v".to_string(), "in.txt".to_string()];
+  /// let cl = def.parse(args.into_iter());
+  ///
+  /// let verbose:bool = cl.option("--verbose");
+  /// assert_eq!(verbose, true);
+  ///
+  /// let input:String = cl.argument(0);
+  /// assert_eq!(input, "in.txt");
+  /// ```
+  #[cfg(feature = "yaml")]
+  pub fn from_yaml(document:&str) -> Result<Self, serde_yaml::Error> {
+    serde_yaml::from_str::<DefDocument>(document).map(CommandLineDef::from)
+  }
+}
@@ -0,0 +1,87 @@
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+
+/// The kinds of errors in a commandline definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DefErrorKind {
+  /// An option alias or subcommand name is not valid. e.g. `-ab` or `--`
+  InvalidName,
+  /// An option alias, subcommand name or variadic argument is defined more than once
+  Redefined,
+  /// An option or subcommand referred to by a definition method has not been defined
+  NotDefined,
+  /// An option's default value is not one of its valid values, or cannot be converted to its type
+  InvalidDefault,
+  /// An option cannot be defined that way, such as a toggle without a short alias, or a flag with
+  /// an arity
+  InvalidOption,
+  /// Two parts of the definition cannot be used together, such as a variadic argument and
+  /// subcommands
+  Conflict,
+}
+
+/// An error in a commandline definition, returned by `CommandLineDef::build`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefError {
+  /// The kind of error
+  kind: DefErrorKind,
+  /// The message describing the error
+  message: String,
+}
+
+impl DefError {
+  /// Creates a new DefError
+  ///
+  /// # Arguments
+  ///
+  /// * `kind` - The kind of error
+  /// * `message` - The message describing the error
+  #[inline]
+  pub(crate) fn new(kind: DefErrorKind, message: String) -> Self {
+    DefError {
+      kind,
+      message,
+    }
+  }
+
+  /// Returns the kind of error
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, DefErrorKind};
+  /// let err = CommandLineDef::builder()
+  ///   .add_flag(vec!["-v"], "Verbose output")
+  ///   .add_flag(vec!["-v"], "Version")
+  ///   .build()
+  ///   .unwrap_err();
+  /// assert_eq!(err.kind(), DefErrorKind::Redefined);
+  /// ```
+  #[inline]
+  pub fn kind(&self) -> DefErrorKind {
+    self.kind
+  }
+
+  /// Returns the message describing the error
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let err = CommandLineDef::builder().lazy_values("--tags").build().unwrap_err();
+  /// assert_eq!(err.message(), "Option '--tags' not defined");
+  /// ```
+  #[inline]
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+}
+
+impl Display for DefError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+    write!(f, "{}", self.message)
+  }
+}
+
+impl core::error::Error for DefError {}
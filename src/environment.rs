@@ -0,0 +1,75 @@
+#[cfg(feature = "dotenv")]
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+#[cfg(feature = "dotenv")]
+use alloc::string::ToString;
+use super::cl_def::CommandLineDef;
+#[cfg(feature = "dotenv")]
+use super::parse_error::ErrorKind;
+use super::parse_error::ParseError;
+#[cfg(feature = "dotenv")]
+use crate::text::T;
+
+/// The environment variables read while parsing. Variables set in the process take precedence over
+/// the ones loaded from a .env file, and the process environment is never modified.
+#[derive(Default)]
+pub(crate) struct Environment {
+  /// The variables loaded from the .env file
+  #[cfg(feature = "dotenv")]
+  dotenv:BTreeMap<String, String>,
+}
+
+impl Environment {
+  /// Loads the environment for the definition. The .env file is only read if the definition uses
+  /// environment variables.
+  #[cfg(feature = "dotenv")]
+  pub(crate) fn load(cl_def:&CommandLineDef, program_name:&str) -> Result<Self, ParseError> {
+    let mut dotenv = BTreeMap::default();
+    let path = match &cl_def.dotenv_path {
+      Some(path) if Self::is_used(cl_def) => path,
+      _ => return Ok(Environment { dotenv }),
+    };
+    let invalid = |e:dotenvy::Error| {
+      cl_def.parse_error(ErrorKind::InvalidDotenv, T.dotenv_invalid(&path.to_string_lossy(), &e.to_string()), program_name)
+    };
+    match dotenvy::from_path_iter(path) {
+      Ok(iter) => for item in iter {
+        let (key, value) = item.map_err(invalid)?;
+        dotenv.entry(key).or_insert(value);
+      },
+      Err(e) if e.not_found() => {},
+      Err(e) => return Err(invalid(e)),
+    }
+    Ok(Environment { dotenv })
+  }
+
+  /// Loads the environment for the definition
+  #[cfg(not(feature = "dotenv"))]
+  #[inline]
+  pub(crate) fn load(_cl_def:&CommandLineDef, _program_name:&str) -> Result<Self, ParseError> {
+    Ok(Environment::default())
+  }
+
+  /// Returns true if the definition reads any environment variables
+  #[cfg(feature = "dotenv")]
+  #[inline]
+  fn is_used(cl_def:&CommandLineDef) -> bool {
+    cl_def.args_env_var.is_some() || cl_def.option_defs.iter().any(|od| od.env.is_some())
+  }
+
+  /// Returns the value of an environment variable, if it is set and valid unicode. Without std,
+  /// environment variables are never read.
+  #[inline]
+  pub(crate) fn var(&self, var:&str) -> Option<String> {
+    #[cfg(feature = "std")]
+    if let Ok(value) = std::env::var(var) {
+      return Some(value);
+    }
+    #[cfg(feature = "dotenv")]
+    if let Some(value) = self.dotenv.get(var) {
+      return Some(value.clone());
+    }
+    let _ = var;
+    None
+  }
+}
@@ -0,0 +1,117 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use std::panic::resume_unwind;
+use std::process::exit;
+use super::cl_def::CommandLineDef;
+use super::parse_error::{ErrorKind, ParseError};
+
+/// The exit code used by `ErrorBehavior::Exit` for an invalid commandline
+const USAGE_EXIT_CODE: i32 = 2;
+
+/// Called with the exit code in place of `std::process::exit`
+pub(crate) type ExitHandler = Arc<dyn Fn(i32) + Send + Sync>;
+
+/// What `CommandLineDef::parse` does when the commandline is invalid, or help or the version is
+/// requested
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorBehavior {
+  /// Panics with the error message and the usage message
+  #[default]
+  Panic,
+  /// Prints the help or version to stdout and exits with 0, or prints the error to stderr and
+  /// exits with 2, the conventional code for invalid usage. See `CommandLineDef::with_output` and
+  /// `CommandLineDef::exit_handler` to capture the output and the exit code.
+  Exit,
+  /// Unwinds with the ParseError as the payload, without printing anything, so a library can
+  /// recover it with `std::panic::catch_unwind`. `parse` never returns the error, and the unwind
+  /// aborts the process when built with `panic = "abort"`. Use `CommandLineDef::try_parse` to get
+  /// the ParseError as a `Result`.
+  Unwind,
+}
+
+impl CommandLineDef {
+  /// Sets what `parse` does when the commandline is invalid, or help or the version is requested
+  ///
+  /// # Arguments
+  ///
+  /// * `behavior` - What to do with the ParseError
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use std::panic::{catch_unwind, AssertUnwindSafe};
+  /// use cl_parse::{CommandLineDef, ErrorBehavior, ErrorKind, ParseError};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.on_error(ErrorBehavior::Unwind);
+  ///
+  /// let args=vec!["program".to_string(), "-x".to_string()];
+  /// let payload = catch_unwind(AssertUnwindSafe(|| cl_def.parse(args.into_iter()))).unwrap_err();
+  /// let err = payload.downcast_ref::<ParseError>().unwrap();
+  /// assert_eq!(err.kind(), ErrorKind::OptionNotDefined);
+  /// ```
+  #[inline]
+  pub fn on_error(&mut self, behavior:ErrorBehavior) -> &mut Self {
+    self.error_behavior = behavior;
+    self
+  }
+
+  /// Sets the function called with the exit code by `ErrorBehavior::Exit` in place of
+  /// `std::process::exit`, so tests can check the exit code without the process exiting. If the
+  /// function returns, `parse` unwinds with the ParseError as the payload, like
+  /// `ErrorBehavior::Unwind`.
+  ///
+  /// # Arguments
+  ///
+  /// * `handler` - Called with the exit code
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use std::panic::{catch_unwind, AssertUnwindSafe};
+  /// use std::sync::{Arc, Mutex};
+  /// use cl_parse::{CommandLineDef, ErrorBehavior};
+  /// let code = Arc::new(Mutex::new(None));
+  /// let exited = code.clone();
+  ///
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def
+  ///   .on_error(ErrorBehavior::Exit)
+  ///   .exit_handler(move |code| *exited.lock().unwrap() = Some(code));
+  ///
+  /// let args=vec!["program".to_string(), "-x".to_string()];
+  /// assert!(catch_unwind(AssertUnwindSafe(|| cl_def.parse(args.into_iter()))).is_err());
+  /// assert_eq!(*code.lock().unwrap(), Some(2));
+  /// ```
+  #[inline]
+  pub fn exit_handler(&mut self, handler:impl Fn(i32) + Send + Sync + 'static) -> &mut Self {
+    self.exit_handler = Some(Arc::new(handler));
+    self
+  }
+
+  /// Handles a ParseError from `parse` as chosen with `on_error`
+  pub(crate) fn fail(&self, error:ParseError) -> ! {
+    match self.error_behavior {
+      ErrorBehavior::Panic => panic!("{}", error),
+      ErrorBehavior::Exit if matches!(error.kind(), ErrorKind::Help | ErrorKind::Version) => {
+        self.print_out(&error);
+        self.exit(0, error)
+      },
+      ErrorBehavior::Exit => {
+        self.print_err(&error);
+        self.exit(USAGE_EXIT_CODE, error)
+      },
+      ErrorBehavior::Unwind => resume_unwind(Box::new(error)),
+    }
+  }
+
+  /// Exits with the code, or calls the exit handler and unwinds with the ParseError if it returns
+  fn exit(&self, code:i32, error:ParseError) -> ! {
+    match &self.exit_handler {
+      Some(handler) => {
+        handler(code);
+        resume_unwind(Box::new(error))
+      },
+      None => exit(code),
+    }
+  }
+}
@@ -0,0 +1,87 @@
+use alloc::string::{String, ToString};
+use core::fmt::Display;
+use core::str::FromStr;
+use super::command_line::CommandLine;
+use crate::text::T;
+use crate::from_str_lenient;
+
+/// Converts the values of an option that takes several values, such as one with an arity of 2,
+/// into a tuple with an element for each value. Implemented for tuples of 1 to 4 elements whose
+/// types implement `FromStr`.
+pub trait FromValues: Sized {
+  /// The number of values converted
+  const LEN:usize;
+
+  /// Converts the values. There are always `LEN` values.
+  ///
+  /// # Arguments
+  ///
+  /// * `values` - The values of the option
+  ///
+  /// # Errors
+  ///
+  /// Returns the position of the value that cannot be converted, counting from 1, and the
+  /// conversion error
+  fn from_values(values:&[&str]) -> Result<Self, (usize, String)>;
+}
+
+/// Implements FromValues for a tuple, with the index of the value for each element
+macro_rules! tuple_from_values {
+  ($len:literal; $($t:ident $idx:tt),+) => {
+    impl<$($t),+> FromValues for ($($t,)+)
+    where $($t: FromStr, $t::Err: Display),+ {
+      const LEN:usize = $len;
+
+      #[inline]
+      fn from_values(values:&[&str]) -> Result<Self, (usize, String)> {
+        Ok(($(from_str_lenient::<$t>(values[$idx]).map_err(|e| ($idx + 1, e.to_string()))?,)+))
+      }
+    }
+  };
+}
+
+tuple_from_values!(1; A 0);
+tuple_from_values!(2; A 0, B 1);
+tuple_from_values!(3; A 0, B 1, C 2);
+tuple_from_values!(4; A 0, B 1, C 2, D 3);
+
+impl CommandLine {
+  /// Returns the values of an option as a tuple, with each value converted to the type of its
+  /// element. Values given as separate args are kept whole, while a value from another source is
+  /// split at commas.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - A string slice that holds the name of the option
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  /// * Panics if the number of values is not the number of elements
+  /// * Panics if a value cannot be converted, naming the value, counting from 1, and the
+  ///   conversion error
+  ///
+  /// # Examples
+  ///
+  /// ```
+  ///  use cl_parse::CommandLineDef;
+  ///  let env_args=vec![String::from("program"), String::from("--size"), String::from("800"), String::from("600")];
+  ///  let cl = CommandLineDef::new()
+  ///   .add_option(vec!["--size"], Some("pixels"), None, "The width and height")
+  ///   .arity("--size", 2)
+  ///   .parse(env_args.into_iter());
+  ///
+  ///  let (width, height):(u32, u32) = cl.tuple("--size");
+  ///  assert_eq!((width, height), (800, 600));
+  /// ```
+  pub fn tuple<V:FromValues>(&self, name:&str) -> V {
+    let values = self.value_list(name).unwrap_or_else(|| panic!("{}", T.option_not_found(name)));
+    if values.len() != V::LEN {
+      panic!("{}", T.option_values_count_ne(name, V::LEN, values.len()));
+    }
+    match V::from_values(&values) {
+      Ok(tuple) => tuple,
+      Err((idx, e)) => panic!("{}", T.option_element_cannot_convert(idx, name, values[idx - 1], &e)),
+    }
+  }
+}
@@ -0,0 +1,149 @@
+use alloc::format;
+use alloc::vec::Vec;
+use super::cl_def::CommandLineDef;
+use super::{leak, LONG_HELP, SHORT_HELP};
+
+/// Accepts getopts style option declarations and produces a CommandLineDef, so users of the
+/// `getopts` crate can migrate incrementally.
+///
+/// Option names are given without their leading dashes, and an empty name means the option has no
+/// short or long alias. Options that are not required default to an empty value when they are not
+/// specified. The `-h`, `--help` option is provided by the CommandLineDef, so declaring it is
+/// ignored.
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::CommandLineDef;
+/// use cl_parse::getopts::Options;
+///
+/// let mut opts = Options::new();
+/// opts.optopt("o", "", "set output file name", "NAME")
+///   .optflag("h", "help", "print this help menu")
+///   .optflag("v", "verbose", "print more output");
+///
+/// let args=vec!["program".to_string(), "-o".to_string(), "out.txt".to_string()];
+/// let cl = CommandLineDef::from(opts).parse(args.into_iter());
+///
+/// let output:String = cl.option("-o");
+/// assert_eq!(output, "out.txt");
+///
+/// let verbose:bool = cl.option("--verbose");
+/// assert_eq!(verbose, false);
+/// ```
+#[derive(Default)]
+pub struct Options {
+  /// The definition being built
+  cl_def: CommandLineDef,
+}
+
+impl Options {
+  /// Creates a new, empty set of options
+  #[inline]
+  pub fn new() -> Self {
+    Options::default()
+  }
+
+  /// Declares a flag. e.g. `-v`, `--verbose`
+  ///
+  /// # Arguments
+  ///
+  /// * `short_name` - The short name without the leading `-`. e.g. `v`
+  /// * `long_name` - The long name without the leading `--`. e.g. `verbose`
+  /// * `desc` - The description of the flag
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the names are not valid option names, or are already defined
+  #[inline]
+  pub fn optflag(&mut self, short_name:&str, long_name:&str, desc:&str) -> &mut Self {
+    self.add(short_name, long_name, None, Some(""), desc)
+  }
+
+  /// Declares a flag that may be given a value. The value is only taken when it is attached, such
+  /// as `-cauto` or `--color=auto`, and the flag given alone, or not specified, has an empty value.
+  /// Unlike getopts, a value given as a separate argument after the short name is not taken, so
+  /// `-c auto` is the flag followed by the argument `auto`.
+  ///
+  /// # Arguments
+  ///
+  /// * `short_name` - The short name without the leading `-`. e.g. `c`
+  /// * `long_name` - The long name without the leading `--`. e.g. `color`
+  /// * `desc` - The description of the option
+  /// * `hint` - The name of the value. e.g. `WHEN`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the names are not valid option names, or are already defined
+  #[inline]
+  pub fn optflagopt(&mut self, short_name:&str, long_name:&str, desc:&str, hint:&str) -> &mut Self {
+    self.optopt(short_name, long_name, desc, hint);
+    if let Some(&alias) = self.cl_def.option_defs.last().and_then(|od| od.aliases.first()) {
+      self.cl_def.optional_value(alias);
+    }
+    self
+  }
+
+  /// Declares an optional option that takes a value. It defaults to an empty value when not
+  /// specified.
+  ///
+  /// # Arguments
+  ///
+  /// * `short_name` - The short name without the leading `-`. e.g. `o`
+  /// * `long_name` - The long name without the leading `--`. e.g. `output`
+  /// * `desc` - The description of the option
+  /// * `hint` - The name of the value. e.g. `FILE`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the names are not valid option names, or are already defined
+  #[inline]
+  pub fn optopt(&mut self, short_name:&str, long_name:&str, desc:&str, hint:&str) -> &mut Self {
+    self.add(short_name, long_name, Some(hint), Some(""), desc)
+  }
+
+  /// Declares a required option that takes a value
+  ///
+  /// # Arguments
+  ///
+  /// * `short_name` - The short name without the leading `-`. e.g. `i`
+  /// * `long_name` - The long name without the leading `--`. e.g. `input`
+  /// * `desc` - The description of the option
+  /// * `hint` - The name of the value. e.g. `FILE`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the names are not valid option names, or are already defined
+  #[inline]
+  pub fn reqopt(&mut self, short_name:&str, long_name:&str, desc:&str, hint:&str) -> &mut Self {
+    self.add(short_name, long_name, Some(hint), None, desc)
+  }
+
+  #[inline]
+  fn add(&mut self, short_name:&str, long_name:&str, hint:Option<&str>, default:Option<&'static str>, desc:&str) -> &mut Self {
+    let mut aliases:Vec<&'static str> = Vec::with_capacity(2);
+    if !short_name.is_empty() {
+      aliases.push(leak(format!("-{short_name}")));
+    }
+    if !long_name.is_empty() {
+      aliases.push(leak(format!("--{long_name}")));
+    }
+    if hint.is_none() && aliases.iter().all(|alias| *alias == SHORT_HELP || *alias == LONG_HELP) {
+      return self;
+    }
+    let alias = aliases.first().copied();
+    self.cl_def.add_option(aliases, hint.map(|hint| leak(hint.into())), default, leak(desc.into()));
+    if let (Some(alias), Some(_)) = (alias, hint) {
+      // getopts accepts `--name=` as an empty value
+      self.cl_def.allow_empty_values(alias);
+    }
+    self
+  }
+}
+
+impl From<Options> for CommandLineDef {
+  #[inline]
+  fn from(options: Options) -> Self {
+    options.cl_def
+  }
+}
@@ -0,0 +1,370 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::min;
+use super::cl_def::CommandLineDef;
+use super::environment::Environment;
+use super::hyperlink::{hyperlink, hyperlinks_supported, Stream};
+use crate::text::right_to_left;
+
+/// Starts a right-to-left paragraph
+const RIGHT_TO_LEFT_MARK: &str = "\u{200F}";
+/// Starts text isolated as left to right
+const LEFT_TO_RIGHT_ISOLATE: &str = "\u{2066}";
+/// Ends isolated text
+const POP_DIRECTIONAL_ISOLATE: &str = "\u{2069}";
+
+/// Where the valid values of an option are shown in its help line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidValuesPosition {
+  /// The valid values are not shown
+  #[default]
+  Hidden,
+  /// The valid values are shown before the description
+  BeforeDescription,
+  /// The valid values are shown after the description
+  AfterDescription,
+}
+
+/// The layout of the option and subcommand lines of the help. By default the options are right
+/// aligned in a column as wide as the longest option, and separated from the description by ` : `.
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::{CommandLineDef, HelpLayout};
+/// let usage = CommandLineDef::new()
+///   .disable_help()
+///   .add_flag(vec!["-v","--verbose"], "Verbose output")
+///   .add_flag(vec!["-q"], "Quiet output")
+///   .help_layout(HelpLayout::new().indent(2).separator("  ").left_align(true))
+///   .usage("program");
+///
+/// assert_eq!(usage, "Usage: program [-qv]\n  -v, --verbose  Verbose output\n  -q             Quiet output");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HelpLayout {
+  /// The number of spaces before each line
+  indent:usize,
+  /// The text between the option column and the description
+  separator:&'static str,
+  /// True if the options are left aligned in their column
+  left_align:bool,
+  /// The maximum width of the option column. Longer options are on a line of their own.
+  max_option_width:Option<usize>,
+  /// Where the valid values of an option are shown
+  valid_values:ValidValuesPosition,
+  /// True if each line is the option and its description, with no indent or alignment
+  plain:bool,
+  /// True if the lines are right to left. If `None`, they are right to left when the locale used
+  /// for messages is.
+  right_to_left:Option<bool>,
+  /// True if the options link to their documentation. If `None`, they do when the terminal
+  /// supports hyperlinks.
+  hyperlinks:Option<bool>,
+}
+
+impl Default for HelpLayout {
+  fn default() -> Self {
+    HelpLayout {
+      indent:0,
+      separator:" : ",
+      left_align:false,
+      max_option_width:None,
+      valid_values:ValidValuesPosition::default(),
+      plain:false,
+      right_to_left:None,
+      hyperlinks:None,
+    }
+  }
+}
+
+impl HelpLayout {
+  /// Creates the default layout
+  #[inline]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Sets the number of spaces before each line
+  #[inline]
+  pub fn indent(mut self, indent:usize) -> Self {
+    self.indent = indent;
+    self
+  }
+
+  /// Sets the text between the option column and the description. e.g. `" : "`
+  #[inline]
+  pub fn separator(mut self, separator:&'static str) -> Self {
+    self.separator = separator;
+    self
+  }
+
+  /// Left aligns the options in their column, instead of right aligning them
+  #[inline]
+  pub fn left_align(mut self, left_align:bool) -> Self {
+    self.left_align = left_align;
+    self
+  }
+
+  /// Sets the maximum width of the option column. An option longer than the width is on a line of
+  /// its own, with the description on the next line after the column.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, HelpLayout};
+  /// let usage = CommandLineDef::new()
+  ///   .disable_help()
+  ///   .add_flag(vec!["-v"], "Verbose output")
+  ///   .add_option(vec!["--database-url"], Some("url"), None, "The database")
+  ///   .help_layout(HelpLayout::new().max_option_width(4))
+  ///   .usage("program");
+  ///
+  /// assert_eq!(usage, "Usage: program [-v] --database-url <url>\n  -v : Verbose output\n--database-url <url>\n     : The database (required)");
+  /// ```
+  #[inline]
+  pub fn max_option_width(mut self, width:usize) -> Self {
+    self.max_option_width = Some(width);
+    self
+  }
+
+  /// Sets where the valid values of an option are shown
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, HelpLayout, ValidValuesPosition};
+  /// let usage = CommandLineDef::new()
+  ///   .disable_help()
+  ///   .add_option(vec!["--level"], Some("level"), Some("low"), "The level")
+  ///   .valid_values("--level", vec!["low","high"])
+  ///   .help_layout(HelpLayout::new().valid_values(ValidValuesPosition::AfterDescription))
+  ///   .usage("program");
+  ///
+  /// assert!(usage.ends_with("--level <level> : The level [values: low, high] [default: low]"));
+  /// ```
+  #[inline]
+  pub fn valid_values(mut self, position:ValidValuesPosition) -> Self {
+    self.valid_values = position;
+    self
+  }
+
+  /// Shows each option or subcommand on a line of its own, followed by `: ` and its description,
+  /// with no indent or column alignment, for screen readers and narrow terminals. The indent,
+  /// separator, alignment and maximum width are ignored.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, HelpLayout};
+  /// let usage = CommandLineDef::new()
+  ///   .disable_help()
+  ///   .add_flag(vec!["-v","--verbose"], "Verbose output")
+  ///   .add_flag(vec!["-q"], "Quiet output")
+  ///   .help_layout(HelpLayout::new().plain(true))
+  ///   .usage("program");
+  ///
+  /// assert_eq!(usage, "Usage: program [-qv]\n-v, --verbose: Verbose output\n-q: Quiet output");
+  /// ```
+  #[inline]
+  pub fn plain(mut self, plain:bool) -> Self {
+    self.plain = plain;
+    self
+  }
+
+  /// Lays out the lines right to left, as they are by default when the text used for messages is
+  /// right to left, such as a catalog registered for `ar` or `he` with `register_messages`. Each line starts with a right-to-left mark and each
+  /// option is isolated as left-to-right text, so terminals that support bidirectional text show
+  /// the options in a column on the right, aligned against the descriptions on their left, instead
+  /// of reordering the option and description runs within the line.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, HelpLayout};
+  /// let usage = CommandLineDef::new()
+  ///   .disable_help()
+  ///   .add_flag(vec!["-v","--verbose"], "פלט מפורט")
+  ///   .add_flag(vec!["-q"], "פלט שקט")
+  ///   .help_layout(HelpLayout::new().right_to_left(true))
+  ///   .usage("program");
+  ///
+  /// assert!(usage.ends_with("\n\u{200F}\u{2066}-v, --verbose\u{2069} : פלט מפורט\n\u{200F}           \u{2066}-q\u{2069} : פלט שקט"));
+  /// ```
+  ///
+  /// The lines are right to left when the locale is and the application registered messages for
+  /// it, unless the layout says otherwise. Without the messages the help is in English, left to
+  /// right.
+  ///
+  /// ```
+  /// # #[cfg(feature = "i18n")] {
+  /// use cl_parse::{register_messages, set_locale, CommandLineDef, HelpLayout};
+  /// set_locale("ar-EG");
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.disable_help().add_flag(vec!["-v"], "verbose.description");
+  /// assert!(cl_def.usage("program").ends_with("\n-v : verbose.description"));
+  ///
+  /// register_messages("ar", &[("verbose.description", "إخراج مفصل")]);
+  /// assert!(cl_def.usage("program").ends_with("\n\u{200F}\u{2066}-v\u{2069} : إخراج مفصل"));
+  /// assert!(cl_def.help_layout(HelpLayout::new().right_to_left(false)).usage("program").ends_with("\n-v : إخراج مفصل"));
+  /// # }
+  /// ```
+  #[inline]
+  pub fn right_to_left(mut self, right_to_left:bool) -> Self {
+    self.right_to_left = Some(right_to_left);
+    self
+  }
+
+  /// Writes the options that have a documentation URL, and their `see docs` references, as OSC 8
+  /// hyperlinks to the URL. By default they are hyperlinks when the text goes to a terminal known
+  /// to support them, stdout for the help and stderr for the errors, and otherwise the URL is shown
+  /// as plain text after the description. The text written to the writers set with `with_output`
+  /// only has hyperlinks when they are enabled here.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, HelpLayout};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def
+  ///   .disable_help()
+  ///   .add_flag(vec!["-v"], "Verbose output")
+  ///   .doc_url("-v", "https://example.com/v");
+  ///
+  /// let usage = cl_def.help_layout(HelpLayout::new().hyperlinks(true)).usage("program");
+  /// assert!(usage.ends_with(concat!("\x1b]8;;https://example.com/v\x1b\\-v\x1b]8;;\x1b\\ : Verbose output ",
+  ///   "[\x1b]8;;https://example.com/v\x1b\\see docs\x1b]8;;\x1b\\]")));
+  ///
+  /// let usage = cl_def.help_layout(HelpLayout::new().hyperlinks(false)).usage("program");
+  /// assert!(usage.ends_with("-v : Verbose output [docs: https://example.com/v]"));
+  /// ```
+  #[inline]
+  pub fn hyperlinks(mut self, hyperlinks:bool) -> Self {
+    self.hyperlinks = Some(hyperlinks);
+    self
+  }
+
+  /// Returns where the valid values of an option are shown
+  #[inline]
+  pub(crate) fn valid_values_position(&self) -> ValidValuesPosition {
+    self.valid_values
+  }
+
+  /// Returns true if the options in the help lines link to their documentation
+  #[inline]
+  pub(crate) fn hyperlinks_enabled(&self) -> bool {
+    self.hyperlinks == Some(true)
+  }
+
+  /// Returns the layout with hyperlinks, unless it sets them itself, when the text is written to
+  /// a stream that supports them. None is a writer set with `with_output`, or no stream.
+  #[inline]
+  pub(crate) fn for_stream(self, stream:Option<Stream>) -> Self {
+    match self.hyperlinks {
+      Some(_) => self,
+      None => self.hyperlinks(stream.is_some_and(hyperlinks_supported)),
+    }
+  }
+
+  /// Returns a help line, with the option in a column of the width, linked to the url when
+  /// hyperlinks are enabled. Right to left, each line starts with a right-to-left mark and the
+  /// option is isolated as left-to-right text.
+  pub(crate) fn line(&self, option:&str, url:Option<&str>, description:&str, width:usize) -> String {
+    let linked = match url {
+      Some(url) if self.hyperlinks_enabled() => hyperlink(url, option),
+      _ => option.to_string(),
+    };
+    let right_to_left = self.right_to_left.unwrap_or_else(right_to_left);
+    let (open, close) = match right_to_left {
+      true => (LEFT_TO_RIGHT_ISOLATE, POP_DIRECTIONAL_ISOLATE),
+      false => ("", ""),
+    };
+    let line = if self.plain {
+      format!("{open}{linked}{close}: {description}")
+    } else {
+      let width = self.max_option_width.map_or(width, |max| min(width, max));
+      let (indent, separator) = (" ".repeat(self.indent), self.separator);
+      // the column is measured in chars, so non-ASCII aliases and value names line up
+      let option_width = option.chars().count();
+      let padding = " ".repeat(width.saturating_sub(option_width));
+      if option_width > width {
+        format!("{indent}{open}{linked}{close}\n{indent}{:width$}{separator}{description}", "")
+      } else if self.left_align {
+        format!("{indent}{open}{linked}{close}{padding}{separator}{description}")
+      } else {
+        format!("{indent}{padding}{open}{linked}{close}{separator}{description}")
+      }
+    };
+    match right_to_left {
+      true => line.split('\n').map(|line| format!("{RIGHT_TO_LEFT_MARK}{line}")).collect::<Vec<String>>().join("\n"),
+      false => line,
+    }
+  }
+}
+
+impl CommandLineDef {
+  /// Sets the layout of the option and subcommand lines of the help
+  ///
+  /// # Arguments
+  ///
+  /// * `layout` - The layout of the help lines
+  #[inline]
+  pub fn help_layout(&mut self, layout:HelpLayout) -> &mut Self {
+    self.help_layout = layout;
+    self
+  }
+
+  /// Shows the help with the plain layout when the environment variable is set to a value other
+  /// than empty or `0`, so users of screen readers can choose it without the program having a
+  /// setting for it. See [`HelpLayout::plain`].
+  ///
+  /// # Arguments
+  ///
+  /// * `var` - The environment variable choosing the plain help. e.g. `PLAIN_HELP`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// # #[cfg(feature = "std")] {
+  /// use cl_parse::CommandLineDef;
+  /// std::env::set_var("DOC_PLAIN_HELP", "1");
+  /// let usage = CommandLineDef::new()
+  ///   .disable_help()
+  ///   .add_option(vec!["-f","--file"], Some("path"), Some("-"), "The file to read")
+  ///   .plain_help_env_var("DOC_PLAIN_HELP")
+  ///   .usage("program");
+  ///
+  /// assert!(usage.ends_with("\n-f, --file <path>: The file to read [default: -]"));
+  /// # }
+  /// ```
+  #[inline]
+  pub fn plain_help_env_var(&mut self, var:&'static str) -> &mut Self {
+    self.plain_help_env_var = Some(var);
+    self
+  }
+
+  /// Returns the layout of the help written to the stream, which is plain if the environment
+  /// variable set with `plain_help_env_var` is set
+  pub(crate) fn effective_help_layout(&self, stream:Stream) -> HelpLayout {
+    let layout = match self.plain_help_env_var.and_then(|var| Environment::default().var(var)) {
+      Some(value) if !value.is_empty() && value != "0" => self.help_layout.plain(true),
+      _ => self.help_layout,
+    };
+    layout.for_stream(Some(stream).filter(|_| !self.has_output()))
+  }
+
+  /// Returns true if the generated text goes to the writers set with `with_output`
+  #[cfg(feature = "std")]
+  #[inline]
+  fn has_output(&self) -> bool {
+    self.output.is_some()
+  }
+
+  /// Without std the text is never written, so there are no writers.
+  #[cfg(not(feature = "std"))]
+  #[inline]
+  fn has_output(&self) -> bool {
+    false
+  }
+}
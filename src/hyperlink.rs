@@ -0,0 +1,48 @@
+use alloc::format;
+use alloc::string::String;
+
+/// Returns the text as an OSC 8 hyperlink to the url, which terminals that support it display as
+/// the text and open when it is clicked
+#[inline]
+pub(crate) fn hyperlink(url:&str, text:&str) -> String {
+  format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// The standard stream generated text is written to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stream {
+  /// The help, written to stdout
+  Stdout,
+  /// The errors, written to stderr
+  Stderr,
+}
+
+/// Returns true if the stream is a terminal known to support OSC 8 hyperlinks. `FORCE_HYPERLINK`
+/// set to a value other than `0` enables them, and set to `0` disables them.
+#[cfg(feature = "std")]
+pub(crate) fn hyperlinks_supported(stream:Stream) -> bool {
+  use std::io::IsTerminal;
+  let var = |var| std::env::var(var).ok().filter(|value| !value.is_empty());
+  if let Some(force) = var("FORCE_HYPERLINK") {
+    return force != "0";
+  }
+  let terminal = match stream {
+    Stream::Stdout => std::io::stdout().is_terminal(),
+    Stream::Stderr => std::io::stderr().is_terminal(),
+  };
+  if !terminal {
+    return false;
+  }
+  ["DOMTERM", "WT_SESSION", "KONSOLE_VERSION"].iter().any(|name| var(name).is_some())
+    || var("VTE_VERSION").and_then(|version| version.parse::<u32>().ok()).is_some_and(|version| version >= 5000)
+    || var("TERM_PROGRAM").is_some_and(|program| ["iTerm.app", "WezTerm", "vscode", "ghostty", "Hyper"].contains(&program.as_str()))
+    || var("TERM").is_some_and(|term| ["xterm-kitty", "alacritty", "foot"].contains(&term.as_str()))
+}
+
+/// Without std, the terminal cannot be detected and hyperlinks are only written when the layout
+/// enables them.
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn hyperlinks_supported(_stream:Stream) -> bool {
+  false
+}
@@ -0,0 +1,206 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+use super::cl_def::CommandLineDef;
+use super::option_def::OptionDef;
+use super::subcommand::Subcommand;
+
+/// A read-only view of an option in a CommandLineDef
+#[derive(Clone, Copy)]
+pub struct OptionInfo<'a> {
+  od:&'a OptionDef,
+}
+
+impl<'a> OptionInfo<'a> {
+  /// Returns the aliases of the option, in the order they were defined. e.g. `-f`, `--file`
+  #[inline]
+  pub fn aliases(&self) -> &'a [&'static str] {
+    &self.od.aliases
+  }
+
+  /// Returns the name of the option's value, or `None` if the option is a flag
+  #[inline]
+  pub fn value_name(&self) -> Option<&'static str> {
+    self.od.value_name
+  }
+
+  /// Returns the value used when the option is not specified, or `None` if the option is required
+  /// or a flag
+  #[inline]
+  pub fn default_value(&self) -> Option<&'static str> {
+    self.od.value_name.and(self.od.default_value)
+  }
+
+  /// Returns the description of the option
+  #[inline]
+  pub fn description(&self) -> &'static str {
+    self.od.description
+  }
+
+  /// Returns true if the option must be specified
+  #[inline]
+  pub fn is_required(&self) -> bool {
+    self.od.value_name.is_some() && self.od.default_value.is_none()
+  }
+
+  /// Returns true if the option is a flag that counts its occurrences
+  #[inline]
+  pub fn is_counter(&self) -> bool {
+    self.od.counting
+  }
+
+  /// Returns true if the option is the built-in help option
+  #[inline]
+  pub fn is_help(&self) -> bool {
+    self.od.help
+  }
+
+  /// Returns true if the option is deprecated
+  #[inline]
+  pub fn is_deprecated(&self) -> bool {
+    self.od.deprecated
+  }
+
+  /// Returns the values accepted by the option. If empty, any value is accepted.
+  #[inline]
+  pub fn valid_values(&self) -> &'a [&'static str] {
+    &self.od.valid_values
+  }
+
+  /// Returns the range of the number of values the option takes. Flags take one value.
+  #[inline]
+  pub fn arity(&self) -> RangeInclusive<usize> {
+    self.od.arity.clone()
+  }
+
+  /// Returns true if the option only takes the minimum number of values
+  #[inline]
+  pub fn is_lazy(&self) -> bool {
+    self.od.lazy
+  }
+
+  /// Returns the environment variable used for the option's value, if any
+  #[inline]
+  pub fn env(&self) -> Option<&'static str> {
+    self.od.env
+  }
+}
+
+/// A read-only view of a subcommand in a CommandLineDef
+#[derive(Clone, Copy)]
+pub struct SubcommandInfo<'a> {
+  subcommand:&'a Subcommand,
+}
+
+impl<'a> SubcommandInfo<'a> {
+  /// Returns the names of the subcommand. The first is the canonical name, and the others are
+  /// aliases.
+  #[inline]
+  pub fn names(&self) -> &'a [&'static str] {
+    &self.subcommand.names
+  }
+
+  /// Returns the one line summary of the subcommand
+  #[inline]
+  pub fn summary(&self) -> &'static str {
+    self.subcommand.summary
+  }
+
+  /// Returns the definition of the subcommand's options, arguments and subcommands
+  #[inline]
+  pub fn def(&self) -> &'a CommandLineDef {
+    &self.subcommand.cl_def
+  }
+}
+
+impl CommandLineDef {
+  /// Returns the options of this definition, in the order they were defined. The built-in help
+  /// option is included, and can be recognised with `OptionInfo::is_help`.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.add_option(vec!["-f","--file"], Some("path"), None, "The file to read");
+  ///
+  /// let file = cl_def.options().find(|option| !option.is_help()).unwrap();
+  /// assert_eq!(file.aliases(), ["-f", "--file"]);
+  /// assert_eq!(file.value_name(), Some("path"));
+  /// assert_eq!(file.is_required(), true);
+  /// ```
+  pub fn options(&self) -> impl Iterator<Item=OptionInfo<'_>> {
+    self.option_defs.iter().map(|od| OptionInfo { od })
+  }
+
+  /// Returns the names of the arguments of this definition, in order
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.add_argument("src").add_argument("dest");
+  ///
+  /// assert_eq!(cl_def.argument_names(), ["src", "dest"]);
+  /// ```
+  #[inline]
+  pub fn argument_names(&self) -> &[&'static str] {
+    &self.argument_names
+  }
+
+  /// Returns the subcommands of this definition, in the order they were added
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.add_subcommand(vec!["remove","rm"], "Remove files", CommandLineDef::new());
+  ///
+  /// let remove = cl_def.subcommands().next().unwrap();
+  /// assert_eq!(remove.names(), ["remove", "rm"]);
+  /// assert_eq!(remove.summary(), "Remove files");
+  /// ```
+  pub fn subcommands(&self) -> impl Iterator<Item=SubcommandInfo<'_>> {
+    self.subcommands.iter().map(|subcommand| SubcommandInfo { subcommand })
+  }
+
+  /// Returns every command in the tree rooted at this definition, depth first, with the full
+  /// command path, so documentation and completion for every command can be generated in one pass.
+  /// The first entry is this definition, with the program name as its path.
+  ///
+  /// # Arguments
+  ///
+  /// * `program_name` - The name of the program, used as the start of every path. e.g. `git`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let mut stash = CommandLineDef::new();
+  /// stash.add_subcommand(vec!["pop"], "Apply and remove a stash", CommandLineDef::new());
+  ///
+  /// let mut git = CommandLineDef::new();
+  /// git
+  ///   .add_subcommand(vec!["stash"], "Stash changes", stash)
+  ///   .add_subcommand(vec!["status"], "Show the status", CommandLineDef::new());
+  ///
+  /// let paths:Vec<String> = git.command_tree("git").into_iter().map(|(path, _)| path).collect();
+  /// assert_eq!(paths, vec!["git", "git stash", "git stash pop", "git status"]);
+  /// ```
+  pub fn command_tree(&self, program_name:&str) -> Vec<(String, &CommandLineDef)> {
+    let mut commands = Vec::default();
+    self.collect_commands(program_name.to_string(), &mut commands);
+    commands
+  }
+
+  /// Adds this definition and its subcommands to the commands, depth first
+  fn collect_commands<'a>(&'a self, path:String, commands:&mut Vec<(String, &'a CommandLineDef)>) {
+    commands.push((path.clone(), self));
+    for subcommand in &self.subcommands {
+      subcommand.cl_def.collect_commands(format!("{path} {}", subcommand.names[0]), commands);
+    }
+  }
+}
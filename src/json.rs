@@ -0,0 +1,22 @@
+use alloc::string::String;
+use core::fmt::Write;
+
+/// Returns the value as a quoted JSON string
+#[inline]
+pub(crate) fn json_string(value:&str) -> String {
+  let mut json = String::with_capacity(value.len() + 2);
+  json.push('"');
+  for c in value.chars() {
+    match c {
+      '"' => json.push_str("\\\""),
+      '\\' => json.push_str("\\\\"),
+      '\n' => json.push_str("\\n"),
+      '\r' => json.push_str("\\r"),
+      '\t' => json.push_str("\\t"),
+      c if c.is_control() => { let _ = write!(json, "\\u{:04x}", c as u32); },
+      c => json.push(c),
+    }
+  }
+  json.push('"');
+  json
+}
@@ -0,0 +1,153 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::any::type_name;
+use super::cl_def::CommandLineDef;
+use super::json::json_string;
+use super::option_def::OptionDef;
+use super::{from_str_lenient, VALUE_DELIMITER};
+
+impl CommandLineDef {
+  /// Returns a JSON Schema describing the options and arguments accepted by this definition, so
+  /// other tools can validate or render forms for the program's parameters.
+  ///
+  /// The schema describes an object with `options` and `arguments` properties. Each option is
+  /// named after its first long alias without the leading dashes, or its short alias if it has no
+  /// long alias. Flags are booleans, counters are integers and options with values are strings,
+  /// or integers, numbers or booleans for typed options of those types. Options that take several
+  /// values are arrays of their values, with the number of values they take. The arguments are an
+  /// array of strings, in order, followed by the strings of the variadic argument if there is one.
+  /// The built-in help option is not included.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let schema = CommandLineDef::new()
+  ///   .add_flag(vec!["-v","--verbose"], "Verbose output")
+  ///   .add_option(vec!["-f","--file"], Some("path"), None, "The file to read")
+  ///   .add_argument("dest")
+  ///   .to_json_schema();
+  ///
+  /// assert!(schema.contains(r#""verbose": {"type": "boolean", "description": "Verbose output", "default": false}"#));
+  /// assert!(schema.contains(r#""required": ["file"]"#));
+  /// assert!(schema.contains(r#"{"title": "dest", "type": "string"}"#));
+  /// ```
+  pub fn to_json_schema(&self) -> String {
+    let mut properties:Vec<String> = Vec::default();
+    let mut required:Vec<String> = Vec::default();
+
+    for od in self.option_defs.iter().filter(|od| !od.help) {
+      let name = json_string(od.key());
+      let description = json_string(od.description);
+      let property = match (od.value_name, od.default_value) {
+        (None, _) if od.counting => format!(r#"{name}: {{"type": "integer", "description": {description}, "minimum": 0, "default": 0}}"#),
+        (None, _) => format!(r#"{name}: {{"type": "boolean", "description": {description}, "default": false}}"#),
+        (Some(_), Some(default)) => format!(r#"{name}: {{{}, "description": {description}, "default": {}}}"#, value_schema(od), json_default(od, default)),
+        (Some(_), None) => {
+          required.push(name.clone());
+          format!(r#"{name}: {{{}, "description": {description}}}"#, value_schema(od))
+        },
+      };
+      properties.push(property);
+    }
+
+    let arguments:Vec<String> = self.argument_names.iter().enumerate()
+      .filter(|(idx, _)| self.variadic != Some(*idx))
+      .map(|(_, argument_name)| format!(r#"{{"title": {}, "type": "string"}}"#, json_string(argument_name)))
+      .collect();
+    let variadic = match self.variadic {
+      Some(idx) => format!(r#"{{"title": {}, "type": "string"}}"#, json_string(self.argument_names[idx])),
+      None => String::from("false"),
+    };
+    // the arguments that can be given with an option instead may be missing
+    let min_items = (0..self.argument_names.len())
+      .filter(|idx| self.variadic != Some(*idx) && self.alternatives.iter().all(|(argument, _)| argument != idx))
+      .count();
+
+    format!(r#"{{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "type": "object",
+  "properties": {{
+    "options": {{
+      "type": "object",
+      "properties": {{{}}},
+      "required": [{}],
+      "additionalProperties": false
+    }},
+    "arguments": {{
+      "type": "array",
+      "prefixItems": [{}],
+      "items": {},
+      "minItems": {}
+    }}
+  }},
+  "required": ["options", "arguments"]
+}}"#,
+      join_lines(&properties, 8),
+      required.join(", "),
+      join_lines(&arguments, 8),
+      variadic,
+      min_items)
+  }
+}
+
+/// Returns the JSON Schema type of the values of a typed option, from the name of its type
+#[inline]
+pub(crate) fn json_type<T>() -> &'static str {
+  match type_name::<T>() {
+    "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => "integer",
+    "f32" | "f64" => "number",
+    "bool" => "boolean",
+    _ => "string",
+  }
+}
+
+/// Returns the schema keywords of an option's value, an array of its values with their number for
+/// an option that takes several values. e.g. `"type": "integer"`
+#[inline]
+fn value_schema(od:&OptionDef) -> String {
+  if od.arity == (1..=1) {
+    return format!(r#""type": {}"#, json_string(od.json_type));
+  }
+  let mut schema = format!(r#""type": "array", "items": {{"type": {}}}, "minItems": {}"#, json_string(od.json_type), od.arity.start());
+  if *od.arity.end() != usize::MAX {
+    schema.push_str(&format!(r#", "maxItems": {}"#, od.arity.end()));
+  }
+  schema
+}
+
+/// Returns an option's default value as JSON of the option's type, an array of the comma separated
+/// values for an option that takes several values
+#[inline]
+fn json_default(od:&OptionDef, default:&str) -> String {
+  if od.arity == (1..=1) {
+    return json_value(od.json_type, default);
+  }
+  let values:Vec<String> = default.split(VALUE_DELIMITER).filter(|_| !default.is_empty())
+    .map(|value| json_value(od.json_type, value))
+    .collect();
+  format!("[{}]", values.join(", "))
+}
+
+/// Returns a value as JSON of the type, or as a string if it is not a value of the type
+#[inline]
+fn json_value(json_type:&str, value:&str) -> String {
+  let literal = match json_type {
+    "integer" => from_str_lenient::<i128>(value).map(|integer| integer.to_string()).ok(),
+    "number" => from_str_lenient::<f64>(value).ok().filter(|number| number.is_finite()).map(|number| number.to_string()),
+    "boolean" => from_str_lenient::<bool>(value).map(|boolean| boolean.to_string()).ok(),
+    _ => None,
+  };
+  literal.unwrap_or_else(|| json_string(value))
+}
+
+/// Joins the values with one per line at the indent, or returns an empty string if there are none
+#[inline]
+fn join_lines(values:&[String], indent:usize) -> String {
+  if values.is_empty() {
+    return String::default();
+  }
+  let separator = format!(",\n{:indent$}", "");
+  format!("\n{:indent$}{}\n{:close$}", "", values.join(&separator), "", close = indent - 2)
+}
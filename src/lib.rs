@@ -11,6 +11,8 @@
 //!
 //!  - option aliases. e.g. -f, --file
 //!  - options with negative values. e.g. --increment -1
+//!  - negative numbers as arguments. e.g. -1 or -.5
+//!  - long option values attached with '='. e.g. --file=in.txt
 //!  - flag concatenation. i.e. -xvgf  is equivalent to -x -v -g -f
 //!  - Auto usage message generation
 //!  - Auto help message generation
@@ -20,17 +22,60 @@
 //!  - option and argument validation. i.e. only defined options and arguments can be used
 //!  - unordered options and arguments
 //!  - retrieving the option or argument in the target type. e.g. i32, String, etc.
+//!  - booleans spelled true/false, yes/no, on/off or 1/0, in any case
+//!  - integers written in hex, octal or binary. e.g. 0xff, 0o755, 0b1010
+//!  - errors returned as values with `try_parse`, for environments such as wasm
+//!
+//! # Features
+//!
+//!  - `std` (default) - Uses the standard library. Disabling it builds the parser with `#![no_std]`
+//!    and `alloc`.
+//!  - `i18n` (default) - Localizes messages using the system locale, or the locale given to
+//!    `set_locale`. Disabling it uses English messages and removes the `sys-locale` and
+//!    `lazy_static` dependencies.
+//!  - `clap` - Converts definitions to and from a subset of clap's `Command` model.
+//!  - `toml`, `yaml` - Loads definitions from TOML or YAML documents.
+//!  - `dotenv` - Loads environment variables from a `.env` file, without modifying the process
+//!    environment.
+//!  - `log` - Sets the `log` crate's maximum level from the verbosity or a `--log-level` option.
+//!  - `env_logger` - Also initializes `env_logger` with that level.
+//!  - `glob` - Expands wildcards in arguments, for shells such as cmd.exe that do not, and adds
+//!    options for glob patterns.
+//!  - `semver` - Adds options for semantic versions and version requirements.
+//!  - `uuid` - Adds options for UUIDs.
 //!
 //! # Examples
 //!
 #![deny(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
-const SHORT_OPTION: &'static str = "-";
-const LONG_OPTION: &'static str = "--";
-const SHORT_HELP: &'static str = "-h";
-const LONG_HELP: &'static str = "--help";
-const TRUE: &'static str = "true";
-const FALSE: &'static str = "false";
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use core::str::FromStr;
+
+const SHORT_OPTION: &str = "-";
+const LONG_OPTION: &str = "--";
+const PLUS_OPTION: &str = "+";
+const SHORT_HELP: &str = "-h";
+const LONG_HELP: &str = "--help";
+const SLASH_HELP: &str = "/?";
+const SHORT_VERSION: &str = "-V";
+const LONG_VERSION: &str = "--version";
+const SHORT_VERBOSE: &str = "-v";
+const LONG_VERBOSE: &str = "--verbose";
+const SHORT_QUIET: &str = "-q";
+const LONG_QUIET: &str = "--quiet";
+const LONG_LOG_LEVEL: &str = "--log-level";
+const SECTION_SEPARATOR: char = '.';
+const VALUE_SEPARATOR: char = '=';
+const VALUE_DELIMITER: char = ',';
+const COLON_VALUE_SEPARATOR: char = ':';
+const SLASH_OPTION: &str = "/";
+const TRUE: &str = "true";
+const FALSE: &str = "false";
 
 /// # Option Def
 ///
@@ -47,14 +92,261 @@ mod cl_def;
 /// `command_line` is a collection of utilities for processing commandline arguments
 mod command_line;
 
+/// # Parse Error
+///
+/// `parse_error` describes the errors returned when a commandline cannot be parsed
+mod parse_error;
+
+/// # Warning
+///
+/// `warning` describes the non-fatal issues found while parsing a commandline
+mod warning;
+
+/// # Static Command Line Def
+///
+/// `static_cl_def` allows a CommandLineDef to be declared in a static
+#[cfg(feature = "std")]
+mod static_cl_def;
+
+/// # Clap Interop
+///
+/// `clap_interop` converts between a CommandLineDef and a clap `Command`
+#[cfg(feature = "clap")]
+mod clap_interop;
+
+/// # Color
+///
+/// `color` adds the standard `--color` option
+mod color;
+
+/// # Config
+///
+/// `config` writes config files for a CommandLineDef
+#[cfg(feature = "std")]
+pub mod config;
+
+/// # Completion Spec
+///
+/// `completion_spec` exports a CommandLineDef as a Fig or carapace completion spec
+mod completion_spec;
+
+/// # Def Document
+///
+/// `def_document` loads a CommandLineDef from a YAML or TOML document
+#[cfg(any(feature = "toml", feature = "yaml"))]
+mod def_document;
+
+/// # Logging
+///
+/// `logging` sets the level of the `log` crate from the commandline
+#[cfg(feature = "log")]
+mod logging;
+
+/// # Environment
+///
+/// `environment` reads the environment variables used by a CommandLineDef
+mod environment;
+
+/// # Getopts
+///
+/// `getopts` is a compatibility shim for migrating from the `getopts` crate
+pub mod getopts;
+
 pub use cl_def::CommandLineDef;
+pub use color::ColorChoice;
 pub use command_line::CommandLine;
+pub use compiled_def::CompiledDef;
+pub use def_error::{DefError, DefErrorKind};
+pub use from_values::FromValues;
+pub use help_layout::{HelpLayout, ValidValuesPosition};
+pub use introspection::{OptionInfo, SubcommandInfo};
+pub use multi_call::MultiCall;
+pub use output_format::OutputFormat;
+pub use parse_error::{ErrorKind, ParseError};
+pub use source::Source;
+pub use typed_option::TypedOption;
+pub use value_parser::ValueParser;
+pub use warning::{Warning, WarningKind};
+#[cfg(feature = "std")]
+pub use error_behavior::ErrorBehavior;
+#[cfg(feature = "std")]
+pub use static_cl_def::StaticCommandLineDef;
+#[cfg(feature = "i18n")]
+pub use text::{register_messages, set_locale};
+
+/// # Compiled Def
+///
+/// `compiled_def` checks a CommandLineDef, and returns it as a definition that can no longer change
+mod compiled_def;
+
+/// # Def Error
+///
+/// `def_error` describes the errors in a commandline definition
+mod def_error;
+
+/// # Error Behavior
+///
+/// `error_behavior` chooses whether `parse` panics, exits or unwinds with an invalid commandline
+#[cfg(feature = "std")]
+mod error_behavior;
+
+/// # From Values
+///
+/// `from_values` converts the values of an option that takes several values into a tuple
+mod from_values;
+
+/// # Help Layout
+///
+/// `help_layout` sets the layout of the option and subcommand lines of the help
+mod help_layout;
+
+/// # Hyperlink
+///
+/// `hyperlink` writes OSC 8 terminal hyperlinks, and detects the terminals that support them
+mod hyperlink;
+
+/// # Introspection
+///
+/// `introspection` exposes the options, arguments and subcommands of a CommandLineDef
+mod introspection;
+
+/// # Json
+///
+/// `json` contains helpers for writing JSON
+mod json;
+
+/// # Json Schema
+///
+/// `json_schema` exports a CommandLineDef as a JSON Schema
+mod json_schema;
+
+/// # Multi Call
+///
+/// `multi_call` selects a CommandLineDef from the program name, for busybox style binaries
+mod multi_call;
+
+/// # Output
+///
+/// `output` writes the generated text to stdout and stderr, or to writers set by the caller
+#[cfg(feature = "std")]
+mod output;
+
+/// # Output Format
+///
+/// `output_format` adds the standard `--output` option, choosing the format of the output
+mod output_format;
+
+/// # Packs
+///
+/// `packs` adds standard sets of options, so related tools share consistent options
+mod packs;
+
+/// # Paths
+///
+/// `paths` retrieves option values as paths, with `~` expanded
+#[cfg(feature = "std")]
+mod paths;
+
+/// # Repl
+///
+/// `repl` parses lines read interactively with a CommandLineDef
+mod repl;
+
+/// # Shell
+///
+/// `shell` quotes and splits values for POSIX shells and PowerShell, and reads piped args
+pub mod shell;
+
+/// # Source
+///
+/// `source` describes where option values come from
+mod source;
+
+/// # Subcommand
+///
+/// `subcommand` adds subcommands to a CommandLineDef
+mod subcommand;
+
+/// # Testing
+///
+/// `testing` contains helpers for testing commandline definitions
+pub mod testing;
 
 mod text;
 
+/// # Typed Option
+///
+/// `typed_option` defines options whose values are converted to a type declared up front
+mod typed_option;
+
+/// # Value Kinds
+///
+/// `value_kinds` adds options for values such as semantic versions, UUIDs and glob patterns,
+/// checked when parsing
+#[cfg(any(feature = "semver", feature = "uuid", feature = "glob"))]
+mod value_kinds;
+
+/// # Value Parser
+///
+/// `value_parser` converts values with `TryFrom` or a ValueParser
+mod value_parser;
+
+/// # Usage Parser
+///
+/// `usage_parser` creates a CommandLineDef from a usage string
+mod usage_parser;
+
+/// Leaks a string built at runtime so it can be used in a definition. Definitions are expected
+/// to live for the life of the program.
 #[inline]
-fn format_usage(msg: &str, usage: &str) -> String {
-  format!("{}\n{}", msg, usage)
+fn leak(s: String) -> &'static str {
+  Box::leak(s.into_boxed_str())
+}
+
+/// Returns the file name of a program path without its extension. e.g. `gunzip` for
+/// `/usr/bin/gunzip` or `C:\bin\gunzip.exe`
+fn program_stem(path: &str) -> &str {
+  let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+  match file_name.rfind('.') {
+    Some(0) | None => file_name,
+    Some(dot) => &file_name[..dot],
+  }
+}
+
+/// Converts a value with `FromStr`. If that fails and the value is a boolean spelled `yes`, `no`,
+/// `on`, `off`, `1` or `0`, in any case, it is converted as `true` or `false`, so booleans accept
+/// the spellings used in environment variables and config files. If the value is an integer
+/// literal with a `0x`, `0o` or `0b` prefix, it is converted from its decimal value.
+fn from_str_lenient<T: FromStr>(value: &str) -> Result<T, T::Err> {
+  T::from_str(value).or_else(|e| {
+    let spelling = |word: &str, one: &str, words: &[&str]| {
+      [word, one].iter().chain(words).any(|s| s.eq_ignore_ascii_case(value))
+    };
+    match (spelling(TRUE, "1", text::T.true_words()), spelling(FALSE, "0", text::T.false_words()), radix_literal(value)) {
+      (true, _, _) => T::from_str(TRUE).map_err(|_| e),
+      (_, true, _) => T::from_str(FALSE).map_err(|_| e),
+      (_, _, Some(decimal)) => T::from_str(&decimal).map_err(|_| e),
+      _ => Err(e),
+    }
+  })
+}
+
+/// Returns the decimal value of an integer literal with a `0x`, `0o` or `0b` prefix, in either
+/// case, after an optional sign. e.g. `255` for `0xff` and `-5` for `-0b101`
+fn radix_literal(value: &str) -> Option<String> {
+  let (sign, literal) = match value.strip_prefix('-') {
+    Some(literal) => ("-", literal),
+    None => ("", value.strip_prefix('+').unwrap_or(value)),
+  };
+  let radix = match literal.get(..2)? {
+    "0x" | "0X" => 16,
+    "0o" | "0O" => 8,
+    "0b" | "0B" => 2,
+    _ => return None,
+  };
+  let digits = literal[2..].replace('_', "");
+  let magnitude = u128::from_str_radix(&digits, radix).ok()?;
+  Some(format!("{sign}{magnitude}"))
 }
 
 #[inline]
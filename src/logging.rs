@@ -0,0 +1,109 @@
+use alloc::vec;
+use log::LevelFilter;
+use super::cl_def::CommandLineDef;
+use super::command_line::CommandLine;
+use crate::text::T;
+use crate::LONG_LOG_LEVEL;
+
+impl CommandLineDef {
+  /// Adds a `--log-level <level>` option, to set the log level by name. When it is not specified,
+  /// [`CommandLine::log_level`] uses the verbosity instead.
+  ///
+  /// # Panics
+  ///
+  /// * Panics if `--log-level` is already defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// use log::LevelFilter;
+  /// let args=vec!["program".to_string(), "--log-level".to_string(), "debug".to_string()];
+  /// let cl = CommandLineDef::new().add_log_level().parse(args.into_iter());
+  /// assert_eq!(cl.log_level(), LevelFilter::Debug);
+  /// ```
+  #[inline]
+  pub fn add_log_level(&mut self) -> &mut Self {
+    self.add_option(vec![LONG_LOG_LEVEL], Some(T.log_level_value_name()), Some(""), T.log_level_description())
+  }
+}
+
+impl CommandLine {
+  /// Returns the log level set on the commandline. The `--log-level` option added by
+  /// `CommandLineDef::add_log_level` is used if it is specified. Otherwise the level is derived from
+  /// the verbosity, starting at `Warn` and moving one level per `-v` or `-q`.
+  ///
+  /// | verbosity | level   |
+  /// |-----------|---------|
+  /// | -2 or less| `Off`   |
+  /// | -1        | `Error` |
+  /// | 0         | `Warn`  |
+  /// | 1         | `Info`  |
+  /// | 2         | `Debug` |
+  /// | 3 or more | `Trace` |
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the `--log-level` value is not a log level
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// use log::LevelFilter;
+  /// let args=vec!["program".to_string(), "-v".to_string()];
+  /// let cl = CommandLineDef::new().add_verbosity().add_log_level().parse(args.into_iter());
+  /// assert_eq!(cl.log_level(), LevelFilter::Info);
+  /// ```
+  pub fn log_level(&self) -> LevelFilter {
+    if self.value(LONG_LOG_LEVEL).is_some_and(|level| !level.is_empty()) {
+      return self.option(LONG_LOG_LEVEL);
+    }
+    match self.verbosity() {
+      i8::MIN..=-2 => LevelFilter::Off,
+      -1 => LevelFilter::Error,
+      0 => LevelFilter::Warn,
+      1 => LevelFilter::Info,
+      2 => LevelFilter::Debug,
+      _ => LevelFilter::Trace,
+    }
+  }
+
+  /// Sets the maximum level of the `log` crate to [`CommandLine::log_level`]
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// use log::LevelFilter;
+  /// let args=vec!["program".to_string(), "-q".to_string()];
+  /// CommandLineDef::new().add_verbosity().parse(args.into_iter()).init_log();
+  /// assert_eq!(log::max_level(), LevelFilter::Error);
+  /// ```
+  #[inline]
+  pub fn init_log(&self) {
+    log::set_max_level(self.log_level());
+  }
+
+  /// Initializes `env_logger` with [`CommandLine::log_level`] as the default level. Module
+  /// specific directives in `RUST_LOG` still apply.
+  ///
+  /// # Panics
+  ///
+  /// * Panics if a logger has already been initialized
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// use log::LevelFilter;
+  /// let args=vec!["program".to_string(), "-vv".to_string()];
+  /// CommandLineDef::new().add_verbosity().parse(args.into_iter()).init_env_logger();
+  /// assert_eq!(log::max_level(), LevelFilter::Debug);
+  /// ```
+  #[cfg(feature = "env_logger")]
+  #[inline]
+  pub fn init_env_logger(&self) {
+    env_logger::Builder::from_default_env().filter_level(self.log_level()).init();
+  }
+}
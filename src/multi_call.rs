@@ -0,0 +1,159 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::iter::once;
+use super::cl_def::CommandLineDef;
+use super::command_line::CommandLine;
+use super::parse_error::{ErrorKind, ParseError};
+use super::{panic_msg, program_stem};
+use crate::text::T;
+
+/// Selects one of several CommandLineDefs from the program name, so a single binary installed
+/// under several names, e.g. hard linked as `gzip` and `gunzip`, can present a different
+/// commandline for each name. The program is chosen by the file name of the program path without
+/// its extension. If that is not one of the programs, the first arg names the program instead,
+/// e.g. `busybox gunzip file.gz`.
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::{CommandLineDef, MultiCall};
+/// let mut gzip = CommandLineDef::new();
+/// gzip.add_flag(vec!["-k","--keep"], "Keep the input file").add_argument("file");
+/// let mut gunzip = CommandLineDef::new();
+/// gunzip.add_flag(vec!["-t","--test"], "Test the compressed file").add_argument("file");
+///
+/// let mut multi_call = MultiCall::new();
+/// multi_call.add_program("gzip", gzip).add_program("gunzip", gunzip);
+///
+/// let args=vec!["/usr/bin/gunzip".to_string(), "-t".to_string(), "notes.gz".to_string()];
+/// let (program, cl) = multi_call.try_parse(args.into_iter()).unwrap();
+/// assert_eq!(program, "gunzip");
+///
+/// let test:bool = cl.option("--test");
+/// assert_eq!(test, true);
+/// ```
+#[derive(Default)]
+pub struct MultiCall {
+  /// The programs, with the name each is selected by
+  programs:Vec<(&'static str, CommandLineDef)>,
+}
+
+impl MultiCall {
+  /// Creates a new MultiCall without any programs
+  #[inline]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds a program, selected when the program name is `name`
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the program, without a path or extension. e.g. `gunzip`
+  /// * `cl_def` - The definition used to parse the program's commandline
+  ///
+  /// # Panics
+  ///
+  /// Panics if a program with the name has already been added
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, MultiCall};
+  /// let mut multi_call = MultiCall::new();
+  /// multi_call.add_program("gzip", CommandLineDef::new());
+  /// ```
+  pub fn add_program(&mut self, name:&'static str, cl_def:CommandLineDef) -> &mut Self {
+    if self.find_program(name).is_some() {
+      panic_msg(T.program_redefined(name));
+    }
+    self.programs.push((name, cl_def));
+    self
+  }
+
+  /// Parses the args with the program selected by the program name, panicking with the error
+  /// message if the program is not defined or the commandline is not valid. Returns the name of the
+  /// program and the CommandLine.
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - A string iterator that holds the commandline arguments to be parsed
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, MultiCall};
+  /// let mut multi_call = MultiCall::new();
+  /// multi_call.add_program("gzip", CommandLineDef::new());
+  ///
+  /// let args=vec!["busybox".to_string(), "gzip".to_string()];
+  /// let (program, cl) = multi_call.parse(args.into_iter());
+  /// assert_eq!(program, "gzip");
+  /// assert_eq!(cl.program_name(), "busybox gzip");
+  /// ```
+  pub fn parse(&self, args:impl Iterator<Item=String>) -> (&'static str, CommandLine) {
+    match self.try_parse(args) {
+      Ok(parsed) => parsed,
+      Err(e) => panic!("{}", e),
+    }
+  }
+
+  /// Parses the args with the program selected by the program name, returning an error instead
+  /// of panicking. Returns the name of the program and the CommandLine.
+  ///
+  /// # Arguments
+  ///
+  /// * `args` - A string iterator that holds the commandline arguments to be parsed
+  ///
+  /// # Errors
+  ///
+  /// * [`ErrorKind::ProgramNotDefined`] if neither the program name nor the first arg names a
+  ///   program. The usage message lists the programs.
+  /// * Any error returned by the program's `CommandLineDef::try_parse`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind, MultiCall};
+  /// let mut multi_call = MultiCall::new();
+  /// multi_call.add_program("gzip", CommandLineDef::new());
+  ///
+  /// let args=vec!["busybox".to_string(), "tar".to_string()];
+  /// let err = multi_call.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::ProgramNotDefined);
+  /// assert_eq!(err.message(), "Program 'tar' not defined");
+  /// assert_eq!(err.usage(), "Programs:\n  gzip");
+  /// ```
+  pub fn try_parse(&self, mut args:impl Iterator<Item=String>) -> Result<(&'static str, CommandLine), ParseError> {
+    let program_path = args.next().unwrap_or_default();
+    if let Some((name, cl_def)) = self.find_program(program_stem(&program_path)) {
+      return cl_def.try_parse(once(program_path).chain(args)).map(|cl| (*name, cl));
+    }
+
+    let program = args.next().unwrap_or_else(|| String::from(program_stem(&program_path)));
+    match self.find_program(&program) {
+      Some((name, cl_def)) => {
+        let mut cl = cl_def.try_parse(once(format!("{program_path} {name}")).chain(args))?;
+        cl.set_program_path(&program_path);
+        Ok((*name, cl))
+      },
+      None => Err(ParseError::new(ErrorKind::ProgramNotDefined, T.program_not_defined(&program), self.usage())),
+    }
+  }
+
+  /// Returns the program with the name
+  #[inline]
+  fn find_program(&self, name:&str) -> Option<&(&'static str, CommandLineDef)> {
+    self.programs.iter().find(|(program, _)| *program == name)
+  }
+
+  /// Returns the list of programs, used as the usage message when a program is not defined
+  fn usage(&self) -> String {
+    let mut usage = T.usage_programs();
+    for (name, _) in &self.programs {
+      usage.push_str(&format!("\n  {name}"));
+    }
+    usage
+  }
+}
@@ -1,10 +1,20 @@
-use crate::{LONG_OPTION, panic_msg, SHORT_OPTION};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+use smallvec::SmallVec;
+use crate::{CommandLineDef, Source};
+use crate::command_line::OptionForm;
+use crate::{LONG_OPTION, PLUS_OPTION, SECTION_SEPARATOR, SHORT_OPTION};
 use crate::text::T;
 
 /// Defines the valid options for this program
+#[derive(Clone)]
 pub(crate) struct OptionDef {
   /// The aliases for this option. e.g. -f --filename
-  pub(crate) aliases:Vec<&'static str>,
+  pub(crate) aliases:Aliases,
   /// The name for the value associated with the option. e.g. -f path. If None,
   /// this option will be treated as a flag and the default value will be false.
   pub(crate) value_name:Option<&'static str>,
@@ -13,8 +23,61 @@ pub(crate) struct OptionDef {
   pub(crate) default_value:Option<&'static str>,
   /// The description of this option. e.g. The file to be read.
   pub(crate) description:&'static str,
+  /// True if using this option raises a deprecation warning
+  pub(crate) deprecated:bool,
+  /// Called as soon as this option is parsed
+  pub(crate) action:Option<Action>,
+  /// True if this is the built-in help option
+  pub(crate) help:bool,
+  /// True if this is the version option
+  pub(crate) version:bool,
+  /// True if this flag counts its occurrences instead of being set once
+  pub(crate) counting:bool,
+  /// The values accepted by this option. If empty, any value is accepted.
+  pub(crate) valid_values:ValidValues,
+  /// The environment variable used for the value when the option is not on the commandline
+  pub(crate) env:Option<&'static str>,
+  /// True if this option can only be set with its environment variable
+  pub(crate) env_only:bool,
+  /// The sources that are not used for this option's value
+  pub(crate) excluded_sources:Vec<Source>,
+  /// Checks that a value can be converted to the option's type, for typed options
+  pub(crate) validator:Option<Validator>,
+  /// The JSON Schema type of the option's values. e.g. `integer` for an option typed `u16`
+  pub(crate) json_type:&'static str,
+  /// The number of values the option takes. Several values are stored as a list, and as their
+  /// value separated by `VALUE_DELIMITER`.
+  pub(crate) arity:RangeInclusive<usize>,
+  /// True if the option only takes the minimum number of values, instead of taking values until
+  /// the maximum or the next option
+  pub(crate) lazy:bool,
+  /// True if the option's values may be empty, such as `--name=`
+  pub(crate) allow_empty:bool,
+  /// True if the option's value is optional, and only taken when it is attached to the option, such
+  /// as `-cval` or `--color=val`. The option given alone has an empty value.
+  pub(crate) optional_value:bool,
+  /// The value of a toggle given with a `+` prefix, such as `+x`, or None if the option is not a
+  /// toggle. The `-` prefix gives the opposite value.
+  pub(crate) toggle:Option<bool>,
+  /// The alias that clears a flag paired with the alias that sets it, such as `--disable-cache`
+  /// for `--enable-cache`, or None if the flag is not paired
+  pub(crate) disable:Option<&'static str>,
+  /// The URL of the option's documentation, linked from its help line
+  pub(crate) doc_url:Option<&'static str>,
 }
 
+/// The aliases of an option, stored inline since most options have one or two
+pub(crate) type Aliases = SmallVec<[&'static str; 2]>;
+
+/// The valid values of an option, stored inline since most options have only a few
+pub(crate) type ValidValues = SmallVec<[&'static str; 4]>;
+
+/// An action run as soon as an option is parsed, with the definition and the program name
+pub(crate) type Action = Arc<dyn Fn(&CommandLineDef, &str) + Send + Sync>;
+
+/// Converts a value to the type of a typed option, returning the conversion error if it cannot
+pub(crate) type Validator = Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
 impl OptionDef {
   /// Creates a new OptionDef
   ///
@@ -22,45 +85,144 @@ impl OptionDef {
   ///
   /// * `aliases` - The aliases for this option. e.g. -f --filename
   /// * `value_name` - The name for the value associated with the option. e.g. -f path. If None,
-  /// this option will be treated as a flag and the default value will be false.
+  ///   this option will be treated as a flag and the default value will be false.
   /// * `default_value` - An Option<T> containing the value to use if one is not supplied. If `None`,
-  /// then this option will be considered required and will panic if a value is not specified on the
-  /// commandline. if `value_name` is None, `default_value` is ignored.
+  ///   then this option will be considered required and will panic if a value is not specified on the
+  ///   commandline. if `value_name` is None, `default_value` is ignored.
   /// * `description` - The description of this option. e.g. The file to be read.
-  ///
-  /// # Panics
-  ///
-  /// * Panics if the alias does not start with '-' or '--'.
-  /// * Panics if the alias starts with '--' and the length is less than 4
-  /// * Panics if the alias starts with '-' and the length is not equal to 2
-  ///
   #[inline]
   pub(crate) fn new(aliases:Vec<&'static str>, value_name:Option<&'static str>, default_value:Option<&'static str>, description:&'static str) -> Self {
-    Self::validate_aliases(&aliases);
     OptionDef {
       description,
-      aliases,
+      aliases:Aliases::from_vec(aliases),
       value_name,
       default_value,
+      deprecated:false,
+      action:None,
+      help:false,
+      version:false,
+      counting:false,
+      valid_values:ValidValues::new(),
+      env:None,
+      env_only:false,
+      excluded_sources:Vec::default(),
+      validator:None,
+      json_type:"string",
+      arity:1..=1,
+      lazy:false,
+      allow_empty:false,
+      optional_value:false,
+      toggle:None,
+      disable:None,
+      doc_url:None,
+    }
+  }
+
+  /// Returns the alias used to identify this option in generated output. This is the first long
+  /// alias, or the first alias if there are no long aliases.
+  #[inline]
+  pub(crate) fn canonical_alias(&self) -> &'static str {
+    self.aliases.iter().find(|alias| alias.starts_with(LONG_OPTION)).unwrap_or(&self.aliases[0])
+  }
+
+  /// Returns how this option is written on the commandline
+  #[inline]
+  pub(crate) fn form(&self) -> OptionForm {
+    match (self.value_name, self.counting, self.disable) {
+      (Some(_), _, _) if self.optional_value => OptionForm::Attached,
+      (Some(_), _, _) => OptionForm::Value,
+      (None, true, _) => OptionForm::Counter,
+      (None, false, Some(disable)) => OptionForm::Pair(disable),
+      (None, false, None) => self.toggle.map_or(OptionForm::Flag, OptionForm::Toggle),
+    }
+  }
+
+  /// Returns true if this flag can be given more than once. Counters count each occurrence, and
+  /// the last occurrence of a paired flag wins.
+  #[inline]
+  pub(crate) fn repeatable(&self) -> bool {
+    self.counting || self.disable.is_some()
+  }
+
+  /// Returns the name used for this option in config files and schemas. This is the canonical
+  /// alias without the leading dashes.
+  #[inline]
+  pub(crate) fn key(&self) -> &'static str {
+    self.canonical_alias().trim_start_matches(SHORT_OPTION)
+  }
+
+  /// Returns the aliases of the option for the help, with the `+` alias of each short alias of a
+  /// toggle. e.g. `-x, +x`
+  #[inline]
+  pub(crate) fn help_aliases(&self) -> String {
+    let mut aliases:Vec<String> = self.aliases.iter().map(|alias| alias.to_string()).collect();
+    if self.toggle.is_some() {
+      let short_aliases = self.aliases.iter().filter(|alias| !alias.starts_with(LONG_OPTION));
+      aliases.extend(short_aliases.map(|alias| alias.replacen(SHORT_OPTION, PLUS_OPTION, 1)));
     }
+    aliases.join(", ")
   }
 
+  /// Returns the placeholder for the option's values in the usage message, with the value name
+  /// repeated once for each required value, followed by `[<x>...]` if it takes more. e.g. `<x> <x>`.
+  /// An optional value is `[<x>]`.
   #[inline]
-  fn validate_aliases(aliases:&Vec<&'static str>) {
+  pub(crate) fn value_placeholder(&self, value_name:&str) -> String {
+    if self.optional_value {
+      return format!("[<{value_name}>]");
+    }
+    let (min, max) = (*self.arity.start(), *self.arity.end());
+    let mut placeholders = vec![format!("<{value_name}>"); min];
+    if max > min {
+      placeholders.push(format!("[<{value_name}>...]"));
+    }
+    placeholders.join(" ")
+  }
+
+  /// Returns the message for an option found with a number of values outside its arity
+  #[inline]
+  pub(crate) fn arity_message(&self, option:&str, found:usize) -> String {
+    match (*self.arity.start(), *self.arity.end()) {
+      (1, 1) => T.option_value_required(option),
+      (min, max) if min == max => T.option_values_required(option, min, found),
+      (min, max) => T.option_values_range_required(option, min, max, found),
+    }
+  }
+
+  /// Checks the aliases of an option. Returns the message if there are no aliases, or for the first
+  /// invalid alias:
+  ///
+  /// * An alias that does not start with '-' or '--'.
+  /// * An alias that starts with '--' and whose length is less than 4
+  /// * An alias that starts with '-' and whose length is not equal to 2
+  /// * An alias that starts with '-' and is not an ASCII character
+  /// * An alias that starts with '--' and has an empty section. e.g. --db..host
+  #[inline]
+  pub(crate) fn validate_aliases(aliases:&[&'static str]) -> Result<(), String> {
+    if aliases.is_empty() {
+      return Err(T.option_no_aliases());
+    }
     for alias in aliases {
       let option_len = alias.trim_start_matches(SHORT_OPTION).len();
       if alias.starts_with(LONG_OPTION) {
         if option_len < 2 || alias.len()-option_len>2 {
-          panic_msg(T.option_invalid_long_name(alias));
+          return Err(T.option_invalid_long_name(alias));
+        }
+        if alias[2..].split(SECTION_SEPARATOR).any(str::is_empty) {
+          return Err(T.option_invalid_section(alias));
         }
       } else if alias.starts_with(SHORT_OPTION) {
+        if !alias.is_ascii() {
+          return Err(T.option_non_ascii_short_name(alias));
+        }
         if option_len==0 || option_len>1
         {
-          panic_msg(T.option_invalid_short_name(alias));
+          return Err(T.option_invalid_short_name(alias));
         }
       } else {
-        panic_msg(T.option_invalid_name(alias));
+        return Err(T.option_invalid_name(alias));
       };
     }
+    Ok(())
   }
 }
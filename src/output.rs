@@ -0,0 +1,74 @@
+use alloc::sync::Arc;
+use core::fmt::Display;
+use std::io::Write;
+use std::sync::Mutex;
+use super::cl_def::CommandLineDef;
+
+/// A writer that receives the output in place of stdout or stderr
+pub(crate) type OutputWriter = Arc<Mutex<dyn Write + Send>>;
+
+impl CommandLineDef {
+  /// Sets the writers that receive the generated text in place of stdout and stderr. The help and
+  /// version printed by `ErrorBehavior::Exit` are written to `out`, and the errors and the warnings
+  /// without a warning sink are written to `err`.
+  ///
+  /// # Arguments
+  ///
+  /// * `out` - Receives the text otherwise printed to stdout
+  /// * `err` - Receives the text otherwise printed to stderr
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use std::io::Write;
+  /// use std::sync::{Arc, Mutex};
+  /// use cl_parse::CommandLineDef;
+  ///
+  /// #[derive(Clone, Default)]
+  /// struct Shared(Arc<Mutex<Vec<u8>>>);
+  ///
+  /// impl Write for Shared {
+  ///   fn write(&mut self, buf:&[u8]) -> std::io::Result<usize> { self.0.lock().unwrap().write(buf) }
+  ///   fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+  /// }
+  ///
+  /// let err = Shared::default();
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def
+  ///   .with_output(std::io::sink(), err.clone())
+  ///   .add_flag(vec!["--old"], "Use --new instead")
+  ///   .deprecate_option("--old");
+  ///
+  /// let args=vec!["program".to_string(), "--old".to_string()];
+  /// cl_def.parse(args.into_iter());
+  /// assert_eq!(*err.0.lock().unwrap(), b"Option '--old' is deprecated\n");
+  /// ```
+  #[inline]
+  pub fn with_output(&mut self, out:impl Write + Send + 'static, err:impl Write + Send + 'static) -> &mut Self {
+    self.output = Some((Arc::new(Mutex::new(out)), Arc::new(Mutex::new(err))));
+    self
+  }
+
+  /// Writes a line to the output writer, or to stdout
+  pub(crate) fn print_out(&self, text:&dyn Display) {
+    match &self.output {
+      Some((out, _)) => Self::write_line(out, text),
+      None => println!("{text}"),
+    }
+  }
+
+  /// Writes a line to the error writer, or to stderr
+  pub(crate) fn print_err(&self, text:&dyn Display) {
+    match &self.output {
+      Some((_, err)) => Self::write_line(err, text),
+      None => eprintln!("{text}"),
+    }
+  }
+
+  /// Writes a line to the writer. Write errors are ignored.
+  #[inline]
+  fn write_line(writer:&OutputWriter, text:&dyn Display) {
+    let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = writeln!(writer, "{text}").and_then(|_| writer.flush());
+  }
+}
@@ -0,0 +1,93 @@
+use alloc::vec;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use super::cl_def::CommandLineDef;
+use super::command_line::CommandLine;
+use crate::text::T;
+
+const LONG_OUTPUT: &str = "--output";
+const TABLE: &str = "table";
+const JSON: &str = "json";
+const YAML: &str = "yaml";
+
+/// The format of the output, as chosen with the `--output` option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+  /// A table for people to read
+  #[default]
+  Table,
+  /// JSON, for other programs to read
+  Json,
+  /// YAML, for other programs to read
+  Yaml,
+}
+
+impl FromStr for OutputFormat {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      TABLE => Ok(OutputFormat::Table),
+      JSON => Ok(OutputFormat::Json),
+      YAML => Ok(OutputFormat::Yaml),
+      _ => Err(()),
+    }
+  }
+}
+
+impl Display for OutputFormat {
+  fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+    match self {
+      OutputFormat::Table => write!(f, "{TABLE}"),
+      OutputFormat::Json => write!(f, "{JSON}"),
+      OutputFormat::Yaml => write!(f, "{YAML}"),
+    }
+  }
+}
+
+impl CommandLineDef {
+  /// Adds the standard `--output <format>` option, accepting `table`, `json` or `yaml` and
+  /// defaulting to `table`. Use [`CommandLine::output_format`] to get the format.
+  ///
+  /// # Panics
+  ///
+  /// * Panics if `--output` is already defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind, OutputFormat};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.add_output_format();
+  ///
+  /// let args=vec!["program".to_string(), "--output".to_string(), "json".to_string()];
+  /// assert_eq!(cl_def.parse(args.into_iter()).output_format(), OutputFormat::Json);
+  ///
+  /// let args=vec!["program".to_string(), "--output".to_string(), "xml".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  /// ```
+  #[inline]
+  pub fn add_output_format(&mut self) -> &mut Self {
+    self.add_option(vec![LONG_OUTPUT], Some(T.output_format_value_name()), Some(TABLE), T.output_format_description())
+      .valid_values(LONG_OUTPUT, vec![TABLE, JSON, YAML])
+  }
+}
+
+impl CommandLine {
+  /// Returns the format chosen with the `--output` option added by
+  /// `CommandLineDef::add_output_format`, or `Table` if the option is not defined.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, OutputFormat};
+  /// let args=vec!["program".to_string()];
+  /// let cl = CommandLineDef::new().add_output_format().parse(args.into_iter());
+  /// assert_eq!(cl.output_format(), OutputFormat::Table);
+  /// ```
+  #[inline]
+  pub fn output_format(&self) -> OutputFormat {
+    self.value(LONG_OUTPUT).and_then(|format| format.parse().ok()).unwrap_or_default()
+  }
+}
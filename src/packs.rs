@@ -0,0 +1,99 @@
+use alloc::string::ToString;
+use alloc::vec;
+use super::cl_def::CommandLineDef;
+use crate::text::T;
+use crate::{leak, LONG_LOG_LEVEL, LONG_QUIET, LONG_VERBOSE, SHORT_QUIET, SHORT_VERBOSE};
+
+const LONG_JSON: &str = "--json";
+const LONG_TIMEOUT: &str = "--timeout";
+const LONG_RETRIES: &str = "--retries";
+const LOG_LEVELS: [&str; 7] = ["", "off", "error", "warn", "info", "debug", "trace"];
+
+impl CommandLineDef {
+  /// Adds the logging options: `-v, --verbose` and `-q, --quiet` to set the verbosity, as with
+  /// `add_verbosity`, and `--log-level <level>` to set the log level by name. If `--quiet` is
+  /// already defined, such as by `add_output_options`, it is kept and still lowers the verbosity.
+  /// With the `log` feature, `CommandLine::log_level` reads the level.
+  ///
+  /// # Panics
+  ///
+  /// * Panics if `-v`, `--verbose` or `--log-level` is already defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "-vv".to_string(), "--log-level".to_string(), "debug".to_string()];
+  /// let cl = CommandLineDef::new().add_logging_options().parse(args.into_iter());
+  /// assert_eq!(cl.verbosity(), 2);
+  /// assert_eq!(cl.option_str("--log-level"), "debug");
+  /// ```
+  pub fn add_logging_options(&mut self) -> &mut Self {
+    self.add_counter(vec![SHORT_VERBOSE, LONG_VERBOSE], T.verbose_description());
+    if self.find_option_def(LONG_QUIET).is_none() {
+      self.add_counter(vec![SHORT_QUIET, LONG_QUIET], T.quiet_description());
+    }
+    self.add_option(vec![LONG_LOG_LEVEL], Some(T.log_level_value_name()), Some(""), T.log_level_description())
+      .valid_values(LONG_LOG_LEVEL, LOG_LEVELS.to_vec())
+  }
+
+  /// Adds the output options: `--color <when>`, as with `add_color`, `-q, --quiet` to print no
+  /// informational output, and `--json` to write the output as JSON. If `--quiet` is already
+  /// defined, such as by `add_logging_options`, it is kept.
+  ///
+  /// # Panics
+  ///
+  /// * Panics if `--color` or `--json` is already defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{ColorChoice, CommandLineDef};
+  /// let args=vec!["program".to_string(), "--json".to_string(), "-q".to_string()];
+  /// let cl = CommandLineDef::new().add_output_options().parse(args.into_iter());
+  /// assert_eq!(cl.color(), ColorChoice::Auto);
+  /// assert_eq!(cl.option::<bool>("--quiet"), true);
+  /// assert_eq!(cl.option::<bool>("--json"), true);
+  /// ```
+  pub fn add_output_options(&mut self) -> &mut Self {
+    self.add_color();
+    if self.find_option_def(LONG_QUIET).is_none() {
+      self.add_flag(vec![SHORT_QUIET, LONG_QUIET], T.quiet_output_description());
+    }
+    self.add_flag(vec![LONG_JSON], T.json_description())
+  }
+
+  /// Adds the networking options: `--timeout <seconds>`, the timeout of each request, and
+  /// `--retries <count>`, the number of times a failed request is retried. Their values must be
+  /// whole numbers.
+  ///
+  /// # Arguments
+  ///
+  /// * `timeout` - The timeout in seconds if `--timeout` is not specified
+  /// * `retries` - The number of retries if `--retries` is not specified
+  ///
+  /// # Panics
+  ///
+  /// * Panics if `--timeout` or `--retries` is already defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.add_network_options(30, 3);
+  ///
+  /// let args=vec!["program".to_string(), "--timeout".to_string(), "5".to_string()];
+  /// let cl = cl_def.parse(args.into_iter());
+  /// assert_eq!(cl.option::<u64>("--timeout"), 5);
+  /// assert_eq!(cl.option::<u32>("--retries"), 3);
+  ///
+  /// let args=vec!["program".to_string(), "--retries".to_string(), "many".to_string()];
+  /// assert_eq!(cl_def.try_parse(args.into_iter()).unwrap_err().kind(), ErrorKind::InvalidValue);
+  /// ```
+  pub fn add_network_options(&mut self, timeout:u64, retries:u32) -> &mut Self {
+    self.add_typed_option::<u64>(vec![LONG_TIMEOUT], T.timeout_value_name(), Some(leak(timeout.to_string())), T.timeout_description());
+    self.add_typed_option::<u32>(vec![LONG_RETRIES], T.retries_value_name(), Some(leak(retries.to_string())), T.retries_description());
+    self
+  }
+}
@@ -0,0 +1,240 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use crate::json::json_string;
+
+/// The kinds of errors that can occur while parsing a commandline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+  /// Help was requested with `-h` or `--help`. The error message is the usage message.
+  Help,
+  /// The version was requested with `-V` or `--version`. The error message is the version text.
+  Version,
+  /// An option on the commandline has not been defined
+  OptionNotDefined,
+  /// An abbreviated long option is the prefix of several options, when long options can be
+  /// abbreviated
+  AmbiguousOption,
+  /// An option that requires a value was specified without one
+  MissingValue,
+  /// An option's value is not one of its valid values
+  InvalidValue,
+  /// An option that can only be set with an environment variable was specified on the commandline
+  EnvOnlyOption,
+  /// An option, or one of its aliases, was specified more than once
+  MultipleOccurrences,
+  /// A flag concatenation contains a non-ASCII character. Short options are always ASCII.
+  NonAsciiFlags,
+  /// The args in the environment variable set with `args_env_var` have an unterminated quote or
+  /// escape
+  InvalidEnvArgs,
+  /// The .env file cannot be read or parsed
+  InvalidDotenv,
+  /// A required option was not specified on the commandline or in its environment variable
+  MissingRequiredOption,
+  /// The number of arguments found does not match the number of arguments defined
+  WrongArgumentCount,
+  /// The arg naming the subcommand is not a defined subcommand
+  SubcommandNotDefined,
+  /// The arg naming the subcommand is a prefix of several subcommands, when subcommands are
+  /// inferred from their prefixes
+  AmbiguousSubcommand,
+  /// A line parsed with `parse_line` or `repl` has an unterminated quote or escape
+  InvalidLine,
+  /// The program name, or the arg after it, does not name a program of a `MultiCall`
+  ProgramNotDefined,
+}
+
+/// An error produced while parsing a commandline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+  /// The kind of error
+  kind: ErrorKind,
+  /// The message describing the error
+  message: String,
+  /// The usage message for the commandline definition
+  usage: String,
+  /// Displayed after the message instead of the usage message, for concise errors
+  footer: Option<String>,
+  /// The option, environment variable or subcommand the error is about, if any
+  option: Option<Box<str>>,
+  /// The options or subcommands that could have been meant
+  suggestions: Box<[String]>,
+  /// True if the error is displayed as JSON
+  json: bool,
+}
+
+impl ParseError {
+  /// Creates a new ParseError
+  ///
+  /// # Arguments
+  ///
+  /// * `kind` - The kind of error
+  /// * `message` - The message describing the error
+  /// * `usage` - The usage message for the commandline definition
+  #[inline]
+  pub(crate) fn new(kind: ErrorKind, message: String, usage: String) -> Self {
+    ParseError {
+      kind,
+      message,
+      usage,
+      footer: None,
+      option: None,
+      suggestions: Box::default(),
+      json: false,
+    }
+  }
+
+  /// Sets the option, environment variable or subcommand the error is about
+  #[inline]
+  pub(crate) fn with_option(mut self, option: &str) -> Self {
+    self.option = Some(Box::from(option));
+    self
+  }
+
+  /// Sets the options or subcommands that could have been meant
+  #[inline]
+  pub(crate) fn with_suggestions(mut self, suggestions: &[&str]) -> Self {
+    self.suggestions = suggestions.iter().map(|suggestion| suggestion.to_string()).collect();
+    self
+  }
+
+  /// Displays the error as JSON
+  #[inline]
+  pub(crate) fn with_json(mut self) -> Self {
+    self.json = true;
+    self
+  }
+
+  /// Displays the footer after the message instead of the usage message. An empty footer displays
+  /// only the message.
+  #[inline]
+  pub(crate) fn with_footer(mut self, footer: String) -> Self {
+    self.footer = Some(footer);
+    self
+  }
+
+  /// Returns the kind of error
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let args=vec!["program".to_string(), "-x".to_string()];
+  /// let err = CommandLineDef::new().try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::OptionNotDefined);
+  /// ```
+  #[inline]
+  pub fn kind(&self) -> ErrorKind {
+    self.kind
+  }
+
+  /// Returns the message describing the error, without the usage message
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "-x".to_string()];
+  /// let err = CommandLineDef::new().try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.message(), "Option '-x' not defined");
+  /// ```
+  #[inline]
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+
+  /// Returns the usage message for the commandline definition
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "-x".to_string()];
+  /// let err = CommandLineDef::new().try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.usage(), "Usage: program [-h]\n-h, --help : Display usage message");
+  /// ```
+  #[inline]
+  pub fn usage(&self) -> &str {
+    &self.usage
+  }
+
+  /// Returns the option, environment variable or subcommand the error is about, as it was given,
+  /// if the error is about one
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "--bogus".to_string()];
+  /// let err = CommandLineDef::new().try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.option(), Some("--bogus"));
+  /// ```
+  #[inline]
+  pub fn option(&self) -> Option<&str> {
+    self.option.as_deref()
+  }
+
+  /// Returns the options or subcommands that could have been meant, such as the options an
+  /// ambiguous abbreviation could expand to
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "--co".to_string()];
+  /// let err = CommandLineDef::new()
+  ///   .add_flag(vec!["--color"], "Color the output")
+  ///   .add_flag(vec!["--count"], "Count the lines")
+  ///   .infer_long_options(true)
+  ///   .try_parse(args.into_iter())
+  ///   .unwrap_err();
+  /// assert_eq!(err.suggestions(), ["--color", "--count"]);
+  /// ```
+  #[inline]
+  pub fn suggestions(&self) -> &[String] {
+    &self.suggestions
+  }
+
+  /// Returns the error as a JSON object with its kind, the option it is about or null, its message
+  /// and its suggestions, for programs that read the errors
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec!["program".to_string(), "-n".to_string()];
+  /// let err = CommandLineDef::new()
+  ///   .add_option(vec!["-n"], Some("count"), Some("1"), "The count")
+  ///   .try_parse(args.into_iter())
+  ///   .unwrap_err();
+  /// assert_eq!(err.to_json(),
+  ///   r#"{"kind": "MissingValue", "option": "-n", "message": "A value is required for option '-n'", "suggestions": []}"#);
+  /// ```
+  pub fn to_json(&self) -> String {
+    let option = self.option.as_deref().map_or(String::from("null"), json_string);
+    let suggestions:Vec<String> = self.suggestions.iter().map(|suggestion| json_string(suggestion)).collect();
+    format!("{{\"kind\": \"{:?}\", \"option\": {option}, \"message\": {}, \"suggestions\": [{}]}}",
+            self.kind, json_string(&self.message), suggestions.join(", "))
+  }
+}
+
+impl Display for ParseError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+    match self.kind {
+      ErrorKind::Help => write!(f, "{}", self.usage),
+      ErrorKind::Version => write!(f, "{}", self.message),
+      _ if self.json => write!(f, "{}", self.to_json()),
+      _ => match &self.footer {
+        Some(footer) if footer.is_empty() => write!(f, "{}", self.message),
+        Some(footer) => write!(f, "{}\n{}", self.message, footer),
+        None => write!(f, "{}\n{}", self.message, self.usage),
+      },
+    }
+  }
+}
+
+impl core::error::Error for ParseError {}
@@ -0,0 +1,103 @@
+use std::env;
+use std::fs;
+use std::io::Result;
+use std::path::PathBuf;
+use super::command_line::CommandLine;
+
+/// The character that starts a path in a home directory
+const TILDE: char = '~';
+
+impl CommandLine {
+  /// Returns the value of an option as a path, with a leading `~` expanded to the home directory
+  /// and `~user` expanded to the home directory of the user, as a shell would. A value that is
+  /// not expanded by the shell, such as `--dir=~/src` or a default value, still refers to the home
+  /// directory. If the home directory cannot be found, or the user is unknown, the value is
+  /// returned unchanged.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the option
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use std::path::PathBuf;
+  /// use cl_parse::CommandLineDef;
+  /// let home = PathBuf::from(std::env::var("HOME").unwrap());
+  /// let env_args=vec![String::from("program"), String::from("--dir=~/src")];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-d","--dir"], Some("dir"), Some("."), "The source directory")
+  ///   .parse(env_args.into_iter());
+  ///
+  /// assert_eq!(cl.path("--dir"), home.join("src"));
+  /// ```
+  pub fn path(&self, name:&str) -> PathBuf {
+    expand_tilde(&self.option::<String>(name))
+  }
+
+  /// Returns the value of an option as a path with `~` expanded, as for `path`, made absolute
+  /// with all symbolic links resolved
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the option
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the path does not exist, or cannot be resolved
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let env_args=vec![String::from("program"), String::from("-d"), String::from("src/..")];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-d","--dir"], Some("dir"), Some("."), "The source directory")
+  ///   .parse(env_args.into_iter());
+  ///
+  /// assert_eq!(cl.canonical_path("-d").unwrap(), std::env::current_dir().unwrap().canonicalize().unwrap());
+  /// ```
+  pub fn canonical_path(&self, name:&str) -> Result<PathBuf> {
+    fs::canonicalize(self.path(name))
+  }
+}
+
+/// Expands a leading `~` or `~user` in a path
+fn expand_tilde(value:&str) -> PathBuf {
+  let Some(rest) = value.strip_prefix(TILDE) else {
+    return PathBuf::from(value);
+  };
+  let (user, rest) = rest.split_once(['/', '\\']).unwrap_or((rest, ""));
+  let home = match user {
+    "" => home_dir(),
+    user => user_home_dir(user),
+  };
+  match home {
+    Some(home) if rest.is_empty() => home,
+    Some(home) => home.join(rest),
+    None => PathBuf::from(value),
+  }
+}
+
+/// Returns the home directory of the current user
+fn home_dir() -> Option<PathBuf> {
+  env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).filter(|home| !home.is_empty()).map(PathBuf::from)
+}
+
+/// Returns the home directory of the user from the password file, or None if the user is not in
+/// it, as a shell leaves an unknown `~user` unexpanded
+fn user_home_dir(user:&str) -> Option<PathBuf> {
+  let passwd = fs::read_to_string("/etc/passwd").ok()?;
+  passwd.lines()
+    .map(|line| line.split(':').collect::<Vec<_>>())
+    .find(|fields| fields.len() > 5 && fields[0] == user)
+    .map(|fields| PathBuf::from(fields[5]))
+}
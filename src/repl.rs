@@ -0,0 +1,107 @@
+use alloc::string::String;
+use core::iter::once;
+#[cfg(feature = "std")]
+use std::io::{BufRead, Result, Write};
+use super::cl_def::CommandLineDef;
+use super::command_line::CommandLine;
+use super::parse_error::{ErrorKind, ParseError};
+use super::shell::split_posix;
+use crate::text::T;
+
+impl CommandLineDef {
+  /// Splits a line the way a POSIX shell does and parses it with this definition, as if the words
+  /// followed the program name on the commandline. This lets an interactive console reuse the
+  /// definition of the batch program.
+  ///
+  /// # Arguments
+  ///
+  /// * `program_name` - The program name used in the usage message. e.g. `db`
+  /// * `line` - The line to parse, without the program name. e.g. `query --limit 10 'a b'`
+  ///
+  /// # Errors
+  ///
+  /// * [`ErrorKind::InvalidLine`] if the line has an unterminated quote or escape
+  /// * Any error returned by `try_parse`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.add_option(vec!["-n","--name"], Some("name"), Some(""), "The name").add_argument("file");
+  ///
+  /// let cl = cl_def.parse_line("db", "--name 'a b' notes.txt").unwrap();
+  /// let name:String = cl.option("--name");
+  /// assert_eq!(name, "a b");
+  ///
+  /// let err = cl_def.parse_line("db", "--name 'a b").unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::InvalidLine);
+  /// ```
+  pub fn parse_line(&self, program_name:&str, line:&str) -> core::result::Result<CommandLine, ParseError> {
+    let words = split_posix(line)
+        .ok_or_else(|| self.parse_error(ErrorKind::InvalidLine, T.line_unterminated(), program_name))?;
+    self.try_parse(once(String::from(program_name)).chain(words))
+  }
+
+  /// Runs a read-eval-print loop. A prompt made of the program name followed by `> ` is written
+  /// to the output, then a line is read from the input, parsed with `parse_line`, and the result is
+  /// passed to the handler. Errors are not fatal, so the handler can print them and the loop
+  /// continues. Blank lines are skipped. The loop ends when the input ends, or when the handler
+  /// returns false.
+  ///
+  /// # Arguments
+  ///
+  /// * `program_name` - The program name used in the prompt and the usage message. e.g. `db`
+  /// * `input` - The input the lines are read from. e.g. `std::io::stdin().lock()`
+  /// * `output` - The output the prompt is written to. e.g. `std::io::stdout()`
+  /// * `handler` - Called with the result of parsing each line. Returns false to end the loop.
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the input cannot be read or the prompt cannot be written
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def.add_flag(vec!["-q","--quit"], "Quit the console").add_argument("word");
+  ///
+  /// let input = "hello\n\n-x\n--quit bye\nignored\n".as_bytes();
+  /// let mut output = Vec::new();
+  /// let mut words = Vec::new();
+  /// cl_def.repl("echo", input, &mut output, |result| match result {
+  ///   Ok(cl) => {
+  ///     words.push(cl.argument::<String>(0));
+  ///     !cl.option::<bool>("--quit")
+  ///   },
+  ///   Err(e) => {
+  ///     words.push(e.message().to_string());
+  ///     true
+  ///   },
+  /// }).unwrap();
+  ///
+  /// assert_eq!(words, vec!["hello", "Option '-x' not defined", "bye"]);
+  /// assert_eq!(String::from_utf8(output).unwrap(), "echo> echo> echo> echo> ");
+  /// ```
+  #[cfg(feature = "std")]
+  pub fn repl(&self, program_name:&str, mut input:impl BufRead, mut output:impl Write,
+              mut handler:impl FnMut(core::result::Result<CommandLine, ParseError>) -> bool) -> Result<()> {
+    let prompt = format!("{program_name}> ");
+    let mut line = String::default();
+    loop {
+      output.write_all(prompt.as_bytes())?;
+      output.flush()?;
+      line.clear();
+      if input.read_line(&mut line)? == 0 {
+        return Ok(());
+      }
+      if line.trim().is_empty() {
+        continue;
+      }
+      if !handler(self.parse_line(program_name, &line)) {
+        return Ok(());
+      }
+    }
+  }
+}
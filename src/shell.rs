@@ -0,0 +1,200 @@
+//! Quoting and splitting of values for shells, so applications composing commandlines from parsed
+//! values do not need their own escaping, and reading args piped from other programs.
+//!
+//! # Examples
+//!
+//! ```
+//! use cl_parse::shell::{quote_posix, quote_powershell};
+//! let file = "my file's.txt";
+//! assert_eq!(format!("cat {}", quote_posix(file)), r#"cat 'my file'\''s.txt'"#);
+//! assert_eq!(format!("Get-Content {}", quote_powershell(file)), "Get-Content 'my file''s.txt'");
+//! ```
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{BufRead, Error, ErrorKind, Result};
+
+/// Returns true if the value does not need quoting in any supported shell
+#[inline]
+fn is_safe(value:&str) -> bool {
+  !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || "_-./:=+%@".contains(c))
+    && !value.starts_with(['=', '@'])
+}
+
+/// Quotes a value for a POSIX shell such as sh, bash or zsh. Values made only of characters
+/// that have no special meaning are returned unchanged. Other values are wrapped in single quotes,
+/// with each single quote written as `'\''`.
+///
+/// # Arguments
+///
+/// * `value` - The value to quote
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::shell::quote_posix;
+/// assert_eq!(quote_posix("file.txt"), "file.txt");
+/// assert_eq!(quote_posix("a b"), "'a b'");
+/// assert_eq!(quote_posix("it's"), r#"'it'\''s'"#);
+/// assert_eq!(quote_posix(""), "''");
+/// ```
+pub fn quote_posix(value:&str) -> String {
+  if is_safe(value) {
+    return value.to_string();
+  }
+  let mut quoted = String::with_capacity(value.len() + 2);
+  quoted.push('\'');
+  for c in value.chars() {
+    match c {
+      '\'' => quoted.push_str("'\\''"),
+      _ => quoted.push(c),
+    }
+  }
+  quoted.push('\'');
+  quoted
+}
+
+/// Quotes a value for PowerShell. Values made only of characters that have no special meaning
+/// are returned unchanged. Other values are wrapped in single quotes, with each single quote,
+/// including the typographic quotes PowerShell also accepts, doubled.
+///
+/// # Arguments
+///
+/// * `value` - The value to quote
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::shell::quote_powershell;
+/// assert_eq!(quote_powershell("file.txt"), "file.txt");
+/// assert_eq!(quote_powershell("$HOME"), "'$HOME'");
+/// assert_eq!(quote_powershell("it's"), "'it''s'");
+/// assert_eq!(quote_powershell(""), "''");
+/// ```
+pub fn quote_powershell(value:&str) -> String {
+  if is_safe(value) {
+    return value.to_string();
+  }
+  let mut quoted = String::with_capacity(value.len() + 2);
+  quoted.push('\'');
+  for c in value.chars() {
+    if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+      quoted.push(c);
+    }
+    quoted.push(c);
+  }
+  quoted.push('\'');
+  quoted
+}
+
+/// Splits a line into words the way a POSIX shell does, without expansions. Words are separated
+/// by whitespace. Single quotes keep everything up to the next single quote, double quotes keep
+/// everything up to the next unescaped double quote, and a backslash escapes the next character.
+/// Returns `None` if a quote is not closed or the line ends with a backslash.
+///
+/// # Arguments
+///
+/// * `line` - The line to split. e.g. `--name 'a b' -v`
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::shell::split_posix;
+/// assert_eq!(split_posix(r#"--name 'a b' --path "C:\\dir" it\'s"#).unwrap(),
+///   vec!["--name", "a b", "--path", "C:\\dir", "it's"]);
+/// assert_eq!(split_posix("--name 'a b"), None);
+/// ```
+pub fn split_posix(line:&str) -> Option<Vec<String>> {
+  let mut words:Vec<String> = Vec::default();
+  let mut word:Option<String> = None;
+  let mut chars = line.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      c if c.is_whitespace() => words.extend(word.take()),
+      '\'' => {
+        let word = word.get_or_insert_with(String::default);
+        loop {
+          match chars.next()? {
+            '\'' => break,
+            c => word.push(c),
+          }
+        }
+      },
+      '"' => {
+        let word = word.get_or_insert_with(String::default);
+        loop {
+          match chars.next()? {
+            '"' => break,
+            '\\' => match chars.next()? {
+              c @ ('"' | '\\' | '$' | '`') => word.push(c),
+              '\n' => {},
+              c => { word.push('\\'); word.push(c); },
+            },
+            c => word.push(c),
+          }
+        }
+      },
+      '\\' => match chars.next()? {
+        '\n' => {},
+        c => word.get_or_insert_with(String::default).push(c),
+      },
+      c => word.get_or_insert_with(String::default).push(c),
+    }
+  }
+  words.extend(word);
+  Some(words)
+}
+
+/// Reads args delimited by newlines or NUL characters, as produced by `find -print0` and read by
+/// `xargs -0`, so pipelines can pass more args than fit on a commandline. Chain the args after
+/// the commandline args before parsing. Empty args are skipped, and with newline delimiters a
+/// trailing carriage return is removed.
+///
+/// # Arguments
+///
+/// * `input` - The input the args are read from. e.g. `std::io::stdin().lock()`
+/// * `delimiter` - The byte between args, `b'\n'` or `b'\0'`
+///
+/// # Errors
+///
+/// Returns an error if the input cannot be read, or an arg is not valid UTF-8
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::CommandLineDef;
+/// use cl_parse::shell::read_args;
+/// let input = "a.txt\0my notes.txt\0".as_bytes();
+/// let args = vec!["program".to_string(), "-v".to_string()].into_iter()
+///   .chain(read_args(input, b'\0').unwrap());
+///
+/// let cl = CommandLineDef::new()
+///   .add_flag(vec!["-v","--verbose"], "Verbose output")
+///   .add_argument("first")
+///   .add_argument("second")
+///   .parse(args);
+///
+/// let second:String = cl.argument(1);
+/// assert_eq!(second, "my notes.txt");
+/// ```
+#[cfg(feature = "std")]
+pub fn read_args(mut input:impl BufRead, delimiter:u8) -> Result<Vec<String>> {
+  let mut args:Vec<String> = Vec::default();
+  let mut buf:Vec<u8> = Vec::default();
+  loop {
+    buf.clear();
+    if input.read_until(delimiter, &mut buf)? == 0 {
+      return Ok(args);
+    }
+    if buf.last() == Some(&delimiter) {
+      buf.pop();
+    }
+    if delimiter == b'\n' && buf.last() == Some(&b'\r') {
+      buf.pop();
+    }
+    if !buf.is_empty() {
+      let arg = String::from_utf8(buf.clone()).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+      args.push(arg);
+    }
+  }
+}
@@ -0,0 +1,19 @@
+/// The sources an option's value can come from. When an option has a value in more than one
+/// source, the value from the source with the highest precedence is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Source {
+  /// The commandline args
+  CommandLine,
+  /// The option's environment variable
+  Env,
+  /// The config values set with `CommandLineDef::config`
+  Config,
+  /// The option's default value
+  Default,
+}
+
+impl Source {
+  /// The precedence used unless the application sets its own, from highest to lowest
+  pub(crate) const PRECEDENCE: [Source; 4] = [Source::CommandLine, Source::Env, Source::Config, Source::Default];
+}
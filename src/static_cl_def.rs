@@ -0,0 +1,59 @@
+use core::ops::Deref;
+use std::sync::OnceLock;
+use super::cl_def::CommandLineDef;
+
+/// A CommandLineDef that can be declared in a `static`. The definition is built by `init` the
+/// first time it is used, and then reused for every parse, avoiding rebuilding the definition on
+/// every invocation.
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::StaticCommandLineDef;
+///
+/// static CL_DEF: StaticCommandLineDef = StaticCommandLineDef::new(|def| {
+///   def.add_flag(vec!["-v","--verbose"], "Verbose output")
+///     .add_argument("file");
+/// });
+///
+/// let args=vec!["program".to_string(), "-v".to_string(), "file.txt".to_string()];
+/// let cl = CL_DEF.parse(args.into_iter());
+///
+/// let verbose:bool = cl.option("--verbose");
+/// assert_eq!(verbose, true);
+/// ```
+pub struct StaticCommandLineDef {
+  /// Builds the definition on first use
+  init: fn(&mut CommandLineDef),
+  /// The definition, once built
+  cl_def: OnceLock<CommandLineDef>,
+}
+
+impl StaticCommandLineDef {
+  /// Creates a new StaticCommandLineDef
+  ///
+  /// # Arguments
+  ///
+  /// * `init` - Adds the options and arguments to the definition. The definition passed to `init`
+  ///   is created with `CommandLineDef::new()`.
+  #[inline]
+  pub const fn new(init: fn(&mut CommandLineDef)) -> Self {
+    StaticCommandLineDef {
+      init,
+      cl_def: OnceLock::new(),
+    }
+  }
+}
+
+impl Deref for StaticCommandLineDef {
+  type Target = CommandLineDef;
+
+  #[inline]
+  fn deref(&self) -> &Self::Target {
+    self.cl_def.get_or_init(|| {
+      let mut cl_def = CommandLineDef::new();
+      (self.init)(&mut cl_def);
+      cl_def
+    })
+  }
+}
@@ -0,0 +1,182 @@
+use alloc::vec::Vec;
+use super::cl_def::CommandLineDef;
+use super::parse_error::{ErrorKind, ParseError};
+use super::def_error::DefErrorKind;
+use super::SHORT_OPTION;
+use crate::text::T;
+
+/// Defines a subcommand, such as `build` in `cargo build`
+pub(crate) struct Subcommand {
+  /// The names of this subcommand. The first is the canonical name, and the others are aliases.
+  pub(crate) names:Vec<&'static str>,
+  /// A one line summary of this subcommand, shown in the parent's help
+  pub(crate) summary:&'static str,
+  /// The definition of the subcommand's options and arguments
+  pub(crate) cl_def:CommandLineDef,
+}
+
+impl CommandLineDef {
+  /// Adds a subcommand, such as `build` in `cargo build`. When the definition has subcommands, the
+  /// first positional arg after the definition's own arguments names the subcommand. The args
+  /// after it are parsed with the subcommand's definition, and the result is available with
+  /// `CommandLine::subcommand`. A subcommand can have aliases, e.g. `rm` for `remove`, which parse
+  /// identically but are not shown in the help.
+  ///
+  /// # Arguments
+  ///
+  /// * `names` - The names of the subcommand. The first is the canonical name, and the others are
+  ///   aliases. e.g. `"remove","rm"`
+  /// * `summary` - A one line summary of the subcommand. e.g. `Remove files`
+  /// * `cl_def` - The definition of the subcommand's options and arguments
+  ///
+  /// # Panics
+  ///
+  /// * Panics if a name is empty or starts with '-'
+  /// * Panics if a name is already used by another subcommand
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let mut remove = CommandLineDef::new();
+  /// remove.add_flag(vec!["-f","--force"], "Ignore missing files").add_argument("file");
+  ///
+  /// let args=vec!["git".to_string(), "rm".to_string(), "-f".to_string(), "notes.txt".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_subcommand(vec!["remove","rm"], "Remove files", remove)
+  ///   .parse(args.into_iter());
+  ///
+  /// let (name, remove) = cl.subcommand().unwrap();
+  /// assert_eq!(name, "remove");
+  /// assert_eq!(remove.program_name(), "git remove");
+  ///
+  /// let force:bool = remove.option("--force");
+  /// assert_eq!(force, true);
+  ///
+  /// let file:String = remove.argument(0);
+  /// assert_eq!(file, "notes.txt");
+  /// ```
+  pub fn add_subcommand(&mut self, names:Vec<&'static str>, summary:&'static str, cl_def:CommandLineDef) -> &mut Self {
+    if names.is_empty() {
+      self.def_failed(DefErrorKind::InvalidName, T.subcommand_invalid_name(""));
+      return self;
+    }
+    if self.variadic.is_some() {
+      self.def_failed(DefErrorKind::Conflict, T.argument_variadic_subcommand());
+      return self;
+    }
+    for name in &names {
+      if name.is_empty() || name.starts_with(SHORT_OPTION) {
+        self.def_failed(DefErrorKind::InvalidName, T.subcommand_invalid_name(name));
+        return self;
+      }
+      if self.find_subcommand(name).is_some() {
+        self.def_failed(DefErrorKind::Redefined, T.subcommand_redefined(name));
+        return self;
+      }
+    }
+    self.subcommands.push(Subcommand {
+      names,
+      summary,
+      cl_def,
+    });
+    self
+  }
+
+  /// Sets the subcommand run when the args do not name one, so `mytool` behaves like
+  /// `mytool status`. The definition's own options, such as `--help`, are still parsed first. The
+  /// default subcommand is parsed without args, and is marked `(default)` in the help.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name or an alias of the subcommand
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the subcommand is not defined
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let mut status = CommandLineDef::new();
+  /// status.add_flag(vec!["-s","--short"], "Give the output in the short format");
+  ///
+  /// let args=vec!["git".to_string()];
+  /// let cl = CommandLineDef::new()
+  ///   .add_subcommand(vec!["status","st"], "Show the working tree status", status)
+  ///   .add_subcommand(vec!["commit"], "Record changes", CommandLineDef::new())
+  ///   .default_subcommand("st")
+  ///   .parse(args.into_iter());
+  ///
+  /// let (name, status) = cl.subcommand().unwrap();
+  /// assert_eq!(name, "status");
+  /// assert_eq!(status.option::<bool>("--short"), false);
+  /// ```
+  pub fn default_subcommand(&mut self, name:&'static str) -> &mut Self {
+    match self.find_subcommand(name) {
+      Some(subcommand) => self.default_subcommand = Some(subcommand.names[0]),
+      None => self.def_failed(DefErrorKind::NotDefined, T.subcommand_not_defined(name)),
+    }
+    self
+  }
+
+  /// Lets a subcommand be named by a prefix of one of its names, such as `st` for `status`, when
+  /// the prefix does not start the names of other subcommands. A name that is given in full is
+  /// always that subcommand, even if it is the prefix of another.
+  ///
+  /// # Arguments
+  ///
+  /// * `enabled` - True to infer subcommands from their prefixes
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// cl_def
+  ///   .add_subcommand(vec!["status"], "Show the working tree status", CommandLineDef::new())
+  ///   .add_subcommand(vec!["stash"], "Stash the changes", CommandLineDef::new())
+  ///   .infer_subcommands(true);
+  ///
+  /// let args=vec!["git".to_string(), "stat".to_string()];
+  /// assert_eq!(cl_def.parse(args.into_iter()).subcommand().unwrap().0, "status");
+  ///
+  /// let args=vec!["git".to_string(), "st".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::AmbiguousSubcommand);
+  /// assert_eq!(err.message(), "Command 'st' is ambiguous, it could be status, stash");
+  /// ```
+  #[inline]
+  pub fn infer_subcommands(&mut self, enabled:bool) -> &mut Self {
+    self.infer_subcommands = enabled;
+    self
+  }
+
+  /// Returns the subcommand with the name or alias
+  #[inline]
+  pub(crate) fn find_subcommand(&self, name:&str) -> Option<&Subcommand> {
+    self.subcommands.iter().find(|subcommand| subcommand.names.contains(&name))
+  }
+
+  /// Returns the subcommand named by the arg, or by a prefix of one of its names when subcommands
+  /// are inferred
+  pub(crate) fn resolve_subcommand(&self, arg:&str, program_name:&str) -> Result<&Subcommand, ParseError> {
+    if let Some(subcommand) = self.find_subcommand(arg) {
+      return Ok(subcommand);
+    }
+    let candidates:Vec<&Subcommand> = match self.infer_subcommands {
+      true => self.subcommands.iter().filter(|subcommand| subcommand.names.iter().any(|name| name.starts_with(arg))).collect(),
+      false => Vec::default(),
+    };
+    match candidates.as_slice() {
+      [subcommand] => Ok(subcommand),
+      [] => Err(self.parse_error(ErrorKind::SubcommandNotDefined, T.subcommand_not_defined(arg), program_name).with_option(arg)),
+      _ => {
+        let names:Vec<&str> = candidates.iter().map(|subcommand| subcommand.names[0]).collect();
+        Err(self.parse_error(ErrorKind::AmbiguousSubcommand, T.subcommand_ambiguous(arg, &names.join(", ")), program_name)
+          .with_option(arg).with_suggestions(&names))
+      },
+    }
+  }
+}
@@ -0,0 +1,95 @@
+//! Helpers for testing commandline definitions, so downstream crates can assert on parse results
+//! without reproducing panic messages.
+//!
+//! # Examples
+//!
+//! ```
+//! use cl_parse::{assert_parse_err, parse_ok, CommandLineDef, ErrorKind};
+//! cl_parse::testing::fixed_locale();
+//!
+//! let mut def = CommandLineDef::new();
+//! def.add_option(vec!["-f","--file"], Some("path"), None, "The file to read");
+//!
+//! let cl = parse_ok!(def, "program -f in.txt");
+//! let file:String = cl.option("--file");
+//! assert_eq!(file, "in.txt");
+//!
+//! assert_parse_err!(def, "program -f", ErrorKind::MissingValue);
+//! ```
+use alloc::string::{String, ToString};
+
+/// Splits a commandline on whitespace into args that can be parsed by a CommandLineDef
+///
+/// # Arguments
+///
+/// * `commandline` - The commandline, starting with the program name. e.g. `program -f in.txt`
+///
+/// # Examples
+///
+/// ```
+/// let args:Vec<String> = cl_parse::testing::args("program -f in.txt").collect();
+/// assert_eq!(args, vec!["program", "-f", "in.txt"]);
+/// ```
+#[inline]
+pub fn args(commandline:&str) -> impl Iterator<Item=String> + '_ {
+  commandline.split_whitespace().map(|arg| arg.to_string())
+}
+
+/// Fixes the locale used for messages to `en-US`, so tests asserting on messages do not depend on
+/// the locale of the machine running them. Call it at the start of each test, before anything is
+/// parsed.
+///
+/// # Examples
+///
+/// ```
+/// cl_parse::testing::fixed_locale();
+/// ```
+#[inline]
+pub fn fixed_locale() {
+  #[cfg(feature = "i18n")]
+  crate::set_locale("en-US");
+}
+
+/// Parses a commandline string with a CommandLineDef, and returns the CommandLine. Panics with the
+/// parse error if the commandline is not valid.
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::{parse_ok, CommandLineDef};
+/// let mut def = CommandLineDef::new();
+/// def.add_flag(vec!["-v","--verbose"], "Verbose output");
+///
+/// let cl = parse_ok!(def, "program -v");
+/// let verbose:bool = cl.option("-v");
+/// assert_eq!(verbose, true);
+/// ```
+#[macro_export]
+macro_rules! parse_ok {
+  ($cl_def:expr, $commandline:expr) => {
+    match $cl_def.try_parse($crate::testing::args($commandline)) {
+      Ok(cl) => cl,
+      Err(e) => panic!("expected '{}' to parse, but got {:?}: {}", $commandline, e.kind(), e.message()),
+    }
+  };
+}
+
+/// Asserts that parsing a commandline string with a CommandLineDef fails with the ErrorKind
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::{assert_parse_err, CommandLineDef, ErrorKind};
+/// let def = CommandLineDef::new();
+///
+/// assert_parse_err!(def, "program --verbose", ErrorKind::OptionNotDefined);
+/// ```
+#[macro_export]
+macro_rules! assert_parse_err {
+  ($cl_def:expr, $commandline:expr, $kind:expr) => {
+    match $cl_def.try_parse($crate::testing::args($commandline)) {
+      Ok(_) => panic!("expected '{}' to fail with {:?}, but it parsed", $commandline, $kind),
+      Err(e) => assert_eq!(e.kind(), $kind, "unexpected error for '{}': {}", $commandline, e.message()),
+    }
+  };
+}
@@ -1,9 +1,17 @@
+use alloc::string::String;
+#[cfg(feature = "i18n")]
 use std::collections::HashMap;
+#[cfg(feature = "i18n")]
 use lazy_static::lazy_static;
+#[cfg(feature = "i18n")]
+use std::sync::{OnceLock, RwLock};
+#[cfg(feature = "i18n")]
 use sys_locale::get_locale;
 
+#[cfg(feature = "i18n")]
 type TextFactory = fn() -> Box<dyn Text + Sync>;
 
+#[cfg(feature = "i18n")]
 lazy_static! {
   static ref TEXT_MAP: HashMap<&'static str, TextFactory> = {
     let mut tm:HashMap<&'static str, TextFactory> = HashMap::new();
@@ -13,14 +21,142 @@ lazy_static! {
   };
 
   pub(crate) static ref T: Box<dyn Text + Sync> = get_text().expect("Could not load locale text");
+
+  /// The locale chosen for the messages
+  static ref CHOSEN_LOCALE: String = LOCALE.get().cloned().or_else(get_locale).unwrap_or("en-US".to_string());
+}
+
+/// The languages written right to left, including `iw`, the former code of Hebrew
+#[cfg(feature = "i18n")]
+const RIGHT_TO_LEFT_LANGUAGES: [&str; 10] = ["ar", "dv", "fa", "he", "iw", "ps", "sd", "ug", "ur", "yi"];
+
+/// A catalog of messages registered by the application, with the locale of the messages
+#[cfg(feature = "i18n")]
+type Catalog = (&'static str, &'static [(&'static str, &'static str)]);
+
+/// The catalogs registered with `register_messages`. They are registered once at startup and read
+/// for every description, so the lookups only share a read lock.
+#[cfg(feature = "i18n")]
+static CATALOGS: RwLock<Vec<Catalog>> = RwLock::new(Vec::new());
+
+/// Without i18n the locale is not queried, and the English text is always used.
+#[cfg(not(feature = "i18n"))]
+pub(crate) static T: &(dyn Text + Sync) = &en_us::EnUs{};
+
+/// A locale injected by the application, used in place of the system locale
+#[cfg(feature = "i18n")]
+static LOCALE: OnceLock<String> = OnceLock::new();
+
+/// Sets the locale used for messages instead of querying the system locale. This is useful where
+/// the system locale is not available, e.g. wasm, or when tests need deterministic messages.
+///
+/// The locale must be set before the first commandline is parsed, and can only be set once.
+///
+/// # Arguments
+///
+/// * `locale` - The BCP 47 locale. e.g. `en-US`
+///
+/// Returns `false` if the locale had already been set.
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::set_locale;
+/// assert_eq!(set_locale("en-US"), true);
+/// assert_eq!(set_locale("en-GB"), false);
+/// ```
+#[cfg(feature = "i18n")]
+pub fn set_locale(locale: &str) -> bool {
+  LOCALE.set(locale.to_string()).is_ok()
+}
+
+/// Registers the messages of the application for a locale, so descriptions can be given as message
+/// keys and the help is shown in the user's language. The descriptions of options, subcommands and
+/// examples are looked up in the catalog of the locale used for messages, then of its language,
+/// then of `en`, and a description with no message is shown as it is. Catalogs registered later
+/// take precedence.
+///
+/// # Arguments
+///
+/// * `locale` - The BCP 47 locale or language of the messages. e.g. `fr` or `fr-CA`
+/// * `messages` - The messages by key
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::{register_messages, set_locale, CommandLineDef};
+/// set_locale("fr-FR");
+/// register_messages("en", &[("file.description", "The file to read")]);
+/// register_messages("fr", &[("file.description", "Le fichier à lire")]);
+///
+/// let usage = CommandLineDef::new()
+///   .disable_help()
+///   .add_option(vec!["-f"], Some("file"), Some("-"), "file.description")
+///   .usage("program");
+/// assert!(usage.ends_with("-f <file> : Le fichier à lire [default: -]"), "{usage}");
+/// ```
+#[cfg(feature = "i18n")]
+pub fn register_messages(locale: &'static str, messages: &'static [(&'static str, &'static str)]) {
+  CATALOGS.write().unwrap_or_else(|e| e.into_inner()).push((locale, messages));
+}
+
+/// Returns the message registered for the key in the locale used for messages, or the key if it
+/// has no message
+#[cfg(feature = "i18n")]
+pub(crate) fn message(key: &'static str) -> &'static str {
+  let catalogs = CATALOGS.read().unwrap_or_else(|e| e.into_inner());
+  if catalogs.is_empty() {
+    return key;
+  }
+  [CHOSEN_LOCALE.as_str(), chosen_language(), "en"].iter()
+    .find_map(|locale| catalogs.iter().rev()
+      .filter(|(catalog_locale, _)| catalog_locale == locale)
+      .find_map(|(_, messages)| messages.iter().find(|(k, _)| *k == key).map(|(_, message)| *message)))
+    .unwrap_or(key)
+}
+
+/// Returns the language of the locale used for messages. e.g. `fr` for `fr-CA`
+#[cfg(feature = "i18n")]
+fn chosen_language() -> &'static str {
+  CHOSEN_LOCALE.split(['-', '_']).next().unwrap_or_default()
+}
+
+/// Returns true if the text used for messages is written right to left, which is when the text of
+/// the library is, or the application registered a catalog for a locale written right to left
+#[cfg(feature = "i18n")]
+pub(crate) fn right_to_left() -> bool {
+  T.right_to_left() || RIGHT_TO_LEFT_LANGUAGES.contains(&chosen_language()) && has_catalog()
+}
+
+/// Returns true if a catalog is registered for the locale used for messages or its language, so
+/// the descriptions are in that language rather than the English fallback
+#[cfg(feature = "i18n")]
+fn has_catalog() -> bool {
+  let catalogs = CATALOGS.read().unwrap_or_else(|e| e.into_inner());
+  catalogs.iter().any(|(locale, _)| *locale == CHOSEN_LOCALE.as_str() || *locale == chosen_language())
+}
+
+/// Without i18n the messages are in English, which is written left to right.
+#[cfg(not(feature = "i18n"))]
+#[inline]
+pub(crate) fn right_to_left() -> bool {
+  T.right_to_left()
+}
+
+/// Without i18n there are no catalogs, and the key is always used.
+#[cfg(not(feature = "i18n"))]
+#[inline]
+pub(crate) fn message(key: &'static str) -> &'static str {
+  key
 }
 
+#[cfg(feature = "i18n")]
 fn get_text() -> Option<Box<dyn Text + Sync>> {
-  let locale1= get_locale().unwrap_or("en-US".to_string());
+  let locale1= CHOSEN_LOCALE.clone();
   let text_factory = TEXT_MAP.get(locale1.as_str()).or_else(|| {
     let locale2 = locale1.split_once("-").unwrap_or(("en","US")).0;
     TEXT_MAP.get(locale2).or_else(|| {
-      TEXT_MAP.get("en").or_else(|| { None })
+      TEXT_MAP.get("en")
     })
   })?;
   Some(text_factory())
@@ -32,17 +168,91 @@ pub(crate) trait Text {
   fn option_value_required(&self, option: &str) -> String;
   fn option_multiple_found(&self, option: &str) -> String;
   fn option_multiple_flags(&self, flag: char) -> String;
-  fn option_invalid_flag(&self, option: &str) -> String;
   fn option_not_defined(&self, option: &str) -> String;
   fn option_invalid_long_name(&self, option: &str) -> String;
   fn option_invalid_short_name(&self, option: &str) -> String;
   fn option_invalid_name(&self, option: &str) -> String;
+  fn option_no_aliases(&self) -> String;
   fn option_required(&self, option: &str) -> String;
   fn option_not_found(&self, option: &str) -> String;
   fn argument_invalid_index(&self, index: usize) -> String;
-  fn option_cannot_convert(&self, option: &str, value: &str) -> String;
-  fn argument_cannot_convert(&self, index: usize, value: &str) -> String;
+  fn option_cannot_convert(&self, option: &str, value: &str, error: &str) -> String;
+  fn argument_cannot_convert(&self, index: usize, value: &str, error: &str) -> String;
   fn usage(&self, program_name: &str) -> String;
+  fn optstring_invalid(&self, optstring: &str) -> String;
+  fn usage_invalid(&self, usage: &str, token: &str) -> String;
+  fn option_non_ascii_short_name(&self, option: &str) -> String;
+  fn option_non_ascii_flags(&self, option: &str) -> String;
+  fn option_deprecated(&self, option: &str) -> String;
+  fn option_invalid_value(&self, option: &str, value: &str, valid_values: &str) -> String;
+  fn option_env_only(&self, option: &str, var: &str) -> String;
+  fn env_var_required(&self, var: &str) -> String;
+  fn env_var_unterminated(&self, var: &str) -> String;
+  #[cfg(feature = "dotenv")]
+  fn dotenv_invalid(&self, path: &str, error: &str) -> String;
+  fn option_invalid_section(&self, option: &str) -> String;
+  fn subcommand_not_defined(&self, command: &str) -> String;
+  fn subcommand_invalid_name(&self, command: &str) -> String;
+  fn subcommand_redefined(&self, command: &str) -> String;
+  fn subcommand_def_error(&self, command: &str, message: &str) -> String;
+  fn usage_command(&self) -> String;
+  fn usage_commands(&self) -> String;
+  fn usage_examples(&self) -> String;
+  fn line_unterminated(&self) -> String;
+  fn program_not_defined(&self, program: &str) -> String;
+  fn program_redefined(&self, program: &str) -> String;
+  fn usage_programs(&self) -> String;
+  fn option_value_not_allowed(&self, option: &str) -> String;
+  fn option_element_cannot_convert(&self, element: usize, option: &str, value: &str, error: &str) -> String;
+  #[cfg(feature = "std")]
+  fn option_entry_invalid(&self, option: &str, entry: &str) -> String;
+  #[cfg(feature = "std")]
+  fn option_key_duplicated(&self, option: &str, key: &str) -> String;
+  #[cfg(feature = "std")]
+  fn option_entry_cannot_convert(&self, key: &str, option: &str, value: &str, error: &str) -> String;
+  fn option_invalid_arity(&self, option: &str, arity: &str) -> String;
+  fn option_values_required(&self, option: &str, arity: usize, found: usize) -> String;
+  fn option_values_count_ne(&self, option: &str, expected: usize, found: usize) -> String;
+  fn option_values_range_required(&self, option: &str, min: usize, max: usize, found: usize) -> String;
+  fn argument_variadic_redefined(&self, argument: &str) -> String;
+  fn argument_variadic_subcommand(&self) -> String;
+  fn argument_alternative_both(&self, option: &str, argument: &str) -> String;
+  fn help_default(&self, value: &str) -> String;
+  fn help_required(&self) -> String;
+  fn help_env(&self, var: &str) -> String;
+  // the built-in options are defined with static descriptions and value names
+  fn help_description(&self) -> &'static str;
+  fn version_description(&self) -> &'static str;
+  fn verbose_description(&self) -> &'static str;
+  fn quiet_description(&self) -> &'static str;
+  fn color_description(&self) -> &'static str;
+  fn color_value_name(&self) -> &'static str;
+  fn output_format_description(&self) -> &'static str;
+  fn output_format_value_name(&self) -> &'static str;
+  fn log_level_description(&self) -> &'static str;
+  fn log_level_value_name(&self) -> &'static str;
+  fn help_see_docs(&self) -> &'static str;
+  fn quiet_output_description(&self) -> &'static str;
+  fn json_description(&self) -> &'static str;
+  fn timeout_description(&self) -> &'static str;
+  fn timeout_value_name(&self) -> &'static str;
+  fn retries_description(&self) -> &'static str;
+  fn retries_value_name(&self) -> &'static str;
+  // words accepted as booleans in values, besides true, false, 1 and 0
+  fn true_words(&self) -> &'static [&'static str];
+  fn false_words(&self) -> &'static [&'static str];
+  fn help_valid_values(&self, values: &str) -> String;
+  fn error_try_help(&self, program_name: &str, help: &str) -> String;
+  fn option_value_empty(&self, option: &str) -> String;
+  fn option_invalid_toggle(&self, option: &str) -> String;
+  fn help_default_command(&self) -> String;
+  fn subcommand_ambiguous(&self, command: &str, candidates: &str) -> String;
+  fn argument_missing(&self, argument: &str) -> String;
+  fn arguments_missing(&self, arguments: &str) -> String;
+  fn option_invalid_default(&self, option: &str, value: &str, values: &str) -> String;
+  fn option_ambiguous(&self, option: &str, candidates: &str) -> String;
+  fn help_docs(&self, url: &str) -> String;
+  fn right_to_left(&self) -> bool;
 }
 
 mod en_us;
@@ -1,3 +1,5 @@
+use alloc::format;
+use alloc::string::String;
 use crate::text::Text;
 
 pub(super) struct EnUs {}
@@ -24,10 +26,6 @@ impl Text for EnUs {
     format!("Multiple '-{flag}' options or aliases on commandline")
   }
   #[inline]
-  fn option_invalid_flag(&self, option: &str) -> String {
-    format!("Option '{option}' is not a flag")
-  }
-  #[inline]
   fn option_not_defined(&self, option: &str) -> String {
     format!("Option '{option}' not defined")
   }
@@ -44,6 +42,10 @@ impl Text for EnUs {
     format!("Invalid option name '{option}'. Options must start with '-' or '--'")
   }
   #[inline]
+  fn option_no_aliases(&self) -> String {
+    String::from("An option must have at least one alias")
+  }
+  #[inline]
   fn option_required(&self, option: &str) -> String {
     format!("Option '{option}' is required")
   }
@@ -56,15 +58,291 @@ impl Text for EnUs {
     format!("Argument index '{index}' is out of bounds")
   }
   #[inline]
-  fn option_cannot_convert(&self, option: &str, value: &str) -> String {
-    format!("Cannot convert option '{option}' from '{value}'")
+  fn option_cannot_convert(&self, option: &str, value: &str, error: &str) -> String {
+    format!("Cannot convert option '{option}' from '{value}': {error}")
   }
   #[inline]
-  fn argument_cannot_convert(&self, index: usize, value: &str) -> String {
-    format!("Cannot convert argument '{index}' from '{value}'")
+  fn argument_cannot_convert(&self, index: usize, value: &str, error: &str) -> String {
+    format!("Cannot convert argument '{index}' from '{value}': {error}")
   }
   #[inline]
   fn usage(&self, program_name: &str) -> String {
     format!("Usage: {program_name}")
   }
-}
\ No newline at end of file
+  #[inline]
+  fn optstring_invalid(&self, optstring: &str) -> String {
+    format!("Invalid option string '{optstring}'. Each ':' must follow an option character")
+  }
+  #[inline]
+  fn usage_invalid(&self, usage: &str, token: &str) -> String {
+    format!("Invalid usage '{usage}'. Unexpected '{token}'")
+  }
+  #[inline]
+  fn option_non_ascii_short_name(&self, option: &str) -> String {
+    format!("Invalid option name '{option}'. Short option names must be an ASCII character. e.g. -f")
+  }
+  #[inline]
+  fn option_non_ascii_flags(&self, option: &str) -> String {
+    format!("Flags '{option}' contain a non-ASCII character. Only ASCII flags can be concatenated")
+  }
+  #[inline]
+  fn option_deprecated(&self, option: &str) -> String {
+    format!("Option '{option}' is deprecated")
+  }
+  #[inline]
+  fn option_invalid_value(&self, option: &str, value: &str, valid_values: &str) -> String {
+    format!("Invalid value '{value}' for option '{option}'. Valid values are: {valid_values}")
+  }
+  #[inline]
+  fn option_env_only(&self, option: &str, var: &str) -> String {
+    format!("Option '{option}' can only be set with the environment variable '{var}'")
+  }
+  #[inline]
+  fn env_var_required(&self, var: &str) -> String {
+    format!("Environment variable '{var}' is required")
+  }
+  #[inline]
+  fn env_var_unterminated(&self, var: &str) -> String {
+    format!("Environment variable '{var}' has an unterminated quote or escape")
+  }
+  #[cfg(feature = "dotenv")]
+  #[inline]
+  fn dotenv_invalid(&self, path: &str, error: &str) -> String {
+    format!("Cannot load '{path}': {error}")
+  }
+  #[inline]
+  fn option_invalid_section(&self, option: &str) -> String {
+    format!("Invalid option name '{option}'. Sections in long option names must not be empty. e.g. --db.host")
+  }
+  #[inline]
+  fn subcommand_not_defined(&self, command: &str) -> String {
+    format!("Command '{command}' not defined")
+  }
+  #[inline]
+  fn subcommand_invalid_name(&self, command: &str) -> String {
+    format!("Invalid command name '{command}'. Command names must not be empty or start with '-'")
+  }
+  #[inline]
+  fn subcommand_redefined(&self, command: &str) -> String {
+    format!("Command '{command}' is already defined")
+  }
+  #[inline]
+  fn subcommand_def_error(&self, command: &str, message: &str) -> String {
+    format!("In command '{command}': {message}")
+  }
+  #[inline]
+  fn usage_command(&self) -> String {
+    String::from("<command>")
+  }
+  #[inline]
+  fn usage_commands(&self) -> String {
+    String::from("Commands:")
+  }
+  #[inline]
+  fn usage_examples(&self) -> String {
+    String::from("Examples:")
+  }
+  #[inline]
+  fn line_unterminated(&self) -> String {
+    String::from("Line has an unterminated quote or escape")
+  }
+  #[inline]
+  fn program_not_defined(&self, program: &str) -> String {
+    format!("Program '{program}' not defined")
+  }
+  #[inline]
+  fn program_redefined(&self, program: &str) -> String {
+    format!("Program '{program}' is already defined")
+  }
+  #[inline]
+  fn usage_programs(&self) -> String {
+    String::from("Programs:")
+  }
+  #[inline]
+  fn option_value_not_allowed(&self, option: &str) -> String {
+    format!("Option '{option}' does not take a value")
+  }
+  #[inline]
+  fn option_element_cannot_convert(&self, element: usize, option: &str, value: &str, error: &str) -> String {
+    format!("Cannot convert element {element} of option '{option}' from '{value}': {error}")
+  }
+  #[cfg(feature = "std")]
+  #[inline]
+  fn option_entry_invalid(&self, option: &str, entry: &str) -> String {
+    format!("Invalid entry '{entry}' for option '{option}'. Entries are written key=value")
+  }
+  #[cfg(feature = "std")]
+  #[inline]
+  fn option_key_duplicated(&self, option: &str, key: &str) -> String {
+    format!("Key '{key}' is repeated in option '{option}'")
+  }
+  #[cfg(feature = "std")]
+  #[inline]
+  fn option_entry_cannot_convert(&self, key: &str, option: &str, value: &str, error: &str) -> String {
+    format!("Cannot convert key '{key}' of option '{option}' from '{value}': {error}")
+  }
+  #[inline]
+  fn option_invalid_arity(&self, option: &str, arity: &str) -> String {
+    format!("Invalid arity {arity} for option '{option}'. Only options with a value take values, and they take at least one")
+  }
+  #[inline]
+  fn option_values_required(&self, option: &str, arity: usize, found: usize) -> String {
+    format!("Option '{option}' requires {arity} values, found {found}")
+  }
+  #[inline]
+  fn option_values_count_ne(&self, option: &str, expected: usize, found: usize) -> String {
+    format!("Option '{option}' has {found} values, expected {expected}")
+  }
+  #[inline]
+  fn option_values_range_required(&self, option: &str, min: usize, max: usize, found: usize) -> String {
+    format!("Option '{option}' requires {min} to {max} values, found {found}")
+  }
+  #[inline]
+  fn argument_variadic_redefined(&self, argument: &str) -> String {
+    format!("Argument '{argument}' cannot be variadic. Only one argument can be variadic")
+  }
+  #[inline]
+  fn argument_variadic_subcommand(&self) -> String {
+    String::from("A definition with a variadic argument cannot have subcommands")
+  }
+  #[inline]
+  fn argument_alternative_both(&self, option: &str, argument: &str) -> String {
+    format!("Option '{option}' and argument '{argument}' cannot both be given")
+  }
+  #[inline]
+  fn help_default(&self, value: &str) -> String {
+    format!("[default: {value}]")
+  }
+  #[inline]
+  fn help_required(&self) -> String {
+    String::from("(required)")
+  }
+  #[inline]
+  fn help_env(&self, var: &str) -> String {
+    format!("[env: {var}]")
+  }
+  #[inline]
+  fn help_description(&self) -> &'static str {
+    "Display usage message"
+  }
+  #[inline]
+  fn version_description(&self) -> &'static str {
+    "Display version information"
+  }
+  #[inline]
+  fn verbose_description(&self) -> &'static str {
+    "Increase verbosity, may be repeated"
+  }
+  #[inline]
+  fn quiet_description(&self) -> &'static str {
+    "Decrease verbosity, may be repeated"
+  }
+  #[inline]
+  fn color_description(&self) -> &'static str {
+    "Color the output: auto, always or never"
+  }
+  #[inline]
+  fn color_value_name(&self) -> &'static str {
+    "when"
+  }
+  #[inline]
+  fn output_format_description(&self) -> &'static str {
+    "Write the output as: table, json or yaml"
+  }
+  #[inline]
+  fn output_format_value_name(&self) -> &'static str {
+    "format"
+  }
+  #[inline]
+  fn log_level_description(&self) -> &'static str {
+    "Set the log level: off, error, warn, info, debug or trace"
+  }
+  #[inline]
+  fn log_level_value_name(&self) -> &'static str {
+    "level"
+  }
+  #[inline]
+  fn help_see_docs(&self) -> &'static str {
+    "see docs"
+  }
+  #[inline]
+  fn quiet_output_description(&self) -> &'static str {
+    "Do not print informational output"
+  }
+  #[inline]
+  fn json_description(&self) -> &'static str {
+    "Write the output as JSON"
+  }
+  #[inline]
+  fn timeout_description(&self) -> &'static str {
+    "The timeout of each request in seconds"
+  }
+  #[inline]
+  fn timeout_value_name(&self) -> &'static str {
+    "seconds"
+  }
+  #[inline]
+  fn retries_description(&self) -> &'static str {
+    "The number of times a failed request is retried"
+  }
+  #[inline]
+  fn retries_value_name(&self) -> &'static str {
+    "count"
+  }
+  #[inline]
+  fn true_words(&self) -> &'static [&'static str] {
+    &["yes", "on"]
+  }
+  #[inline]
+  fn false_words(&self) -> &'static [&'static str] {
+    &["no", "off"]
+  }
+  #[inline]
+  fn help_valid_values(&self, values: &str) -> String {
+    format!("[values: {values}]")
+  }
+  #[inline]
+  fn error_try_help(&self, program_name: &str, help: &str) -> String {
+    format!("Try '{program_name} {help}' for more information.")
+  }
+  #[inline]
+  fn option_value_empty(&self, option: &str) -> String {
+    format!("Option '{option}' does not allow an empty value")
+  }
+  #[inline]
+  fn option_invalid_toggle(&self, option: &str) -> String {
+    format!("Invalid toggle '{option}'. Toggles are flags with a short alias. e.g. -x")
+  }
+  #[inline]
+  fn help_default_command(&self) -> String {
+    String::from("(default)")
+  }
+  #[inline]
+  fn subcommand_ambiguous(&self, command: &str, candidates: &str) -> String {
+    format!("Command '{command}' is ambiguous, it could be {candidates}")
+  }
+  #[inline]
+  fn argument_missing(&self, argument: &str) -> String {
+    format!("Missing required argument <{argument}>")
+  }
+  #[inline]
+  fn arguments_missing(&self, arguments: &str) -> String {
+    format!("Missing required arguments {arguments}")
+  }
+  #[inline]
+  fn option_invalid_default(&self, option: &str, value: &str, values: &str) -> String {
+    format!("Invalid default value '{value}' for option '{option}'. Valid values are: {values}")
+  }
+  #[inline]
+  fn option_ambiguous(&self, option: &str, candidates: &str) -> String {
+    format!("Option '{option}' is ambiguous, it could be {candidates}")
+  }
+  #[inline]
+  fn help_docs(&self, url: &str) -> String {
+    format!("[docs: {url}]")
+  }
+  #[inline]
+  fn right_to_left(&self) -> bool {
+    false
+  }
+}
@@ -0,0 +1,143 @@
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::type_name;
+use core::marker::PhantomData;
+use core::fmt::Display;
+use core::str::FromStr;
+use super::cl_def::CommandLineDef;
+use super::command_line::CommandLine;
+use super::def_error::DefErrorKind;
+use super::json_schema::json_type;
+use super::from_str_lenient;
+use crate::text::T;
+
+/// An option whose values are converted to `T`, returned by `CommandLineDef::add_option_t`. It is
+/// used to retrieve the value with `CommandLine::get`, which always returns a `T`.
+pub struct TypedOption<T> {
+  /// The alias the value is retrieved with
+  alias:&'static str,
+  /// The type of the value
+  value_type:PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for TypedOption<T> {
+  #[inline]
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<T> Copy for TypedOption<T> {}
+
+impl<T> TypedOption<T> {
+  /// Returns the alias the value is retrieved with
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let port = CommandLineDef::new().add_option_t::<u16>(vec!["-p","--port"], Some("8080"), "The port");
+  /// assert_eq!(port.alias(), "-p");
+  /// ```
+  #[inline]
+  pub fn alias(&self) -> &'static str {
+    self.alias
+  }
+}
+
+impl CommandLineDef {
+  /// Adds an option whose values are converted to `T`. The value name shown in the usage message is
+  /// the name of the type. Values from every source are checked when the commandline is parsed, so
+  /// a value that cannot be converted is an [`crate::ErrorKind::InvalidValue`] error rather than a
+  /// panic when the value is retrieved. Returns the TypedOption used to retrieve the value with
+  /// `CommandLine::get`.
+  ///
+  /// # Arguments
+  ///
+  /// * `aliases` - The aliases for this option. e.g. `"-p","--port"`
+  /// * `default_value` - The value used when the option is not specified. If `None`, the option is
+  ///   required.
+  /// * `description` - The description of this option. e.g. `The port to listen on`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the aliases are not valid, as for `add_option`
+  /// * Panics if the default value cannot be converted to `T`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// let port = cl_def.add_option_t::<u16>(vec!["-p","--port"], Some("8080"), "The port to listen on");
+  ///
+  /// let args=vec!["program".to_string(), "-p".to_string(), "80".to_string()];
+  /// let cl = cl_def.parse(args.into_iter());
+  /// assert_eq!(cl.get(port), 80);
+  ///
+  /// let args=vec!["program".to_string(), "-p".to_string(), "http".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  /// assert_eq!(err.message(), "Cannot convert option '-p' from 'http': invalid digit found in string");
+  /// ```
+  pub fn add_option_t<T:FromStr<Err:Display> + 'static>(&mut self, aliases:Vec<&'static str>, default_value:Option<&'static str>,
+                                          description:&'static str) -> TypedOption<T> {
+    self.add_typed_option(aliases, type_short_name::<T>(), default_value, description)
+  }
+
+  /// Adds an option whose values are converted to `T`, with the value name shown in the usage
+  /// message
+  pub(crate) fn add_typed_option<T:FromStr<Err:Display> + 'static>(&mut self, aliases:Vec<&'static str>, value_name:&'static str,
+                                                                   default_value:Option<&'static str>, description:&'static str) -> TypedOption<T> {
+    let alias = aliases.first().copied().unwrap_or_default();
+    if let Some((default, Err(e))) = default_value.map(|default| (default, from_str_lenient::<T>(default))) {
+      self.def_failed(DefErrorKind::InvalidDefault, T.option_cannot_convert(alias, default, &e.to_string()));
+    }
+    let count = self.option_defs.len();
+    self.add_option(aliases, Some(value_name), default_value, description);
+    // the option is not added if the definition has an error that is kept for build
+    let alias = match self.option_defs.get_mut(count) {
+      Some(od) => {
+        od.validator = Some(Arc::new(|value| from_str_lenient::<T>(value).map(|_| ()).map_err(|e| e.to_string())));
+        od.json_type = json_type::<T>();
+        od.aliases[0]
+      },
+      None => alias,
+    };
+    TypedOption {
+      alias,
+      value_type:PhantomData,
+    }
+  }
+}
+
+impl CommandLine {
+  /// Returns the value of a typed option
+  ///
+  /// # Arguments
+  ///
+  /// * `option` - The TypedOption returned by `CommandLineDef::add_option_t`
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let mut cl_def = CommandLineDef::new();
+  /// let ratio = cl_def.add_option_t::<f32>(vec!["--ratio"], Some("0.5"), "The ratio");
+  ///
+  /// let cl = cl_def.parse(vec!["program".to_string()].into_iter());
+  /// assert_eq!(cl.get(ratio), 0.5);
+  /// ```
+  #[inline]
+  pub fn get<T:FromStr<Err:Display>>(&self, option:TypedOption<T>) -> T {
+    self.option(option.alias)
+  }
+}
+
+/// Returns the name of the type without its path. e.g. `PathBuf` for `std::path::PathBuf`
+fn type_short_name<T>() -> &'static str {
+  let name = type_name::<T>();
+  let name = name.split('<').next().unwrap_or(name);
+  name.rsplit("::").next().unwrap_or(name)
+}
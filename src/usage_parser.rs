@@ -0,0 +1,143 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::text::T;
+use super::cl_def::CommandLineDef;
+use super::{leak, LONG_HELP, LONG_OPTION, panic_msg, SHORT_HELP, SHORT_OPTION};
+
+impl CommandLineDef {
+  /// Creates a new CommandLineDef from a usage string. e.g.
+  /// `"Usage: prog [-v] --file <path> <src> <dest>"`
+  ///
+  /// The usage string has the same form as the usage message generated by cl_parse.
+  ///
+  ///  - `-v` or `--verbose` defines a flag, and `-xvf` defines a flag for each character
+  ///  - `-f <path>` or `--file <path>` defines a required option with the value name `path`
+  ///  - `[-f <path>]` defines an optional option, which defaults to an empty value
+  ///  - `-f|--file <path>` defines an option with several aliases
+  ///  - `<src>` defines an argument
+  ///
+  /// The leading `Usage:` and the program name are skipped, and `-h` and `--help` are provided by
+  /// the built-in help option.
+  ///
+  /// # Arguments
+  ///
+  /// * `usage` - The usage string
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the usage string contains something other than options and arguments, or its
+  ///   brackets are unbalanced
+  /// * Panics if an option is not a valid option name, or is defined more than once
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let args=vec![
+  ///   "program".to_string(),
+  ///   "--file".to_string(), "in.txt".to_string(),
+  ///   "a".to_string(), "b".to_string(),
+  /// ];
+  /// let cl = CommandLineDef::from_usage("Usage: prog [-v] --file <path> <src> <dest>")
+  ///   .parse(args.into_iter());
+  ///
+  /// let v:bool = cl.option("-v");
+  /// assert_eq!(v, false);
+  ///
+  /// let file:String = cl.option("--file");
+  /// assert_eq!(file, "in.txt");
+  ///
+  /// let src:String = cl.argument(0);
+  /// assert_eq!(src, "a");
+  /// ```
+  pub fn from_usage(usage:&str) -> Self {
+    let mut cl_def = CommandLineDef::new();
+    let trimmed = usage.trim_start();
+    let trimmed = match trimmed.get(..6) {
+      Some(prefix) if prefix.eq_ignore_ascii_case("usage:") => &trimmed[6..],
+      _ => trimmed,
+    };
+    let mut tokens = tokenize(trimmed).into_iter().skip(1).peekable();
+    let mut optional = false;
+
+    while let Some(token) = tokens.next() {
+      match token.as_str() {
+        "[" if !optional => optional = true,
+        "]" if optional => optional = false,
+        _ if token.starts_with('<') && !optional => {
+          cl_def.add_argument(leak(token.trim_matches(['<', '>']).to_string()));
+        },
+        _ if token.starts_with(SHORT_OPTION) => {
+          let value_name = tokens.next_if(|next| next.starts_with('<'))
+            .map(|value_name| leak(value_name.trim_matches(['<', '>']).to_string()));
+          if value_name.is_none() && !token.starts_with(LONG_OPTION) && token.len() > 2 && !token.contains('|') {
+            for flag in token.chars().skip(1) {
+              add_flag(&mut cl_def, vec![leak(format!("-{flag}"))]);
+            }
+          } else {
+            let aliases:Vec<&'static str> = token.split('|').map(|alias| leak(alias.to_string())).collect();
+            match value_name {
+              None => add_flag(&mut cl_def, aliases),
+              Some(value_name) => {
+                let default = if optional { Some("") } else { None };
+                cl_def.add_option(aliases, Some(value_name), default, "");
+              }
+            }
+          }
+        },
+        _ => panic_msg(T.usage_invalid(usage, &token)),
+      }
+    }
+    if optional {
+      panic_msg(T.usage_invalid(usage, "["));
+    }
+    cl_def
+  }
+}
+
+/// Adds a flag unless it is the built-in help option
+#[inline]
+fn add_flag(cl_def:&mut CommandLineDef, aliases:Vec<&'static str>) {
+  if !aliases.iter().all(|alias| *alias == SHORT_HELP || *alias == LONG_HELP) {
+    cl_def.add_flag(aliases, "");
+  }
+}
+
+/// Splits a usage string into words, `[`, `]`, and `<value names>`, which may contain spaces
+#[inline]
+fn tokenize(usage:&str) -> Vec<String> {
+  let mut tokens:Vec<String> = Vec::default();
+  let mut token = String::default();
+  let mut in_value_name = false;
+
+  for c in usage.chars() {
+    match c {
+      '<' if !in_value_name => {
+        in_value_name = true;
+        token.push(c);
+      },
+      '>' if in_value_name => {
+        in_value_name = false;
+        token.push(c);
+      },
+      '[' | ']' if !in_value_name => {
+        if !token.is_empty() {
+          tokens.push(core::mem::take(&mut token));
+        }
+        tokens.push(c.to_string());
+      },
+      _ if c.is_whitespace() && !in_value_name => {
+        if !token.is_empty() {
+          tokens.push(core::mem::take(&mut token));
+        }
+      },
+      _ => token.push(c),
+    }
+  }
+  if !token.is_empty() {
+    tokens.push(token);
+  }
+  tokens
+}
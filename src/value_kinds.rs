@@ -0,0 +1,166 @@
+use alloc::vec::Vec;
+#[cfg(feature = "semver")]
+use semver::{Version, VersionReq};
+#[cfg(feature = "uuid")]
+use uuid::Uuid;
+#[cfg(feature = "glob")]
+use glob::Pattern;
+use super::cl_def::CommandLineDef;
+use super::typed_option::TypedOption;
+
+#[cfg(feature = "semver")]
+impl CommandLineDef {
+  /// Adds an option whose values are semantic versions, such as `1.2.3` or `2.0.0-rc.1`. Values
+  /// are checked when the commandline is parsed.
+  ///
+  /// # Arguments
+  ///
+  /// * `aliases` - The aliases for this option. e.g. `"--min-version"`
+  /// * `default_value` - The version used when the option is not specified. If `None`, the option
+  ///   is required.
+  /// * `description` - The description of this option. e.g. `The minimum version`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the aliases are not valid, as for `add_option`
+  /// * Panics if the default value is not a semantic version
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// let min_version = cl_def.add_semver_option(vec!["--min-version"], Some("1.0.0"), "The minimum version");
+  ///
+  /// let args=vec!["program".to_string(), "--min-version".to_string(), "1.2.3".to_string()];
+  /// let cl = cl_def.parse(args.into_iter());
+  /// assert_eq!(cl.get(min_version), semver::Version::new(1, 2, 3));
+  ///
+  /// let args=vec!["program".to_string(), "--min-version".to_string(), "1.2".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  /// ```
+  #[inline]
+  pub fn add_semver_option(&mut self, aliases:Vec<&'static str>, default_value:Option<&'static str>,
+                           description:&'static str) -> TypedOption<Version> {
+    self.add_typed_option(aliases, "version", default_value, description)
+  }
+
+  /// Adds an option whose values are semantic version requirements, such as `^1.2`, `>=1.0, <2.0`
+  /// or `1.2.3`. Values are checked when the commandline is parsed.
+  ///
+  /// # Arguments
+  ///
+  /// * `aliases` - The aliases for this option. e.g. `"--requires"`
+  /// * `default_value` - The requirement used when the option is not specified. If `None`, the
+  ///   option is required.
+  /// * `description` - The description of this option. e.g. `The versions to accept`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the aliases are not valid, as for `add_option`
+  /// * Panics if the default value is not a semantic version requirement
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let mut cl_def = CommandLineDef::new();
+  /// let requires = cl_def.add_semver_req_option(vec!["--requires"], Some("*"), "The versions to accept");
+  ///
+  /// let args=vec!["program".to_string(), "--requires".to_string(), ">=1.2, <2".to_string()];
+  /// let cl = cl_def.parse(args.into_iter());
+  /// assert!(cl.get(requires).matches(&semver::Version::new(1, 4, 0)));
+  /// ```
+  #[inline]
+  pub fn add_semver_req_option(&mut self, aliases:Vec<&'static str>, default_value:Option<&'static str>,
+                               description:&'static str) -> TypedOption<VersionReq> {
+    self.add_typed_option(aliases, "range", default_value, description)
+  }
+}
+
+#[cfg(feature = "uuid")]
+impl CommandLineDef {
+  /// Adds an option whose values are UUIDs, such as `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+  /// Values are checked when the commandline is parsed, so a malformed ID is an
+  /// [`crate::ErrorKind::InvalidValue`] error describing what is wrong with it. The value can also
+  /// be retrieved with `CommandLine::option::<Uuid>`.
+  ///
+  /// # Arguments
+  ///
+  /// * `aliases` - The aliases for this option. e.g. `"--id"`
+  /// * `default_value` - The UUID used when the option is not specified. If `None`, the option is
+  ///   required.
+  /// * `description` - The description of this option. e.g. `The ID of the job`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the aliases are not valid, as for `add_option`
+  /// * Panics if the default value is not a UUID
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// use uuid::Uuid;
+  /// let mut cl_def = CommandLineDef::new();
+  /// let id = cl_def.add_uuid_option(vec!["--id"], None, "The ID of the job");
+  ///
+  /// let args=vec!["program".to_string(), "--id".to_string(), "67e55044-10b1-426f-9247-bb680e5fe0c8".to_string()];
+  /// let cl = cl_def.parse(args.into_iter());
+  /// assert_eq!(cl.get(id), Uuid::from_u128(0x67e5504410b1426f9247bb680e5fe0c8));
+  /// assert_eq!(cl.option::<Uuid>("--id"), cl.get(id));
+  ///
+  /// let args=vec!["program".to_string(), "--id".to_string(), "67e55044".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  /// ```
+  #[inline]
+  pub fn add_uuid_option(&mut self, aliases:Vec<&'static str>, default_value:Option<&'static str>,
+                         description:&'static str) -> TypedOption<Uuid> {
+    self.add_typed_option(aliases, "uuid", default_value, description)
+  }
+}
+
+#[cfg(feature = "glob")]
+impl CommandLineDef {
+  /// Adds an option whose values are glob patterns, such as `src/**/*.rs`. Values are compiled
+  /// when the commandline is parsed, so a pattern with a syntax error is an
+  /// [`crate::ErrorKind::InvalidValue`] error giving the position of the error. The compiled
+  /// pattern is retrieved with `CommandLine::get`.
+  ///
+  /// # Arguments
+  ///
+  /// * `aliases` - The aliases for this option. e.g. `"--include"`
+  /// * `default_value` - The pattern used when the option is not specified. If `None`, the option
+  ///   is required.
+  /// * `description` - The description of this option. e.g. `The files to include`
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the aliases are not valid, as for `add_option`
+  /// * Panics if the default value is not a valid pattern
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::{CommandLineDef, ErrorKind};
+  /// let mut cl_def = CommandLineDef::new();
+  /// let include = cl_def.add_glob_option(vec!["--include"], Some("*"), "The files to include");
+  ///
+  /// let args=vec!["program".to_string(), "--include".to_string(), "src/**/*.rs".to_string()];
+  /// let cl = cl_def.parse(args.into_iter());
+  /// assert!(cl.get(include).matches("src/text/en_us.rs"));
+  ///
+  /// let args=vec!["program".to_string(), "--include".to_string(), "src/[a".to_string()];
+  /// let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  /// assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  /// assert_eq!(err.message(),
+  ///   "Cannot convert option '--include' from 'src/[a': Pattern syntax error near position 4: invalid range pattern");
+  /// ```
+  #[inline]
+  pub fn add_glob_option(&mut self, aliases:Vec<&'static str>, default_value:Option<&'static str>,
+                         description:&'static str) -> TypedOption<Pattern> {
+    self.add_typed_option(aliases, "pattern", default_value, description)
+  }
+}
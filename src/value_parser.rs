@@ -0,0 +1,198 @@
+use alloc::string::{String, ToString};
+use core::fmt::Display;
+use super::command_line::CommandLine;
+use crate::text::T;
+
+/// Converts option and argument values, for types that do not implement `FromStr`, or need
+/// context such as a base directory or a lookup table. Closures returning a `Result` are value
+/// parsers.
+///
+/// # Examples
+///
+/// ```
+/// use cl_parse::{CommandLineDef, ValueParser};
+///
+/// struct Percent;
+///
+/// impl ValueParser for Percent {
+///   type Value = f64;
+///   type Error = String;
+///
+///   fn parse_value(&self, value:&str) -> Result<f64, String> {
+///     let number = value.strip_suffix('%').ok_or("missing '%'")?;
+///     number.parse::<f64>().map(|n| n / 100.0).map_err(|e| e.to_string())
+///   }
+/// }
+///
+/// let env_args=vec![String::from("program"), String::from("--ratio"), String::from("50%")];
+/// let cl = CommandLineDef::new()
+///   .add_option(vec!["--ratio"], Some("percent"), None, "The ratio")
+///   .parse(env_args.into_iter());
+///
+/// assert_eq!(cl.option_with("--ratio", Percent), 0.5);
+/// ```
+pub trait ValueParser {
+  /// The type the values are converted to
+  type Value;
+  /// The error returned when a value cannot be converted
+  type Error: Display;
+
+  /// Converts a value
+  ///
+  /// # Arguments
+  ///
+  /// * `value` - The value of the option or argument
+  ///
+  /// # Errors
+  ///
+  /// Returns an error if the value cannot be converted
+  fn parse_value(&self, value:&str) -> Result<Self::Value, Self::Error>;
+}
+
+impl<V, E:Display, F:Fn(&str) -> Result<V, E>> ValueParser for F {
+  type Value = V;
+  type Error = E;
+
+  #[inline]
+  fn parse_value(&self, value:&str) -> Result<V, E> {
+    self(value)
+  }
+}
+
+impl CommandLine {
+  /// Returns the value of an option converted with `TryFrom<&str>`, for types such as newtypes
+  /// that do not implement `FromStr`
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the option
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  /// * Panics with the conversion error if the value cannot be converted
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  ///
+  /// struct Port(u16);
+  ///
+  /// impl TryFrom<&str> for Port {
+  ///   type Error = String;
+  ///
+  ///   fn try_from(value:&str) -> Result<Self, String> {
+  ///     match value.parse::<u16>() {
+  ///       Ok(port) if port >= 1024 => Ok(Port(port)),
+  ///       _ => Err(String::from("not a port from 1024 to 65535")),
+  ///     }
+  ///   }
+  /// }
+  ///
+  /// let env_args=vec![String::from("program"), String::from("-p"), String::from("8080")];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["-p","--port"], Some("port"), None, "The port")
+  ///   .parse(env_args.into_iter());
+  ///
+  /// let port:Port = cl.option_try_from("--port");
+  /// assert_eq!(port.0, 8080);
+  /// ```
+  #[inline]
+  pub fn option_try_from<V>(&self, name:&str) -> V
+  where V: for<'a> TryFrom<&'a str, Error:Display> {
+    self.option_with(name, |value:&str| V::try_from(value).map_err(|e| e.to_string()))
+  }
+
+  /// Returns the value of an option converted with a ValueParser
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The name of the option
+  /// * `parser` - Converts the value
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the option is not defined
+  /// * Panics with the conversion error if the value cannot be converted
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let env_args=vec![String::from("program"), String::from("--size"), String::from("4k")];
+  /// let cl = CommandLineDef::new()
+  ///   .add_option(vec!["--size"], Some("size"), None, "The size, with an optional k suffix")
+  ///   .parse(env_args.into_iter());
+  ///
+  /// let size = cl.option_with("--size", |value:&str| match value.strip_suffix('k') {
+  ///   Some(k) => k.parse::<u64>().map(|k| k * 1024),
+  ///   None => value.parse::<u64>(),
+  /// });
+  /// assert_eq!(size, 4096);
+  /// ```
+  pub fn option_with<P:ValueParser>(&self, name:&str, parser:P) -> P::Value {
+    let option:&str = self.value(name).unwrap_or_else(|| panic!("{}", T.option_not_found(name)));
+    match parser.parse_value(option) {
+      Ok(value) => value,
+      Err(e) => panic!("{}", T.option_cannot_convert(name, option, &e.to_string())),
+    }
+  }
+
+  /// Returns an argument converted with `TryFrom<&str>`
+  ///
+  /// # Arguments
+  ///
+  /// * `index` - The index of the argument
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the index is out of range
+  /// * Panics with the conversion error if the argument cannot be converted
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let env_args=vec![String::from("program"), String::from("notes.txt")];
+  /// let cl = CommandLineDef::new().add_argument("file").parse(env_args.into_iter());
+  ///
+  /// let file:std::ffi::OsString = cl.argument_try_from(0);
+  /// assert_eq!(file, "notes.txt");
+  /// ```
+  #[inline]
+  pub fn argument_try_from<V>(&self, index:usize) -> V
+  where V: for<'a> TryFrom<&'a str, Error:Display> {
+    self.argument_with(index, |value:&str| V::try_from(value).map_err(|e| e.to_string()))
+  }
+
+  /// Returns an argument converted with a ValueParser
+  ///
+  /// # Arguments
+  ///
+  /// * `index` - The index of the argument
+  /// * `parser` - Converts the argument
+  ///
+  /// # Panics
+  ///
+  /// * Panics if the index is out of range
+  /// * Panics with the conversion error if the argument cannot be converted
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use cl_parse::CommandLineDef;
+  /// let env_args=vec![String::from("program"), String::from("0x1f")];
+  /// let cl = CommandLineDef::new().add_argument("mask").parse(env_args.into_iter());
+  ///
+  /// let mask = cl.argument_with(0, |value:&str| u32::from_str_radix(value.trim_start_matches("0x"), 16));
+  /// assert_eq!(mask, 31);
+  /// ```
+  pub fn argument_with<P:ValueParser>(&self, index:usize, parser:P) -> P::Value {
+    let argument:String = self.argument(index);
+    match parser.parse_value(&argument) {
+      Ok(value) => value,
+      Err(e) => panic!("{}", T.argument_cannot_convert(index, &argument, &e.to_string())),
+    }
+  }
+}
@@ -0,0 +1,54 @@
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+
+/// The kinds of non-fatal issues that can be found while parsing a commandline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WarningKind {
+  /// A deprecated option was used on the commandline
+  DeprecatedOption,
+}
+
+/// A non-fatal issue found while parsing a commandline. Warnings are passed to the sink set with
+/// `CommandLineDef::on_warning`, and parsing continues.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+  /// The kind of warning
+  kind: WarningKind,
+  /// The message describing the warning
+  message: String,
+}
+
+impl Warning {
+  /// Creates a new Warning
+  ///
+  /// # Arguments
+  ///
+  /// * `kind` - The kind of warning
+  /// * `message` - The message describing the warning
+  #[inline]
+  pub(crate) fn new(kind: WarningKind, message: String) -> Self {
+    Warning {
+      kind,
+      message,
+    }
+  }
+
+  /// Returns the kind of warning
+  #[inline]
+  pub fn kind(&self) -> WarningKind {
+    self.kind
+  }
+
+  /// Returns the message describing the warning
+  #[inline]
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+}
+
+impl Display for Warning {
+  fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+    write!(f, "{}", self.message)
+  }
+}
@@ -1,4 +1,11 @@
-use cl_parse::CommandLineDef;
+#![allow(clippy::bool_assert_comparison)]
+
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "std")]
+use cl_parse::config::Format;
+use cl_parse::{CommandLineDef, DefErrorKind, ErrorKind, HelpLayout, MultiCall, OutputFormat, ValidValuesPosition, WarningKind};
+#[cfg(feature = "std")]
+use cl_parse::{ColorChoice, ErrorBehavior, ParseError, Source, StaticCommandLineDef};
 
 #[test]
 fn should_return_default_boolean_false() {
@@ -280,7 +287,7 @@ fn should_panic_for_too_many_args() {
 }
 
 #[test]
-#[should_panic(expected = "Defined 3 arguments, found 2 arguments\nUsage: test [-h] <arg-1> <arg-2> <arg-3>")]
+#[should_panic(expected = "Missing required argument <arg-3>\nUsage: test [-h] <arg-1> <arg-2> <arg-3>")]
 fn should_panic_for_too_few_args() {
   let env_args=vec![String::from("test"), String::from("arg1"), String::from("arg2")];
   let cl = CommandLineDef::new()
@@ -319,8 +326,8 @@ fn should_panic_for_repeated_concat_flags() {
 }
 
 #[test]
-#[should_panic(expected = "Option '-b' is not a flag\nUsage: test [-hm] [-b <batch size>]")]
-fn should_panic_for_not_a_concat_flag() {
+#[should_panic(expected = "A value is required for option '-b'\nUsage: test [-hm] [-b <batch size>]")]
+fn should_panic_for_concat_option_without_value() {
   let env_args=vec![String::from("test"), String::from("-mb")];
   let cl = CommandLineDef::new()
       .add_option(vec!["-b", "--batch"], Some("batch size"),Some("10"),"Batch Size")
@@ -331,6 +338,29 @@ fn should_panic_for_not_a_concat_flag() {
   assert_eq!(m, true);
 }
 
+#[test]
+fn should_take_value_of_last_concat_option() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_flag(vec!["-x"], "Extract")
+    .add_counter(vec!["-v"], "Increase the verbosity")
+    .add_option(vec!["-f", "--file"], Some("archive"), None, "The archive")
+    .add_option(vec!["-C"], Some("dir"), Some("."), "The directory");
+
+  for args in [vec!["tar", "-xvf", "a.tar"], vec!["tar", "-xvfa.tar"], vec!["tar", "-vxf", "a.tar", "-vC", "out"]] {
+    let cl = cl_def.parse(args.iter().map(|a| a.to_string()));
+    assert!(cl.option::<bool>("-x"));
+    assert_eq!(cl.option::<String>("--file"), "a.tar");
+  }
+
+  let cl = cl_def.parse(["tar", "-vxf", "a.tar", "-vCout"].iter().map(|a| a.to_string()));
+  assert_eq!(cl.option::<u32>("-v"), 2);
+  assert_eq!(cl.option::<String>("-C"), "out");
+
+  let err = cl_def.try_parse(["tar", "-fx", "-f", "b.tar"].iter().map(|a| a.to_string())).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::MultipleOccurrences);
+}
+
 #[test]
 #[should_panic(expected = "Option '-u' not defined\nUsage: test [-bhm]")]
 fn should_panic_for_undefined_concat_flags() {
@@ -363,7 +393,7 @@ fn should_panic_for_redefined_alias() {
 }
 
 #[test]
-#[should_panic(expected = "Usage: test [-bfh] -n <num> <arg-0> <arg-1> <arg-2>\n     -h, --help : Display usage message\n  -b, --boolean : A boolean value\n     -f, --faux : Another boolean value\n-n, --num <num> : A required numeric value")]
+#[should_panic(expected = "Usage: test [-bfh] -n <num> <arg-0> <arg-1> <arg-2>\n     -h, --help : Display usage message\n  -b, --boolean : A boolean value\n     -f, --faux : Another boolean value\n-n, --num <num> : A required numeric value (required)")]
 fn should_display_h_help() {
   let env_args = vec![
     String::from("test"),
@@ -385,7 +415,7 @@ fn should_display_h_help() {
 }
 
 #[test]
-#[should_panic(expected = "Option '-e' not defined\nUsage: test [-bfh] -n <num> <arg-0> <arg-1> <arg-2>\n     -h, --help : Display usage message\n  -b, --boolean : A boolean value\n     -f, --faux : Another boolean value\n-n, --num <num> : A required numeric value")]
+#[should_panic(expected = "Option '-e' not defined\nUsage: test [-bfh] -n <num> <arg-0> <arg-1> <arg-2>\n     -h, --help : Display usage message\n  -b, --boolean : A boolean value\n     -f, --faux : Another boolean value\n-n, --num <num> : A required numeric value (required)")]
 fn should_display_help_help() {
   let env_args = vec![
     String::from("test"),
@@ -416,4 +446,2214 @@ fn should_panic_undefined_flag() {
 
   CommandLineDef::new()
       .parse(env_args.into_iter());
-}
\ No newline at end of file
+}
+#[test]
+fn should_return_help_error() {
+  let env_args = vec![String::from("test"), String::from("--help")];
+
+  let err = CommandLineDef::new()
+      .add_flag(vec!["-b", "--boolean"], "A boolean value")
+      .try_parse(env_args.into_iter())
+      .unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::Help);
+  assert_eq!(err.to_string(), "Usage: test [-bh]\n   -h, --help : Display usage message\n-b, --boolean : A boolean value");
+}
+
+#[test]
+fn should_return_errors_instead_of_panicking() {
+  let def = {
+    let mut def = CommandLineDef::new();
+    def.add_flag(vec!["-b"], "A flag")
+      .add_option(vec!["-n", "--num"], Some("num"), None, "A required numeric value")
+      .add_argument("arg-0");
+    def
+  };
+  let try_parse = |args: &[&str]| {
+    def.try_parse(args.iter().map(|a| a.to_string())).unwrap_err()
+  };
+
+  assert_eq!(try_parse(&["test", "-x"]).kind(), ErrorKind::OptionNotDefined);
+  assert_eq!(try_parse(&["test", "arg", "-n"]).kind(), ErrorKind::MissingValue);
+  assert_eq!(try_parse(&["test", "arg", "-n", "1", "--num", "2"]).kind(), ErrorKind::MultipleOccurrences);
+  assert_eq!(try_parse(&["test", "arg", "-bn"]).kind(), ErrorKind::MissingValue);
+  assert_eq!(try_parse(&["test", "arg"]).kind(), ErrorKind::MissingRequiredOption);
+
+  let err = try_parse(&["test", "-n", "1"]);
+  assert_eq!(err.kind(), ErrorKind::WrongArgumentCount);
+  assert_eq!(err.message(), "Missing required argument <arg-0>");
+  assert_eq!(err.usage(), "Usage: test [-bh] -n <num> <arg-0>\n     -h, --help : Display usage message\n             -b : A flag\n-n, --num <num> : A required numeric value (required)");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_parse_concurrently_with_a_shared_definition() {
+  let mut def = CommandLineDef::new();
+  def.add_option(vec!["-n", "--num"], Some("num"), None, "A required numeric value")
+    .add_argument("arg-0");
+
+  std::thread::scope(|scope| {
+    let handles:Vec<_> = (0..8).map(|i| {
+      let def = &def;
+      scope.spawn(move || {
+        for j in 0..100 {
+          let args = vec!["test".to_string(), "-n".to_string(), (i * j).to_string(), format!("arg-{i}")];
+          let cl = def.parse(args.into_iter());
+          let n:i32 = cl.option("--num");
+          assert_eq!(n, i * j);
+          let arg:String = cl.argument(0);
+          assert_eq!(arg, format!("arg-{i}"));
+        }
+      })
+    }).collect();
+    for handle in handles {
+      handle.join().unwrap();
+    }
+  });
+}
+
+#[cfg(feature = "std")]
+static STATIC_DEF: StaticCommandLineDef = StaticCommandLineDef::new(|def| {
+  def.add_flag(vec!["-b", "--boolean"], "A boolean value")
+    .add_option(vec!["-n", "--num"], Some("num"), Some("0"), "A numeric value");
+});
+
+#[cfg(feature = "std")]
+#[test]
+fn should_reuse_static_definition() {
+  for i in 0..3 {
+    let env_args = vec![String::from("test"), String::from("-n"), i.to_string()];
+    let cl = STATIC_DEF.parse(env_args.into_iter());
+
+    let n:i32 = cl.option("-n");
+    assert_eq!(n, i);
+
+    let b:bool = cl.option("--boolean");
+    assert_eq!(b, false);
+  }
+}
+
+#[test]
+fn should_define_options_from_optstring() {
+  let env_args = vec![
+    String::from("test"),
+    String::from("-xv"),
+    String::from("-f"), String::from("archive.tar"),
+  ];
+  let cl = CommandLineDef::from_optstring("+:hxvf:z::").parse(env_args.into_iter());
+
+  let x:bool = cl.option("-x");
+  assert_eq!(x, true);
+
+  let v:bool = cl.option("-v");
+  assert_eq!(v, true);
+
+  let f:String = cl.option("-f");
+  assert_eq!(f, "archive.tar");
+
+  let z:String = cl.option("-z");
+  assert_eq!(z, "");
+}
+
+#[test]
+fn should_take_only_attached_values_for_optstring_optional_values() {
+  let cl_def = CommandLineDef::from_optstring("f:z::");
+  let parse = |args:&[&str]| cl_def.try_parse(args.iter().map(|arg| arg.to_string()));
+
+  let cl = parse(&["test", "-f", "a.tar", "-z9"]).unwrap();
+  assert_eq!(cl.option::<String>("-z"), "9");
+
+  let err = parse(&["test", "-f", "a.tar", "-z", "9"]).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::WrongArgumentCount);
+
+  let err = parse(&["test", "-z9"]).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::MissingRequiredOption);
+  assert_eq!(err.message(), "Option '-f' is required");
+}
+
+#[test]
+#[should_panic(expected = "Invalid option string 'a:::'. Each ':' must follow an option character")]
+fn should_panic_for_invalid_optstring() {
+  CommandLineDef::from_optstring("a:::");
+}
+
+#[test]
+fn should_define_options_from_getopts_declarations() {
+  let env_args = vec![
+    String::from("test"),
+    String::from("--input"), String::from("in.txt"),
+    String::from("-v"),
+  ];
+  let mut opts = cl_parse::getopts::Options::new();
+  opts.reqopt("i", "input", "The input file", "FILE")
+    .optopt("o", "output", "The output file", "FILE")
+    .optflag("h", "help", "Print this help menu")
+    .optflag("v", "", "Verbose output");
+  let cl_def = CommandLineDef::from(opts);
+  let cl = cl_def.parse(env_args.into_iter());
+
+  let input:String = cl.option("-i");
+  assert_eq!(input, "in.txt");
+
+  let output:String = cl.option("--output");
+  assert_eq!(output, "");
+
+  let v:bool = cl.option("-v");
+  assert_eq!(v, true);
+
+  let env_args = vec![String::from("test"), String::from("--input="), String::from("--output=")];
+  let cl = cl_def.parse(env_args.into_iter());
+  assert_eq!(cl.option::<String>("-i"), "");
+  assert_eq!(cl.option::<String>("-o"), "");
+}
+
+#[test]
+fn should_take_optional_getopts_values_only_when_attached() {
+  let mut opts = cl_parse::getopts::Options::new();
+  opts.optflagopt("c", "color", "When to color the output", "WHEN");
+  let cl_def = CommandLineDef::from(opts);
+  let parse = |args:&[&str]| cl_def.parse(args.iter().map(|arg| arg.to_string()));
+
+  assert_eq!(parse(&["test", "-c"]).option::<String>("--color"), "");
+  assert_eq!(parse(&["test", "-cauto"]).option::<String>("--color"), "auto");
+  assert_eq!(parse(&["test", "--color=never"]).option::<String>("-c"), "never");
+  assert_eq!(parse(&["test"]).option::<String>("-c"), "");
+}
+
+#[test]
+fn should_define_options_from_usage() {
+  let env_args = vec![
+    String::from("test"),
+    String::from("-bf"),
+    String::from("arg1"),
+    String::from("-n"), String::from("-1"),
+    String::from("arg2"),
+  ];
+  let cl = CommandLineDef::from_usage("Usage: test [-bfh] [-c|--count <count>] -n <numeric value> <arg-0> <arg-1>")
+      .parse(env_args.into_iter());
+
+  let b:bool = cl.option("-b");
+  assert_eq!(b, true);
+
+  let f:bool = cl.option("-f");
+  assert_eq!(f, true);
+
+  let count:String = cl.option("--count");
+  assert_eq!(count, "");
+
+  let n:i16 = cl.option("-n");
+  assert_eq!(n, -1);
+
+  let arg0:String = cl.argument(0);
+  assert_eq!(arg0, "arg1");
+
+  let arg1:String = cl.argument(1);
+  assert_eq!(arg1, "arg2");
+}
+
+#[test]
+#[should_panic(expected = "Invalid usage 'Usage: test [-b] file'. Unexpected 'file'")]
+fn should_panic_for_invalid_usage() {
+  CommandLineDef::from_usage("Usage: test [-b] file");
+}
+
+#[test]
+fn should_export_json_schema() {
+  let schema = CommandLineDef::new()
+      .add_flag(vec!["-b", "--boolean"], "A boolean value")
+      .add_option(vec!["-n"], Some("num"), None, "A \"required\" numeric value")
+      .add_option(vec!["--name"], Some("name"), Some("cl_parse"), "A name")
+      .add_argument("src")
+      .add_argument("dest")
+      .to_json_schema();
+
+  assert_eq!(schema, r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "type": "object",
+  "properties": {
+    "options": {
+      "type": "object",
+      "properties": {
+        "boolean": {"type": "boolean", "description": "A boolean value", "default": false},
+        "n": {"type": "string", "description": "A \"required\" numeric value"},
+        "name": {"type": "string", "description": "A name", "default": "cl_parse"}
+      },
+      "required": ["n"],
+      "additionalProperties": false
+    },
+    "arguments": {
+      "type": "array",
+      "prefixItems": [
+        {"title": "src", "type": "string"},
+        {"title": "dest", "type": "string"}
+      ],
+      "items": false,
+      "minItems": 2
+    }
+  },
+  "required": ["options", "arguments"]
+}"#);
+}
+
+#[test]
+fn should_export_json_schema_types_arrays_and_variadic_arguments() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def.add_option_t::<u16>(vec!["-p", "--port"], Some("0x50"), "The port");
+  cl_def.add_option_t::<f32>(vec!["--ratio"], None, "The ratio");
+  let schema = cl_def
+      .add_option(vec!["--point"], Some("coord"), Some("3,4"), "The point")
+      .arity("--point", 2)
+      .add_option(vec!["--tags"], Some("tag"), None, "The tags")
+      .arity_range("--tags", 1..=usize::MAX)
+      .add_variadic_argument("src")
+      .add_argument("dest")
+      .to_json_schema();
+
+  assert!(schema.contains(r#""port": {"type": "integer", "description": "The port", "default": 80}"#), "{schema}");
+  assert!(schema.contains(r#""ratio": {"type": "number", "description": "The ratio"}"#), "{schema}");
+  assert!(schema.contains(r#""point": {"type": "array", "items": {"type": "string"}, "minItems": 2, "maxItems": 2, "description": "The point", "default": ["3", "4"]}"#), "{schema}");
+  assert!(schema.contains(r#""tags": {"type": "array", "items": {"type": "string"}, "minItems": 1, "description": "The tags"}"#), "{schema}");
+  assert!(schema.contains(r#""prefixItems": [
+        {"title": "dest", "type": "string"}
+      ],
+      "items": {"title": "src", "type": "string"},
+      "minItems": 1"#), "{schema}");
+}
+
+#[test]
+fn should_export_fig_spec() {
+  let spec = CommandLineDef::new()
+      .add_flag(vec!["-b", "--boolean"], "A boolean value")
+      .add_option(vec!["-n"], Some("num"), None, "A required numeric value")
+      .add_option(vec!["--name"], Some("name"), Some("cl_parse"), "A name")
+      .add_argument("src")
+      .to_fig_spec("test");
+
+  assert_eq!(spec, r#"const completionSpec: Fig.Spec = {
+  name: "test",
+  options: [
+    { name: ["-h", "--help"], description: "Display usage message" },
+    { name: ["-b", "--boolean"], description: "A boolean value" },
+    { name: ["-n"], description: "A required numeric value", isRequired: true, args: { name: "num" } },
+    { name: ["--name"], description: "A name", args: { name: "name", default: "cl_parse" } },
+  ],
+  args: [
+    { name: "src" },
+  ],
+};
+export default completionSpec;
+"#);
+}
+
+#[test]
+fn should_export_carapace_spec() {
+  let spec = CommandLineDef::new()
+      .add_flag(vec!["-b", "--boolean"], "A boolean value")
+      .add_option(vec!["-n"], Some("num"), None, "A required numeric value")
+      .add_option(vec!["--name"], Some("name"), Some("cl_parse"), "A name")
+      .valid_values("--name", vec!["cl_parse", "clap"])
+      .to_carapace_spec("test");
+
+  assert_eq!(spec, r#"name: "test"
+flags:
+  -h, --help: "Display usage message"
+  -b, --boolean: "A boolean value"
+  -n=!: "A required numeric value"
+  --name=: "A name"
+completion:
+  flag:
+    name: ["cl_parse", "clap"]
+"#);
+}
+
+#[test]
+fn should_assert_with_testing_helpers() {
+  cl_parse::testing::fixed_locale();
+  let mut def = CommandLineDef::new();
+  def.add_flag(vec!["-b", "--boolean"], "A boolean value")
+    .add_option(vec!["-n", "--num"], Some("num"), None, "A required numeric value");
+
+  let cl = cl_parse::parse_ok!(def, "test -b --num 1");
+  let n:i16 = cl.option("-n");
+  assert_eq!(n, 1);
+
+  cl_parse::assert_parse_err!(def, "test -b", ErrorKind::MissingRequiredOption);
+  cl_parse::assert_parse_err!(def, "test -n 1 -b -b", ErrorKind::MultipleOccurrences);
+}
+
+#[test]
+fn should_report_first_defined_missing_option() {
+  let def = {
+    let mut def = CommandLineDef::new();
+    def.add_option(vec!["--zeta"], Some("z"), None, "The first required option")
+      .add_option(vec!["-n", "--num"], Some("num"), None, "The second required option")
+      .add_option(vec!["--alpha"], Some("a"), None, "The third required option");
+    def
+  };
+
+  for _ in 0..10 {
+    let err = def.try_parse(vec![String::from("test")].into_iter()).unwrap_err();
+    assert_eq!(err.message(), "Option '--zeta' is required");
+  }
+  let err = def.try_parse(vec![String::from("test"), String::from("--zeta"), String::from("z")].into_iter()).unwrap_err();
+  assert_eq!(err.message(), "Option '-n' is required");
+}
+
+#[test]
+#[should_panic(expected = "Invalid option name '-é'. Short option names must be an ASCII character. e.g. -f")]
+fn should_panic_for_non_ascii_short_option() {
+  CommandLineDef::new().add_flag(vec!["-é"], "A non-ASCII flag");
+}
+
+#[test]
+fn should_return_error_for_non_ascii_flags() {
+  let env_args = vec![String::from("test"), String::from("-xe\u{301}")];
+  let err = CommandLineDef::new()
+      .add_flag(vec!["-x"], "A flag")
+      .add_flag(vec!["-e"], "Another flag")
+      .try_parse(env_args.into_iter())
+      .unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::NonAsciiFlags);
+  assert_eq!(err.message(), "Flags '-xe\u{301}' contain a non-ASCII character. Only ASCII flags can be concatenated");
+}
+
+#[test]
+fn should_warn_for_deprecated_options() {
+  let warnings = Arc::new(Mutex::new(Vec::new()));
+  let sink = warnings.clone();
+  let env_args = vec![String::from("test"), String::from("-ab"), String::from("--old"), String::from("1")];
+  let cl = CommandLineDef::new()
+      .add_flag(vec!["-a"], "A flag")
+      .add_flag(vec!["-b"], "A deprecated flag")
+      .add_option(vec!["--old"], Some("n"), None, "A deprecated option")
+      .deprecate_option("-b")
+      .deprecate_option("--old")
+      .on_warning(move |warning| sink.lock().unwrap().push((warning.kind(), warning.to_string())))
+      .parse(env_args.into_iter());
+
+  let old:i32 = cl.option("--old");
+  assert_eq!(old, 1);
+  assert_eq!(*warnings.lock().unwrap(), vec![
+    (WarningKind::DeprecatedOption, String::from("Option '-b' is deprecated")),
+    (WarningKind::DeprecatedOption, String::from("Option '--old' is deprecated")),
+  ]);
+}
+
+#[test]
+#[should_panic(expected = "Option '--new' not defined")]
+fn should_panic_deprecating_undefined_option() {
+  CommandLineDef::new().deprecate_option("--new");
+}
+
+#[test]
+fn should_run_actions_when_parsed() {
+  let actions = Arc::new(Mutex::new(Vec::new()));
+  let version_actions = actions.clone();
+  let license_actions = actions.clone();
+  let env_args = vec![String::from("test"), String::from("-vL"), String::from("--version")];
+  let err = CommandLineDef::new()
+      .add_action(vec!["-v","--version"], "Display the version", move |_, program_name| {
+        version_actions.lock().unwrap().push(format!("{program_name} version"));
+      })
+      .add_action(vec!["-L"], "Display the license", move |_, program_name| {
+        license_actions.lock().unwrap().push(format!("{program_name} license"));
+      })
+      .try_parse(env_args.into_iter())
+      .unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::MultipleOccurrences);
+  assert_eq!(*actions.lock().unwrap(), vec!["test version", "test license", "test version"]);
+}
+
+#[test]
+fn should_use_custom_help_aliases() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+      .help_option(vec!["-?"])
+      .add_option(vec!["-h","--host"], Some("host"), None, "The host");
+
+  let env_args = vec![String::from("test"), String::from("-?")];
+  let err = cl_def.try_parse(env_args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::Help);
+  assert_eq!(err.usage(), "Usage: test [-?] -h <host>\n               -? : Display usage message\n-h, --host <host> : The host (required)");
+
+  let env_args = vec![String::from("test"), String::from("--help")];
+  let err = cl_def.try_parse(env_args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::OptionNotDefined);
+}
+
+#[test]
+fn should_treat_help_as_undefined_when_disabled() {
+  let env_args = vec![String::from("test"), String::from("-h")];
+  let err = CommandLineDef::new()
+      .add_flag(vec!["-v"], "A flag")
+      .disable_help()
+      .try_parse(env_args.into_iter())
+      .unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::OptionNotDefined);
+  assert_eq!(err.usage(), "Usage: test [-v]\n-v : A flag");
+}
+
+#[test]
+fn should_return_version_text() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+      .add_option(vec!["-n"], Some("num"), None, "A required number")
+      .version("1.0.0")
+      .version_with(|program_name| format!("{program_name} 2.0.0"));
+
+  let env_args = vec![String::from("test"), String::from("-V")];
+  let err = cl_def.try_parse(env_args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::Version);
+  assert_eq!(err.to_string(), "test 2.0.0");
+  assert_eq!(err.usage(), "Usage: test [-Vh] -n <num>\n   -h, --help : Display usage message\n     -n <num> : A required number (required)\n-V, --version : Display version information");
+}
+
+#[test]
+fn should_count_verbosity() {
+  let env_args = vec![String::from("test"), String::from("-vqv"), String::from("--verbose"), String::from("-vv")];
+  let cl = CommandLineDef::new()
+      .add_verbosity()
+      .parse(env_args.into_iter());
+
+  let verbose:u32 = cl.option("-v");
+  assert_eq!(verbose, 5);
+  let quiet:u32 = cl.option("--quiet");
+  assert_eq!(quiet, 1);
+  assert_eq!(cl.verbosity(), 4);
+}
+
+#[test]
+fn should_default_verbosity_to_zero() {
+  let env_args = vec![String::from("test")];
+  assert_eq!(CommandLineDef::new().parse(env_args.clone().into_iter()).verbosity(), 0);
+  assert_eq!(CommandLineDef::new().add_verbosity().parse(env_args.into_iter()).verbosity(), 0);
+}
+
+#[test]
+fn should_return_error_for_concatenated_flag_repeated_by_alias() {
+  let env_args = vec![String::from("test"), String::from("-ab"), String::from("--bool")];
+  let err = CommandLineDef::new()
+      .add_flag(vec!["-a"], "A flag")
+      .add_flag(vec!["-b", "--bool"], "A bool flag")
+      .try_parse(env_args.into_iter())
+      .unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::MultipleOccurrences);
+}
+
+#[test]
+fn should_return_error_for_invalid_value() {
+  let env_args = vec![String::from("test"), String::from("--size"), String::from("huge")];
+  let err = CommandLineDef::new()
+      .add_option(vec!["-s", "--size"], Some("size"), Some("small"), "The size")
+      .valid_values("-s", vec!["small", "large"])
+      .try_parse(env_args.into_iter())
+      .unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  assert_eq!(err.message(), "Invalid value 'huge' for option '--size'. Valid values are: small, large");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_parse_color_choice() {
+  let env_args = vec![String::from("test"), String::from("--color"), String::from("always")];
+  let cl = CommandLineDef::new()
+      .add_color()
+      .parse(env_args.into_iter());
+
+  assert_eq!(cl.color(), ColorChoice::Always);
+  assert!(cl.color().enabled());
+  assert_eq!(cl.color().to_string(), "always");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_prefer_commandline_over_env_over_default() {
+  std::env::set_var("CL_PARSE_TEST_PORT", "8080");
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+      .add_option(vec!["-p", "--port"], Some("port"), Some("80"), "The port")
+      .env("-p", "CL_PARSE_TEST_PORT")
+      .add_option(vec!["--host"], Some("host"), Some("localhost"), "The host")
+      .env("--host", "CL_PARSE_TEST_UNSET_HOST");
+
+  let cl = cl_def.parse(vec![String::from("test")].into_iter());
+  let port:u16 = cl.option("--port");
+  assert_eq!(port, 8080);
+  let host:String = cl.option("--host");
+  assert_eq!(host, "localhost");
+
+  let cl = cl_def.parse(vec![String::from("test"), String::from("-p"), String::from("443")].into_iter());
+  let port:u16 = cl.option("--port");
+  assert_eq!(port, 443);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_require_env_only_option() {
+  let env_args = vec![String::from("test")];
+  let err = CommandLineDef::new()
+      .add_option(vec!["--token"], Some("token"), None, "The API token")
+      .env_only("--token", "CL_PARSE_TEST_UNSET_TOKEN")
+      .try_parse(env_args.into_iter())
+      .unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::MissingRequiredOption);
+  assert_eq!(err.message(), "Environment variable 'CL_PARSE_TEST_UNSET_TOKEN' is required");
+  assert_eq!(err.usage(), "Usage: test [-h]\n                -h, --help : Display usage message\n$CL_PARSE_TEST_UNSET_TOKEN : The API token");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_parse_env_args_before_commandline_args() {
+  std::env::set_var("CL_PARSE_TEST_FLAGS", "-v 'first arg'");
+  let env_args = vec![String::from("test"), String::from("second")];
+  let cl = CommandLineDef::new()
+      .add_counter(vec!["-v"], "Verbose output")
+      .add_argument("first")
+      .add_argument("second")
+      .args_env_var("CL_PARSE_TEST_FLAGS")
+      .parse(env_args.into_iter());
+
+  let first:String = cl.argument(0);
+  assert_eq!(first, "first arg");
+  let second:String = cl.argument(1);
+  assert_eq!(second, "second");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_return_error_for_unterminated_env_args() {
+  std::env::set_var("CL_PARSE_TEST_BAD_FLAGS", "-v 'first");
+  let env_args = vec![String::from("test")];
+  let err = CommandLineDef::new()
+      .add_flag(vec!["-v"], "Verbose output")
+      .args_env_var("CL_PARSE_TEST_BAD_FLAGS")
+      .try_parse(env_args.into_iter())
+      .unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::InvalidEnvArgs);
+  assert_eq!(err.message(), "Environment variable 'CL_PARSE_TEST_BAD_FLAGS' has an unterminated quote or escape");
+}
+
+#[cfg(feature = "dotenv")]
+#[test]
+fn should_return_error_for_invalid_dotenv() {
+  let path = std::env::temp_dir().join("cl_parse_test_invalid_dotenv");
+  std::fs::write(&path, "NOT A VALID LINE\n").unwrap();
+  let env_args = vec![String::from("test")];
+  let err = CommandLineDef::new()
+      .add_option(vec!["--host"], Some("host"), Some("localhost"), "The host")
+      .env("--host", "CL_PARSE_TEST_DOTENV_HOST")
+      .dotenv(&path)
+      .try_parse(env_args.into_iter())
+      .unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::InvalidDotenv);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_write_default_toml_config() {
+  let mut config = Vec::new();
+  CommandLineDef::new()
+      .version("1.0")
+      .add_verbosity()
+      .add_option(vec!["-n", "--name"], Some("name"), Some("say \"hi\""), "The name\nto greet")
+      .add_option(vec!["--token"], Some("token"), None, "The API token")
+      .env_only("--token", "CL_PARSE_TEST_TOKEN")
+      .write_default_config(&mut config, Format::Toml)
+      .unwrap();
+
+  assert_eq!(String::from_utf8(config).unwrap(), "\
+# Increase verbosity, may be repeated
+verbose = 0
+
+# Decrease verbosity, may be repeated
+quiet = 0
+
+# The name
+# to greet
+name = \"say \\\"hi\\\"\"
+");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_use_custom_precedence() {
+  std::env::set_var("CL_PARSE_TEST_PRECEDENCE_LEVEL", "env");
+  let env_args = vec![String::from("test"), String::from("--level"), String::from("cli"), String::from("--mode"), String::from("cli")];
+  let cl = CommandLineDef::new()
+      .add_option(vec!["--level"], Some("level"), Some("default"), "The level")
+      .env("--level", "CL_PARSE_TEST_PRECEDENCE_LEVEL")
+      .add_option(vec!["--mode"], Some("mode"), Some("default"), "The mode")
+      .exclude_source("--mode", Source::CommandLine)
+      .config([("level", "config"), ("mode", "config")])
+      .precedence(vec![Source::Env, Source::CommandLine, Source::Config, Source::Default])
+      .parse(env_args.into_iter());
+
+  let level:String = cl.option("--level");
+  assert_eq!(level, "env");
+  let mode:String = cl.option("--mode");
+  assert_eq!(mode, "config");
+}
+
+#[test]
+fn should_return_error_for_invalid_config_value() {
+  let env_args = vec![String::from("test")];
+  let err = CommandLineDef::new()
+      .add_color()
+      .config([("color", "sometimes")])
+      .try_parse(env_args.into_iter())
+      .unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  assert_eq!(err.message(), "Invalid value 'sometimes' for option 'color'. Valid values are: auto, always, never");
+}
+
+#[test]
+fn should_return_empty_section() {
+  let env_args = vec![String::from("test"), String::from("--db.host"), String::from("example.com")];
+  let cl = CommandLineDef::new()
+      .add_option(vec!["--db.host"], Some("host"), None, "The database host")
+      .parse(env_args.into_iter());
+
+  assert_eq!(cl.section("cache").options(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Invalid option name '--db..host'. Sections in long option names must not be empty. e.g. --db.host")]
+fn should_panic_for_empty_section() {
+  CommandLineDef::new().add_flag(vec!["--db..host"], "An empty section");
+}
+
+fn git_def() -> CommandLineDef {
+  let mut remove = CommandLineDef::new();
+  remove.add_flag(vec!["-f", "--force"], "Ignore missing files").add_argument("file");
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+      .add_flag(vec!["-v"], "Verbose output")
+      .add_subcommand(vec!["remove", "rm"], "Remove files", remove)
+      .add_subcommand(vec!["status"], "Show the status", CommandLineDef::new());
+  cl_def
+}
+
+#[test]
+fn should_list_subcommands_in_usage() {
+  let env_args = vec![String::from("git"), String::from("-h")];
+  let err = git_def().try_parse(env_args.into_iter()).unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::Help);
+  assert_eq!(err.usage(), "Usage: git [-hv] <command>\n-h, --help : Display usage message\n        -v : Verbose output\nCommands:\n    remove : Remove files\n    status : Show the status");
+}
+
+#[test]
+fn should_scope_help_to_subcommand() {
+  let mut remove = CommandLineDef::new();
+  remove
+      .add_flag(vec!["-f", "--force"], "Ignore missing files")
+      .add_argument("file")
+      .add_example("-f notes.txt", "Remove notes.txt even if it is missing");
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+      .add_flag(vec!["-v"], "Verbose output")
+      .add_subcommand(vec!["remove", "rm"], "Remove files", remove);
+
+  let env_args = vec![String::from("git"), String::from("rm"), String::from("-h")];
+  let err = cl_def.try_parse(env_args.into_iter()).unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::Help);
+  assert_eq!(err.usage(), "Usage: git remove [-fh] <file>\n -h, --help : Display usage message\n-f, --force : Ignore missing files\nExamples:\n  git remove -f notes.txt\n    Remove notes.txt even if it is missing");
+}
+
+#[test]
+fn should_parse_parent_options_before_subcommand() {
+  let env_args = vec![String::from("git"), String::from("-v"), String::from("status")];
+  let cl = git_def().parse(env_args.into_iter());
+
+  let verbose:bool = cl.option("-v");
+  assert_eq!(verbose, true);
+  assert_eq!(cl.subcommand().unwrap().0, "status");
+}
+
+#[test]
+fn should_return_subcommand_errors_with_subcommand_usage() {
+  let env_args = vec![String::from("git"), String::from("rm"), String::from("-v")];
+  let err = git_def().try_parse(env_args.into_iter()).unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::OptionNotDefined);
+  assert!(err.usage().starts_with("Usage: git remove [-fh] <file>"));
+}
+
+#[test]
+fn should_return_error_for_undefined_subcommand() {
+  let env_args = vec![String::from("git"), String::from("push")];
+  let err = git_def().try_parse(env_args.into_iter()).unwrap_err();
+
+  assert_eq!(err.kind(), ErrorKind::SubcommandNotDefined);
+  assert_eq!(err.message(), "Command 'push' not defined");
+}
+
+#[test]
+#[should_panic(expected = "Command 'rm' is already defined")]
+fn should_panic_for_redefined_subcommand() {
+  git_def().add_subcommand(vec!["rm"], "Remove", CommandLineDef::new());
+}
+
+#[test]
+#[should_panic(expected = "Option '-o' cannot be redefined")]
+fn should_panic_including_redefined_option() {
+  let mut output_options = CommandLineDef::new();
+  output_options.add_option(vec!["-o"], Some("dir"), None, "The output directory");
+  CommandLineDef::new()
+      .add_flag(vec!["-o"], "Optimize")
+      .include(&output_options);
+}
+
+#[test]
+fn should_walk_command_tree() {
+  let cl_def = git_def();
+  let tree = cl_def.command_tree("git");
+  let paths:Vec<&str> = tree.iter().map(|(path, _)| path.as_str()).collect();
+  assert_eq!(paths, vec!["git", "git remove", "git status"]);
+
+  let (_, remove) = tree[1];
+  let force = remove.options().find(|option| option.aliases().contains(&"--force")).unwrap();
+  assert_eq!(force.description(), "Ignore missing files");
+  assert_eq!(force.value_name(), None);
+  assert_eq!(remove.argument_names(), ["file"]);
+
+  let summaries:Vec<&str> = cl_def.subcommands().map(|subcommand| subcommand.summary()).collect();
+  assert_eq!(summaries, vec!["Remove files", "Show the status"]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_run_subcommands_in_repl() {
+  let cl_def = git_def();
+  let input = "rm -f 'my notes.txt'\nrm\nstatus\n".as_bytes();
+  let mut output:Vec<u8> = Vec::new();
+  let mut results:Vec<String> = Vec::new();
+  cl_def.repl("git", input, &mut output, |result| {
+    match result {
+      Ok(cl) => {
+        let (name, sub) = cl.subcommand().unwrap();
+        results.push(format!("{name} {}", sub.arguments()));
+      },
+      Err(e) => results.push(format!("{:?}", e.kind())),
+    }
+    true
+  }).unwrap();
+
+  assert_eq!(results, vec!["remove 1", "WrongArgumentCount", "status 0"]);
+}
+
+#[test]
+fn should_dispatch_on_program_stem() {
+  let mut gzip = CommandLineDef::new();
+  gzip.add_flag(vec!["-k", "--keep"], "Keep the input file").add_argument("file");
+  let mut gunzip = CommandLineDef::new();
+  gunzip.add_flag(vec!["-t", "--test"], "Test the compressed file").add_argument("file");
+  let mut multi_call = MultiCall::new();
+  multi_call.add_program("gzip", gzip).add_program("gunzip", gunzip);
+
+  let env_args = vec![String::from("C:\\bin\\gzip.exe"), String::from("-k"), String::from("a.txt")];
+  let (program, cl) = multi_call.try_parse(env_args.into_iter()).unwrap();
+  assert_eq!(program, "gzip");
+  assert!(cl.option::<bool>("--keep"));
+
+  let env_args = vec![String::from("gunzip"), String::from("-k"), String::from("a.gz")];
+  let err = multi_call.try_parse(env_args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::OptionNotDefined);
+  assert!(err.usage().starts_with("Usage: gunzip "));
+
+  let env_args = vec![String::from("busybox")];
+  let err = multi_call.try_parse(env_args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::ProgramNotDefined);
+  assert_eq!(err.message(), "Program 'busybox' not defined");
+}
+
+#[test]
+fn should_keep_program_path_for_subcommands() {
+  let env_args = vec![String::from("./target/git.exe"), String::from("rm"), String::from("a.txt")];
+  let cl = git_def().parse(env_args.into_iter());
+  let (_, remove) = cl.subcommand().unwrap();
+
+  assert_eq!(remove.program_name(), "./target/git.exe remove");
+  assert_eq!(remove.program_path(), "./target/git.exe");
+  assert_eq!(remove.program_stem(), "git");
+  assert_eq!(cl.program_stem(), "git");
+}
+
+#[test]
+fn should_only_treat_slash_question_as_help_when_enabled() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def.add_argument("pattern").slash_help(false);
+  let env_args = vec![String::from("program"), String::from("/?")];
+  let cl = cl_def.parse(env_args.clone().into_iter());
+  let pattern:String = cl.argument(0);
+  assert_eq!(pattern, "/?");
+
+  cl_def.slash_help(true);
+  let err = cl_def.try_parse(env_args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::Help);
+  assert_eq!(err.usage(), "Usage: program [-h] <pattern>\n-h, --help : Display usage message");
+}
+
+#[cfg(feature = "glob")]
+#[test]
+fn should_expand_wildcards_in_arguments_only() {
+  let env_args = vec![String::from("program"), String::from("-p"), String::from("src/*.rs"), String::from("src/text/*.rs")];
+  let cl = CommandLineDef::new()
+      .add_option(vec!["-p", "--pattern"], Some("pattern"), None, "The pattern")
+      .add_argument("file")
+      .expand_wildcards(true)
+      .parse(env_args.into_iter());
+
+  let pattern:String = cl.option("--pattern");
+  assert_eq!(pattern, "src/*.rs");
+  let file:String = cl.argument(0);
+  assert_eq!(file, "src/text/en_us.rs");
+}
+
+#[test]
+fn should_parse_attached_values() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+      .add_option(vec!["-f", "--file"], Some("path"), None, "The file to read")
+      .add_flag(vec!["-v", "--verbose"], "Verbose output");
+
+  let env_args = vec![String::from("program"), String::from("--file=a=b.txt")];
+  let cl = cl_def.parse(env_args.into_iter());
+  let file:String = cl.option("-f");
+  assert_eq!(file, "a=b.txt");
+
+  let env_args = vec![String::from("program"), String::from("--file=a"), String::from("--verbose=true")];
+  let err = cl_def.try_parse(env_args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  assert_eq!(err.message(), "Option '--verbose' does not take a value");
+
+  let env_args = vec![String::from("program"), String::from("--file:a")];
+  assert_eq!(cl_def.try_parse(env_args.clone().into_iter()).unwrap_err().kind(), ErrorKind::OptionNotDefined);
+  let cl = cl_def.colon_separator(true).parse(env_args.into_iter());
+  let file:String = cl.option("--file");
+  assert_eq!(file, "a");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_read_newline_delimited_args() {
+  let input = "a.txt\r\n\nb.txt\nc.txt".as_bytes();
+  let args = cl_parse::shell::read_args(input, b'\n').unwrap();
+  assert_eq!(args, vec!["a.txt", "b.txt", "c.txt"]);
+
+  let input:&[u8] = b"a.txt\0\xff\0";
+  let err = cl_parse::shell::read_args(input, b'\0').unwrap_err();
+  assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn should_preprocess_args_before_parsing() {
+  let env_args = vec![String::from("wrapper"), String::from("--wrapper-debug"), String::from("-v"), String::from("a.txt")];
+  let cl = CommandLineDef::new()
+      .add_flag(vec!["-v", "--verbose"], "Verbose output")
+      .add_argument("file")
+      .preprocess(|args| args.into_iter().filter(|arg| !arg.starts_with("--wrapper-")).collect())
+      .parse(env_args.into_iter());
+
+  assert!(cl.option::<bool>("-v"));
+  assert_eq!(cl.argument::<String>(0), "a.txt");
+}
+
+#[test]
+fn should_store_derived_values_in_finalize() {
+  let env_args = vec![String::from("program"), String::from("--host"), String::from("example.com")];
+  let cl = CommandLineDef::new()
+      .add_option(vec!["--host"], Some("host"), None, "The host")
+      .add_option(vec!["-u", "--url"], Some("url"), Some(""), "The url, derived from the host by default")
+      .finalize(|cl| {
+        if cl.option::<String>("--url").is_empty() {
+          let url = format!("https://{}", cl.option::<String>("--host"));
+          cl.set_option("--url", url);
+        }
+      })
+      .parse(env_args.into_iter());
+
+  let url:String = cl.option("-u");
+  assert_eq!(url, "https://example.com");
+}
+
+#[test]
+fn should_validate_typed_options_from_every_source() {
+  let mut cl_def = CommandLineDef::new();
+  let jobs = cl_def.add_option_t::<u8>(vec!["-j", "--jobs"], Some("1"), "The number of jobs");
+  let path = cl_def.add_option_t::<std::path::PathBuf>(vec!["--out"], Some("target"), "The output directory");
+  cl_def.config([("jobs", "many")]);
+
+  let err = cl_def.try_parse(vec![String::from("program")].into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  assert_eq!(err.message(), "Cannot convert option 'jobs' from 'many': invalid digit found in string");
+  assert!(err.usage().contains("[-j <u8>]") && err.usage().contains("[--out <PathBuf>]"));
+
+  cl_def.config([("jobs", "4")]);
+  let cl = cl_def.parse(vec![String::from("program")].into_iter());
+  assert_eq!(cl.get(jobs), 4);
+  assert_eq!(cl.get(path), std::path::PathBuf::from("target"));
+}
+
+#[test]
+#[should_panic(expected = "Cannot convert option '-j' from 'all': invalid digit found in string")]
+fn should_panic_when_typed_default_cannot_be_converted() {
+  CommandLineDef::new().add_option_t::<u8>(vec!["-j", "--jobs"], Some("all"), "The number of jobs");
+}
+
+#[test]
+fn should_accept_lenient_boolean_spellings() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+      .add_flag(vec!["-v", "--verbose"], "Verbose output")
+      .add_option(vec!["--color"], Some("bool"), Some("OFF"), "Colored output")
+      .add_argument("enabled")
+      .config([("verbose", "Yes")]);
+  let cl = cl_def.parse(vec![String::from("program"), String::from("1")].into_iter());
+
+  assert!(cl.option::<bool>("--verbose"));
+  assert!(!cl.option::<bool>("--color"));
+  assert!(cl.argument::<bool>(0));
+  assert_eq!(cl.argument::<u8>(0), 1);
+}
+
+#[cfg(target_os = "linux")]
+#[cfg(feature = "std")]
+#[test]
+fn should_expand_tilde_for_users() {
+  let env_args = vec![String::from("program"), String::from("--dir"), String::from("~root/.config")];
+  let cl = CommandLineDef::new()
+      .add_option(vec!["--dir"], Some("dir"), None, "The directory")
+      .add_option(vec!["--file"], Some("file"), Some("a~b"), "The file")
+      .add_option(vec!["--log"], Some("log"), Some("~cl_parse_no_such_user/log.txt"), "The log file")
+      .parse(env_args.into_iter());
+
+  assert_eq!(cl.path("--dir"), std::path::PathBuf::from("/root/.config"));
+  assert_eq!(cl.path("--file"), std::path::PathBuf::from("a~b"));
+  assert_eq!(cl.path("--log"), std::path::PathBuf::from("~cl_parse_no_such_user/log.txt"));
+}
+
+#[test]
+#[should_panic(expected = "Cannot convert argument '0' from 'x1': invalid digit found in string")]
+fn should_include_conversion_error_in_panic() {
+  let env_args = vec![String::from("program"), String::from("x1")];
+  let cl = CommandLineDef::new().add_argument("count").parse(env_args.into_iter());
+  let _count:u32 = cl.argument(0);
+}
+
+#[test]
+#[should_panic(expected = "Cannot convert option '--level' from 'loud': expected quiet or normal")]
+fn should_include_value_parser_error_in_panic() {
+  let env_args = vec![String::from("program"), String::from("--level"), String::from("loud")];
+  let cl = CommandLineDef::new()
+      .add_option(vec!["--level"], Some("level"), None, "The level")
+      .parse(env_args.into_iter());
+  cl.option_with("--level", |value:&str| match value {
+    "quiet" => Ok(0),
+    "normal" => Ok(1),
+    _ => Err("expected quiet or normal"),
+  });
+}
+
+#[test]
+#[should_panic(expected = "Cannot convert element 3 of option '--ports' from 'x': invalid digit found in string")]
+fn should_name_the_element_that_cannot_be_converted() {
+  let env_args = vec![String::from("program"), String::from("--ports=80,443,x")];
+  let cl = CommandLineDef::new()
+      .add_option(vec!["--ports"], Some("ports"), None, "The ports")
+      .parse(env_args.into_iter());
+  let _ports:Vec<u16> = cl.values("--ports");
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[should_panic(expected = "Key 'cpu' is repeated in option '--limits'")]
+fn should_reject_repeated_keys() {
+  let env_args = vec![String::from("program"), String::from("--limits=cpu=1,cpu=2")];
+  let cl = CommandLineDef::new()
+      .add_option(vec!["--limits"], Some("limits"), None, "The limits")
+      .parse(env_args.into_iter());
+  let _limits:std::collections::HashMap<String, u8> = cl.map("--limits");
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[should_panic(expected = "Invalid entry 'cpu' for option '--limits'. Entries are written key=value")]
+fn should_reject_entries_without_values() {
+  let env_args = vec![String::from("program"), String::from("--limits=cpu")];
+  let cl = CommandLineDef::new()
+      .add_option(vec!["--limits"], Some("limits"), None, "The limits")
+      .parse(env_args.into_iter());
+  let _limits:std::collections::HashMap<String, u8> = cl.map("--limits");
+}
+
+#[cfg(feature = "semver")]
+#[test]
+fn should_reject_invalid_semver_ranges() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def.add_semver_req_option(vec!["--requires"], None, "The versions to accept");
+
+  let env_args = vec![String::from("program"), String::from("--requires"), String::from(">=x")];
+  let err = cl_def.try_parse(env_args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  assert!(err.message().starts_with("Cannot convert option '--requires' from '>=x': "));
+  assert!(err.usage().contains("--requires <range>"));
+}
+
+#[cfg(feature = "clap")]
+#[test]
+fn should_skip_unsupported_clap_actions() {
+  use clap::{Arg, ArgAction, Command};
+  let command = Command::new("program")
+    .arg(Arg::new("cache").long("no-cache").action(ArgAction::SetFalse))
+    .arg(Arg::new("include").short('I').action(ArgAction::Append))
+    .arg(Arg::new("verbose").short('v').action(ArgAction::SetTrue));
+  let cl_def = CommandLineDef::from_clap_command(&command);
+
+  for arg in ["--no-cache", "-I"] {
+    let err = cl_def.try_parse([String::from("program"), String::from(arg)].into_iter()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::OptionNotDefined);
+  }
+  let cl = cl_def.parse([String::from("program"), String::from("-v")].into_iter());
+  assert_eq!(cl.option::<bool>("-v"), true);
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+#[should_panic(expected = "Cannot convert option '--id' from 'none': ")]
+fn should_panic_when_uuid_default_is_invalid() {
+  CommandLineDef::new().add_uuid_option(vec!["--id"], Some("none"), "The ID");
+}
+
+#[cfg(feature = "glob")]
+#[test]
+fn should_compile_glob_options_from_config() {
+  let mut cl_def = CommandLineDef::new();
+  let exclude = cl_def.add_glob_option(vec!["--exclude"], Some("target/*"), "The files to exclude");
+  cl_def.config([("exclude", "*.tmp")]);
+
+  let cl = cl_def.parse(vec![String::from("program")].into_iter());
+  assert!(cl.get(exclude).matches("notes.tmp"));
+  assert!(!cl.get(exclude).matches("target/debug"));
+}
+
+#[test]
+fn should_convert_radix_literals() {
+  let env_args = vec![String::from("program"), String::from("--mask"), String::from("0xFF"), String::from("0o755"),
+                      String::from("--offset"), String::from("-0b1_01")];
+  let mut cl_def = CommandLineDef::new();
+  let mask = cl_def.add_option_t::<u8>(vec!["--mask"], None, "The mask");
+  cl_def.add_option(vec!["--offset"], Some("offset"), Some("0"), "The offset").add_argument("mode");
+  let cl = cl_def.parse(env_args.into_iter());
+
+  assert_eq!(cl.get(mask), 255);
+  assert_eq!(cl.argument::<u32>(0), 493);
+  assert_eq!(cl.option::<i8>("--offset"), -5);
+
+  let env_args = vec![String::from("program"), String::from("--mask"), String::from("0x100"), String::from("0")];
+  let err = cl_def.try_parse(env_args.into_iter()).unwrap_err();
+  assert_eq!(err.message(), "Cannot convert option '--mask' from '0x100': invalid digit found in string");
+}
+
+#[test]
+fn should_prefer_defined_flags_over_negative_numbers() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def.add_flag(vec!["-1"], "Single column").add_argument("offset");
+
+  let env_args = vec![String::from("program"), String::from("-12"), String::from("-2.5e1")];
+  let err = cl_def.try_parse(env_args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::OptionNotDefined);
+
+  let env_args = vec![String::from("program"), String::from("-1"), String::from("-2.5e1")];
+  let cl = cl_def.parse(env_args.into_iter());
+  assert!(cl.option::<bool>("-1"));
+  assert_eq!(cl.argument::<f64>(0), -25.0);
+}
+
+#[test]
+fn should_take_fixed_number_of_values() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_option(vec!["-p","--point"], Some("coord"), None, "The point to draw")
+    .arity("--point", 2)
+    .add_argument("file");
+
+  let env_args = vec![String::from("program"), String::from("--point=3"), String::from("-4"), String::from("out.png")];
+  let cl = cl_def.parse(env_args.into_iter());
+  assert_eq!(cl.values::<i32>("-p"), vec![3, -4]);
+  assert_eq!(cl.tuple::<(i32, i32)>("--point"), (3, -4));
+  assert_eq!(cl.argument::<String>(0), "out.png");
+  assert_eq!(cl_def.usage("program"), "Usage: program [-h] -p <coord> <coord> <file>\n                 \
+    -h, --help : Display usage message\n\
+    -p, --point <coord> <coord> : The point to draw (required)");
+
+  let env_args = vec![String::from("program"), String::from("out.png"), String::from("-p"), String::from("3")];
+  let err = cl_def.try_parse(env_args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::MissingValue);
+  assert_eq!(err.message(), "Option '-p' requires 2 values, found 1");
+}
+
+#[test]
+fn should_keep_commas_in_values_of_options_with_arity() {
+  let env_args = vec![String::from("program"), String::from("--pair"), String::from("a,b"), String::from("c")];
+  let cl = CommandLineDef::new()
+    .add_option(vec!["--pair"], Some("value"), None, "The pair")
+    .arity("--pair", 2)
+    .parse(env_args.into_iter());
+
+  assert_eq!(cl.tuple::<(String, String)>("--pair"), (String::from("a,b"), String::from("c")));
+  assert_eq!(cl.values::<String>("--pair"), vec!["a,b", "c"]);
+}
+
+#[test]
+#[should_panic(expected = "Option '--point' has 2 values, expected 3")]
+fn should_panic_when_tuple_has_wrong_length() {
+  let env_args = vec![String::from("program"), String::from("--point"), String::from("3"), String::from("4")];
+  let cl = CommandLineDef::new()
+    .add_option(vec!["--point"], Some("coord"), None, "The point to draw")
+    .arity("--point", 2)
+    .parse(env_args.into_iter());
+  let _:(i32, i32, i32) = cl.tuple("--point");
+}
+
+#[test]
+#[should_panic(expected = "Invalid arity 2 for option '-v'")]
+fn should_panic_when_flag_has_arity() {
+  CommandLineDef::new().add_flag(vec!["-v"], "Verbose").arity("-v", 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_take_range_of_values() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_flag(vec!["-v","--verbose"], "Verbose output")
+    .add_option(vec!["--files"], Some("file"), Some(""), "The files to read")
+    .arity_range("--files", 0..=2)
+    .env("--files", "IT_RANGE_FILES")
+    .add_argument("dest");
+
+  let env_args = vec![String::from("program"), String::from("--files"), String::from("--verbose"), String::from("out")];
+  let cl = cl_def.parse(env_args.into_iter());
+  assert_eq!(cl.values::<String>("--files"), Vec::<String>::new());
+  assert!(cl.option::<bool>("-v"));
+
+  let env_args = vec![String::from("program"), String::from("--files"), String::from("a"), String::from("b"), String::from("out")];
+  let cl = cl_def.parse(env_args.into_iter());
+  assert_eq!(cl.values::<String>("--files"), vec!["a", "b"]);
+  assert_eq!(cl.argument::<String>(0), "out");
+  assert!(cl_def.usage("program").contains("[--files [<file>...]]"));
+
+  std::env::set_var("IT_RANGE_FILES", "a,b,c");
+  let err = cl_def.try_parse(vec![String::from("program"), String::from("out")].into_iter()).unwrap_err();
+  std::env::remove_var("IT_RANGE_FILES");
+  assert_eq!(err.kind(), ErrorKind::InvalidValue);
+  assert_eq!(err.message(), "Option 'IT_RANGE_FILES' requires 0 to 2 values, found 3");
+}
+
+#[test]
+#[should_panic(expected = "Invalid arity 3..=1 for option '--files'")]
+fn should_panic_when_arity_range_is_empty() {
+  #[allow(clippy::reversed_empty_ranges)]
+  CommandLineDef::new().add_option(vec!["--files"], Some("file"), None, "The files").arity_range("--files", 3..=1);
+}
+
+#[test]
+fn should_stop_taking_values_at_the_options_terminator() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_option(vec!["--files"], Some("file"), None, "The files to read")
+    .arity_range("--files", 1..=5)
+    .add_option(vec!["--exclude"], Some("pattern"), Some("*.o"), "The patterns to exclude")
+    .arity_range("--exclude", 1..=4)
+    .lazy_values("--exclude")
+    .add_variadic_argument("args");
+  let args = |args:&[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>().into_iter();
+
+  let cl = cl_def.parse(args(&["program", "--files", "a", "b", "--", "c", "-d"]));
+  assert_eq!(cl.values::<String>("--files"), vec!["a", "b"]);
+  assert_eq!(cl.rest(), ["c", "-d"]);
+
+  let err = cl_def.try_parse(args(&["program", "--files", "a", "--exclude", "--", "c"])).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::MissingValue);
+}
+
+#[test]
+fn should_take_minimum_values_when_lazy() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_option(vec!["--exclude"], Some("pattern"), None, "The patterns to exclude")
+    .arity_range("--exclude", 1..=4)
+    .lazy_values("--exclude")
+    .add_argument("src")
+    .add_argument("dest");
+
+  let env_args = vec![String::from("program"), String::from("--exclude"), String::from("-x"), String::from("a"), String::from("b")];
+  let cl = cl_def.parse(env_args.into_iter());
+  assert_eq!(cl.values::<String>("--exclude"), vec!["-x"]);
+  assert_eq!((cl.argument::<String>(0), cl.argument::<String>(1)), (String::from("a"), String::from("b")));
+}
+
+#[test]
+fn should_assign_args_around_variadic_argument() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_flag(vec!["-r","--recursive"], "Copy directories recursively")
+    .add_argument("mode")
+    .add_variadic_argument("src")
+    .add_argument("dest");
+  assert_eq!(cl_def.usage("cp"), "Usage: cp [-hr] <mode> [<src>...] <dest>\n     \
+    -h, --help : Display usage message\n\
+    -r, --recursive : Copy directories recursively");
+
+  let env_args = vec![String::from("cp"), String::from("644"), String::from("a"), String::from("-r"), String::from("b"), String::from("out")];
+  let cl = cl_def.parse(env_args.into_iter());
+  assert_eq!(cl.argument::<String>(0), "644");
+  assert_eq!(cl.variadic::<String>(), vec!["a", "b"]);
+  assert_eq!(cl.argument::<String>(3), "out");
+
+  let cl = cl_def.parse(vec![String::from("cp"), String::from("644"), String::from("out")].into_iter());
+  assert_eq!(cl.variadic::<String>(), Vec::<String>::new());
+  assert_eq!(cl.argument::<String>(1), "out");
+
+  let err = cl_def.try_parse(vec![String::from("cp"), String::from("644")].into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::WrongArgumentCount);
+  assert_eq!(err.message(), "Missing required argument <dest>");
+}
+
+#[test]
+#[should_panic(expected = "A definition with a variadic argument cannot have subcommands")]
+fn should_panic_when_variadic_argument_has_subcommands() {
+  CommandLineDef::new()
+    .add_variadic_argument("files")
+    .add_subcommand(vec!["add"], "Add files", CommandLineDef::new());
+}
+
+#[test]
+fn should_take_input_from_option_or_argument() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_argument("mode")
+    .add_option_or_argument(vec!["-i","--input"], "input", "The input file")
+    .add_variadic_argument("dest");
+
+  let env_args = vec![String::from("program"), String::from("fast"), String::from("in.txt"), String::from("a"), String::from("b")];
+  let cl = cl_def.parse(env_args.into_iter());
+  assert_eq!(cl.option::<String>("-i"), "in.txt");
+  assert_eq!(cl.argument::<String>(0), "fast");
+  assert_eq!(cl.variadic::<String>(), vec!["a", "b"]);
+
+  let env_args = vec![String::from("program"), String::from("fast"), String::from("a"), String::from("--input"), String::from("in.txt")];
+  let cl = cl_def.parse(env_args.into_iter());
+  assert_eq!(cl.option::<String>("--input"), "in.txt");
+  assert_eq!(cl.variadic::<String>(), vec!["a"]);
+
+  let err = cl_def.try_parse(vec![String::from("program")].into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::WrongArgumentCount);
+  assert_eq!(err.message(), "Missing required arguments <mode> <input>");
+}
+
+#[test]
+fn should_end_options_at_double_dash() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def.add_flag(vec!["-f","--force"], "Ignore missing files").add_argument("file");
+
+  let env_args = vec![String::from("rm"), String::from("--"), String::from("-f")];
+  let cl = cl_def.parse(env_args.into_iter());
+  assert!(!cl.option::<bool>("--force"));
+  assert_eq!(cl.argument::<String>(0), "-f");
+  assert_eq!(cl.rest(), ["-f"]);
+
+  let env_args = vec![String::from("rm"), String::from("-f"), String::from("notes.txt")];
+  let cl = cl_def.parse(env_args.into_iter());
+  assert!(cl.rest().is_empty());
+
+  let env_args = vec![String::from("rm"), String::from("a"), String::from("--"), String::from("-h")];
+  let err = cl_def.try_parse(env_args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::WrongArgumentCount);
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[cfg(target_os = "linux")]
+fn should_run_rest_as_command() {
+  let env_args = vec![String::from("wrapper"), String::from("--"), String::from("echo"), String::from("a  b"), String::from("$HOME")];
+  let cl = CommandLineDef::new().add_variadic_argument("command").parse(env_args.into_iter());
+
+  let output = cl.rest_command().unwrap().output().unwrap();
+  assert_eq!(String::from_utf8(output.stdout).unwrap(), "a  b $HOME\n");
+
+  let cl = CommandLineDef::new().add_variadic_argument("command").parse(vec![String::from("wrapper")].into_iter());
+  assert!(cl.rest_command().is_none());
+}
+
+#[test]
+fn should_annotate_defaults_and_required_options_in_help() {
+  let usage = CommandLineDef::new()
+    .disable_help()
+    .add_option(vec!["-r","--retries"], Some("count"), Some("10"), "The number of retries")
+    .add_option(vec!["-t","--tag"], Some("tag"), Some(""), "The tag")
+    .add_option(vec!["-u","--url"], Some("url"), None, "The url")
+    .usage("program");
+
+  assert_eq!(usage, "Usage: program [-r <count>] [-t <tag>] -u <url>\n\
+    -r, --retries <count> : The number of retries [default: 10]\n      \
+    -t, --tag <tag> : The tag\n      \
+    -u, --url <url> : The url (required)");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_show_env_vars_in_help() {
+  let usage = CommandLineDef::new()
+    .disable_help()
+    .add_option(vec!["--token"], Some("token"), None, "The API token")
+    .env("--token", "MYAPP_TOKEN")
+    .add_option(vec!["--host"], Some("host"), Some("localhost"), "The host")
+    .env("--host", "MYAPP_HOST")
+    .usage("program");
+
+  assert!(usage.contains("--token <token> : The API token [env: MYAPP_TOKEN] (required)"));
+  assert!(usage.contains("--host <host> : The host [env: MYAPP_HOST] [default: localhost]"));
+}
+
+#[test]
+fn should_lay_out_help_with_template() {
+  let mut cl_def = git_def();
+  cl_def
+    .add_example("status", "Show the status")
+    .help_template("{usage}\n{commands}\n\n{examples}\n\nMore at https://example.com");
+
+  let err = cl_def.try_parse(vec![String::from("git"), String::from("-h")].into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::Help);
+  assert!(err.usage().starts_with("Usage: git "));
+  assert!(err.usage().contains("\nCommands:\n"));
+  assert!(err.usage().ends_with("\n\nExamples:\n  git status\n    Show the status\n\nMore at https://example.com"));
+  assert!(!err.usage().contains("Display usage message"));
+}
+
+#[test]
+fn should_lay_out_help_lines() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_option(vec!["-l","--level"], Some("level"), Some("low"), "The level")
+    .valid_values("--level", vec!["low","high"])
+    .add_subcommand(vec!["run"], "Run the program", CommandLineDef::new())
+    .help_layout(HelpLayout::new().indent(1).separator(" - ").left_align(true).valid_values(ValidValuesPosition::BeforeDescription));
+
+  assert_eq!(cl_def.usage("program"), "Usage: program [-h] [-l <level>] <command>\n \
+    -h, --help          - Display usage message\n \
+    -l, --level <level> - [values: low, high] The level [default: low]\n\
+    Commands:\n \
+    run                 - Run the program");
+}
+
+#[test]
+fn should_display_concise_errors() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def.concise_errors(true).help_option(vec!["-?"]).add_argument("file");
+
+  let err = cl_def.try_parse(vec![String::from("program")].into_iter()).unwrap_err();
+  assert_eq!(err.to_string(), "Missing required argument <file>\nTry 'program -?' for more information.");
+  assert!(err.usage().starts_with("Usage: program"));
+
+  cl_def.disable_help();
+  let err = cl_def.try_parse(vec![String::from("program")].into_iter()).unwrap_err();
+  assert_eq!(err.to_string(), "Missing required argument <file>");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_unwind_with_parse_error() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def.add_argument("file").on_error(ErrorBehavior::Unwind);
+
+  let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cl_def.parse(vec![String::from("program")].into_iter()))).unwrap_err();
+  let err = payload.downcast::<ParseError>().unwrap();
+  assert_eq!(err.kind(), ErrorKind::WrongArgumentCount);
+
+  let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+    cl_def.on_error(ErrorBehavior::Panic).parse(vec![String::from("program")].into_iter())
+  })).unwrap_err();
+  assert!(payload.downcast_ref::<String>().unwrap().starts_with("Missing required argument <file>\nUsage: program"));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_call_exit_handler_with_exit_code() {
+  let codes = Arc::new(Mutex::new(Vec::new()));
+  let exited = codes.clone();
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .on_error(ErrorBehavior::Exit)
+    .exit_handler(move |code| exited.lock().unwrap().push(code));
+
+  for arg in ["--help", "--bogus"] {
+    let args = vec![String::from("program"), String::from(arg)];
+    let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cl_def.parse(args.into_iter()))).unwrap_err();
+    assert!(payload.downcast_ref::<ParseError>().is_some());
+  }
+  assert_eq!(*codes.lock().unwrap(), vec![0, 2]);
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+#[cfg(feature = "std")]
+impl std::io::Write for SharedBuffer {
+  fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    self.0.lock().unwrap().write(buf)
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    Ok(())
+  }
+}
+
+#[cfg(feature = "std")]
+impl SharedBuffer {
+  fn text(&self) -> String {
+    String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+  }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_write_help_and_errors_to_injected_writers() {
+  let (out, err) = (SharedBuffer::default(), SharedBuffer::default());
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .on_error(ErrorBehavior::Exit)
+    .exit_handler(|_| {})
+    .with_output(out.clone(), err.clone())
+    .add_flag(vec!["-v"], "Verbose output");
+
+  for arg in ["--help", "--bogus"] {
+    let args = vec![String::from("program"), String::from(arg)];
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cl_def.parse(args.into_iter()))).is_err());
+  }
+  assert_eq!(out.text(), format!("{}\n", cl_def.usage("program")));
+  assert!(err.text().starts_with("Option '--bogus' not defined"), "{}", err.text());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_only_link_the_help_written_to_injected_writers_when_enabled() {
+  std::env::set_var("FORCE_HYPERLINK", "1");
+  let out = SharedBuffer::default();
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .on_error(ErrorBehavior::Exit)
+    .exit_handler(|_| {})
+    .with_output(out.clone(), std::io::sink())
+    .add_flag(vec!["-v"], "Verbose output")
+    .doc_url("-v", "https://example.com/v");
+  let help = |cl_def:&CommandLineDef| {
+    let args = vec![String::from("program"), String::from("--help")];
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cl_def.parse(args.into_iter()))).is_err());
+  };
+
+  help(&cl_def);
+  assert!(out.text().ends_with("-v : Verbose output [docs: https://example.com/v]\n"), "{}", out.text());
+  cl_def.help_layout(HelpLayout::new().hyperlinks(true));
+  help(&cl_def);
+  std::env::remove_var("FORCE_HYPERLINK");
+  assert!(out.text().contains("\x1b]8;;https://example.com/v\x1b\\see docs"), "{}", out.text());
+}
+
+#[test]
+fn should_tell_default_values_from_given_values() {
+  let env_args = vec![String::from("program"), String::from("--threads"), String::from("4")];
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_option(vec!["-t", "--threads"], Some("count"), Some("4"), "The number of threads")
+    .add_option(vec!["-l", "--level"], Some("level"), Some("1"), "The level")
+    .add_option(vec!["-o", "--output"], Some("file"), Some("out.txt"), "The output")
+    .config([("output", "config.txt")])
+    .finalize(|cl| {
+      if cl.is_default("--level") {
+        cl.set_option("--level", 2)
+      }
+    });
+  let cl = cl_def.parse(env_args.into_iter());
+
+  assert!(!cl.is_default("-t") && !cl.is_default("--threads"));
+  assert!(!cl.is_default("-o"));
+  assert!(!cl.is_default("-l") && !cl.is_default("--level"));
+  assert!(cl.is_default("-h"));
+  let level: u8 = cl.option("-l");
+  assert_eq!(level, 2);
+}
+
+#[test]
+fn should_return_the_positions_of_option_occurrences() {
+  let args: Vec<String> = ["program", "-i", "--depth=2", "file", "-vi", "--", "-i"].iter().map(|s| s.to_string()).collect();
+  let cl = CommandLineDef::new()
+    .add_counter(vec!["-i", "--include"], "Include the next rule")
+    .add_counter(vec!["-v"], "Increase the verbosity")
+    .add_option(vec!["-d", "--depth"], Some("depth"), Some("1"), "The depth")
+    .add_argument("file")
+    .add_argument("rule")
+    .parse(args.into_iter());
+
+  assert_eq!(cl.indices_of("-i"), &[1, 4]);
+  assert_eq!(cl.indices_of("--include"), &[1, 4]);
+  assert_eq!(cl.indices_of("-d"), &[2]);
+  assert_eq!(cl.indices_of("-v"), &[4]);
+  assert!(cl.indices_of("--undefined").is_empty());
+}
+
+#[test]
+fn should_store_empty_values_only_when_allowed() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_option(vec!["--name"], Some("name"), Some("anonymous"), "The name")
+    .add_option(vec!["--suffix"], Some("suffix"), Some(".bak"), "The backup suffix")
+    .allow_empty_values("--suffix")
+    .add_argument("file");
+
+  let args = vec![String::from("program"), String::from("--name"), String::new(), String::from("file")];
+  let cl = cl_def.parse(args.into_iter());
+  assert_eq!(cl.option::<String>("--name"), "");
+  assert_eq!(cl.argument::<String>(0), "file");
+
+  let args = vec![String::from("program"), String::from("--name="), String::from("file")];
+  let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::MissingValue);
+  assert_eq!(err.message(), "Option '--name' does not allow an empty value");
+
+  let args = vec![String::from("program"), String::from("--suffix"), String::new(), String::from("file")];
+  let cl = cl_def.parse(args.into_iter());
+  assert_eq!(cl.option::<String>("--suffix"), "");
+  assert_eq!(cl.argument::<String>(0), "file");
+
+  let args = vec![String::from("program"), String::from("--suffix="), String::from("file")];
+  let cl = cl_def.parse(args.into_iter());
+  assert_eq!(cl.option::<String>("--suffix"), "");
+  assert_eq!(cl.argument::<String>(0), "file");
+}
+
+#[test]
+fn should_set_and_clear_toggles_with_minus_and_plus() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .disable_help()
+    .add_toggle(vec!["-e"], "Exit on the first error")
+    .add_toggle(vec!["-x", "--xtrace"], "Print the commands")
+    .add_toggle(vec!["-o"], "Enable the optimizations")
+    .plus_enables("-o")
+    .add_argument("script");
+
+  let args: Vec<String> = ["program", "-ex", "+o", "+5"].iter().map(|s| s.to_string()).collect();
+  let cl = cl_def.parse(args.into_iter());
+  assert!(cl.option::<bool>("-e") && cl.option::<bool>("--xtrace") && cl.option::<bool>("-o"));
+  assert_eq!(cl.argument::<String>(0), "+5");
+
+  let args: Vec<String> = ["program", "+ex", "-o", "run.sh"].iter().map(|s| s.to_string()).collect();
+  let cl = cl_def.parse(args.into_iter());
+  assert!(!cl.option::<bool>("-e") && !cl.option::<bool>("-x") && !cl.option::<bool>("-o"));
+  assert_eq!(cl.indices_of("+e"), &[] as &[usize]);
+  assert_eq!(cl.indices_of("-e"), &[1]);
+
+  let args: Vec<String> = ["program", "+ey", "run.sh"].iter().map(|s| s.to_string()).collect();
+  let err = cl_def.try_parse(args.into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::OptionNotDefined);
+  assert_eq!(err.message(), "Option '+y' not defined");
+
+  let args: Vec<String> = ["program", "-e", "+e", "run.sh"].iter().map(|s| s.to_string()).collect();
+  assert_eq!(cl_def.try_parse(args.into_iter()).unwrap_err().kind(), ErrorKind::MultipleOccurrences);
+
+  assert_eq!(
+    cl_def.usage("program"),
+    "Usage: program [-eox] <script>\n          -e, +e : Exit on the first error\n-x, --xtrace, +x : Print the commands\n          -o, +o : Enable the optimizations"
+  );
+}
+
+#[test]
+#[should_panic(expected = "Invalid toggle '--xtrace'. Toggles are flags with a short alias. e.g. -x")]
+fn should_panic_for_toggle_without_short_alias() {
+  CommandLineDef::new().add_toggle(vec!["--xtrace"], "Print the commands");
+}
+
+#[test]
+fn should_run_default_subcommand_when_none_is_named() {
+  let mut status = CommandLineDef::new();
+  status.add_flag(vec!["-s", "--short"], "Give the output in the short format");
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_flag(vec!["-q"], "Quiet output")
+    .add_subcommand(vec!["status"], "Show the status", status)
+    .add_subcommand(vec!["commit"], "Record changes", CommandLineDef::new())
+    .default_subcommand("status");
+
+  let cl = cl_def.parse(vec![String::from("git"), String::from("-q")].into_iter());
+  let (name, status) = cl.subcommand().unwrap();
+  assert_eq!(name, "status");
+  assert_eq!(status.program_name(), "git status");
+  assert!(cl.option::<bool>("-q"));
+
+  let cl = cl_def.parse(vec![String::from("git"), String::from("commit")].into_iter());
+  assert_eq!(cl.subcommand().unwrap().0, "commit");
+
+  let err = cl_def.try_parse(vec![String::from("git"), String::from("--help")].into_iter()).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::Help);
+  assert!(err.message().ends_with("Commands:\n    status : Show the status (default)\n    commit : Record changes"), "{}", err.message());
+}
+
+#[test]
+#[should_panic(expected = "Command 'log' not defined")]
+fn should_panic_for_undefined_default_subcommand() {
+  CommandLineDef::new().add_subcommand(vec!["status"], "Show the status", CommandLineDef::new()).default_subcommand("log");
+}
+
+#[test]
+fn should_infer_subcommands_from_unambiguous_prefixes() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_subcommand(vec!["status"], "Show the status", CommandLineDef::new())
+    .add_subcommand(vec!["stash"], "Stash the changes", CommandLineDef::new())
+    .add_subcommand(vec!["remove", "rm"], "Remove files", CommandLineDef::new())
+    .add_subcommand(vec!["r"], "Run the script", CommandLineDef::new());
+  let subcommand = |cl_def: &CommandLineDef, name: &str| {
+    cl_def.try_parse(vec![String::from("git"), String::from(name)].into_iter()).map(|cl| cl.subcommand().unwrap().0.to_string())
+  };
+
+  assert_eq!(subcommand(&cl_def, "sta").unwrap_err().kind(), ErrorKind::SubcommandNotDefined);
+
+  cl_def.infer_subcommands(true);
+  assert_eq!(subcommand(&cl_def, "stat").unwrap(), "status");
+  assert_eq!(subcommand(&cl_def, "stas").unwrap(), "stash");
+  assert_eq!(subcommand(&cl_def, "re").unwrap(), "remove");
+  assert_eq!(subcommand(&cl_def, "r").unwrap(), "r");
+  assert_eq!(subcommand(&cl_def, "x").unwrap_err().kind(), ErrorKind::SubcommandNotDefined);
+  let err = subcommand(&cl_def, "sta").unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::AmbiguousSubcommand);
+  assert_eq!(err.message(), "Command 'sta' is ambiguous, it could be status, stash");
+}
+
+#[test]
+fn should_return_definition_errors_from_build() {
+  let err = CommandLineDef::builder().add_flag(vec!["-ab"], "Invalid").build().unwrap_err();
+  assert_eq!(err.kind(), DefErrorKind::InvalidName);
+  assert_eq!(err.to_string(), err.message());
+
+  let err = CommandLineDef::builder().add_flag(vec!["-v"], "Verbose").add_flag(vec!["-q", "-v"], "Quiet").build().unwrap_err();
+  assert_eq!(err.kind(), DefErrorKind::Redefined);
+
+  let err = CommandLineDef::builder().valid_values("--level", vec!["low"]).build().unwrap_err();
+  assert_eq!(err.kind(), DefErrorKind::NotDefined);
+  assert_eq!(err.message(), "Option '--level' not defined");
+
+  let err = CommandLineDef::builder().add_flag(vec!["-v"], "Verbose").arity("-v", 2).build().unwrap_err();
+  assert_eq!(err.kind(), DefErrorKind::InvalidOption);
+
+  let err = CommandLineDef::builder()
+    .add_subcommand(vec!["add"], "Add files", CommandLineDef::new())
+    .add_variadic_argument("files")
+    .build()
+    .unwrap_err();
+  assert_eq!(err.kind(), DefErrorKind::Conflict);
+
+  let err = CommandLineDef::builder().default_subcommand("status").build().unwrap_err();
+  assert_eq!(err.kind(), DefErrorKind::NotDefined);
+
+  let mut cl_def = CommandLineDef::builder();
+  let jobs = cl_def.add_option_t::<u32>(vec!["-j"], Some("many"), "The number of jobs");
+  let err = cl_def.build().unwrap_err();
+  assert_eq!(err.kind(), DefErrorKind::InvalidDefault);
+  assert_eq!(err.message(), "Cannot convert option '-j' from 'many': invalid digit found in string");
+  assert_eq!(jobs.alias(), "-j");
+
+  let mut set = CommandLineDef::builder();
+  set.add_option_t::<u8>(vec!["--level"], Some("300"), "The level");
+  let err = CommandLineDef::builder().add_subcommand(vec!["set"], "Set the level", set).build().unwrap_err();
+  assert_eq!(err.kind(), DefErrorKind::InvalidDefault);
+  assert_eq!(err.message(), "In command 'set': Cannot convert option '--level' from '300': number too large to fit in target type");
+
+  let err = CommandLineDef::builder().add_option_or_argument(vec![], "file", "The file").build().unwrap_err();
+  assert_eq!(err.kind(), DefErrorKind::InvalidName);
+  assert_eq!(err.message(), "An option must have at least one alias");
+
+  let mut add = CommandLineDef::builder();
+  add.add_flag(vec!["force"], "Force");
+  let mut remote = CommandLineDef::builder();
+  remote.add_subcommand(vec!["add"], "Add a remote", add);
+  let err = CommandLineDef::builder().add_subcommand(vec!["remote"], "Manage remotes", remote).build().unwrap_err();
+  assert_eq!(err.kind(), DefErrorKind::InvalidName);
+  assert!(err.message().starts_with("In command 'remote add': Invalid"));
+
+  let mut level = CommandLineDef::builder();
+  level.add_option(vec!["-l"], Some("level"), Some("max"), "The level").valid_values("-l", vec!["low", "high"]);
+  let err = CommandLineDef::builder().add_subcommand(vec!["set"], "Set the level", level).build().unwrap_err();
+  assert_eq!(err.kind(), DefErrorKind::InvalidDefault);
+  assert_eq!(err.message(), "In command 'set': Invalid default value 'max' for option '-l'. Valid values are: low, high");
+}
+
+#[test]
+fn should_keep_the_first_definition_error_and_skip_the_failed_change() {
+  let mut cl_def = CommandLineDef::builder();
+  cl_def
+    .add_flag(vec!["-v"], "Verbose")
+    .add_option(vec!["-q", "-v"], Some("level"), None, "Quiet")
+    .lazy_values("--missing");
+  let err = cl_def.build().unwrap_err();
+  assert_eq!(err.kind(), DefErrorKind::Redefined);
+  assert_eq!(err.message(), "Option '-v' cannot be redefined");
+
+  assert_eq!(cl_def.build().unwrap_err(), err);
+  assert_eq!(cl_def.usage("program"), "Usage: program [-hv]\n-h, --help : Display usage message\n        -v : Verbose");
+
+  let compiled = CommandLineDef::builder().add_flag(vec!["-v"], "Verbose").build().unwrap();
+  let cl = compiled.parse(vec![String::from("program"), String::from("-v")].into_iter());
+  assert!(cl.option::<bool>("-v"));
+
+  let mut cl_def = CommandLineDef::builder();
+  cl_def
+    .add_flag(vec!["-V"], "Verify")
+    .add_flag(vec!["-v"], "Verbose")
+    .add_counter(vec!["-v"], "Increase the verbosity")
+    .add_action(vec!["-v"], "Print the version", |_, _| panic!("the action was set on -v"))
+    .help_option(vec!["-v"])
+    .version_with(|_| String::from("1.0.0"));
+  let cl = cl_def.parse(vec![String::from("program"), String::from("-v")].into_iter());
+  assert_eq!(cl.option_str("-v"), "true");
+}
+
+#[test]
+fn should_parse_repeatedly_with_compiled_def() {
+  let mut build = CommandLineDef::new();
+  build.add_flag(vec!["-r", "--release"], "Build with optimizations");
+  let mut cl_def = CommandLineDef::builder();
+  cl_def
+    .add_counter(vec!["-v"], "Increase the verbosity")
+    .add_example("-v build", "Build verbosely")
+    .add_subcommand(vec!["build"], "Compile the package", build);
+  let usage = cl_def.usage("cargo");
+  let compiled = cl_def.build().unwrap();
+
+  assert_eq!(compiled.usage("cargo"), usage);
+  assert_eq!(compiled.usage("my tool"), usage.replace("cargo", "my tool"));
+  for verbosity in 0..100u32 {
+    let mut args = vec![String::from("cargo")];
+    args.extend((0..verbosity % 3).map(|_| String::from("-v")));
+    args.extend([String::from("build"), String::from("-r")]);
+    let cl = compiled.parse(args.into_iter());
+    assert_eq!(cl.option::<u32>("-v"), verbosity % 3);
+    assert!(cl.subcommand().unwrap().1.option::<bool>("--release"));
+  }
+
+  let err = compiled.try_parse(["cargo", "build", "-x"].iter().map(|a| a.to_string())).unwrap_err();
+  assert_eq!(err.usage(), "Usage: cargo build [-hr]\n   -h, --help : Display usage message\n-r, --release : Build with optimizations");
+}
+
+#[test]
+fn should_expand_unambiguous_long_option_prefixes() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_flag(vec!["-c", "--color", "--colour"], "Color the output")
+    .add_option(vec!["--column"], Some("n"), Some("1"), "The column")
+    .add_option(vec!["--col"], Some("n"), Some("0"), "The first column")
+    .add_argument("file");
+  let parse = |cl_def: &CommandLineDef, args: &[&str]| cl_def.try_parse(args.iter().map(|a| a.to_string()));
+
+  assert_eq!(parse(&cl_def, &["ls", "--colo", "f"]).unwrap_err().kind(), ErrorKind::OptionNotDefined);
+
+  cl_def.infer_long_options(true);
+  assert!(parse(&cl_def, &["ls", "--colo", "f"]).unwrap().option::<bool>("-c"));
+  assert_eq!(parse(&cl_def, &["ls", "--colu=3", "f"]).unwrap().option::<u32>("--column"), 3);
+  assert_eq!(parse(&cl_def, &["ls", "--col", "2", "f"]).unwrap().option::<u32>("--col"), 2);
+  assert!(parse(&cl_def, &["ls", "--he"]).is_err_and(|e| e.kind() == ErrorKind::Help));
+  assert_eq!(parse(&cl_def, &["ls", "--x", "f"]).unwrap_err().kind(), ErrorKind::OptionNotDefined);
+  assert_eq!(parse(&cl_def, &["ls", "--", "--colo"]).unwrap().argument::<String>(0), "--colo");
+
+  let err = parse(&cl_def, &["ls", "--co", "f"]).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::AmbiguousOption);
+  assert_eq!(err.message(), "Option '--co' is ambiguous, it could be --col, --color, --column");
+}
+
+#[test]
+fn should_borrow_option_and_argument_text() {
+  let args = ["program", "--level", "0x10", "--name=", "file.txt"].iter().map(|a| a.to_string());
+  let cl = CommandLineDef::new()
+    .add_option(vec!["-l", "--level"], Some("level"), None, "The level")
+    .add_option(vec!["--name"], Some("name"), Some("none"), "The name")
+    .allow_empty_values("--name")
+    .add_argument("file")
+    .parse(args);
+
+  assert_eq!(cl.option_str("-l"), "0x10");
+  assert_eq!(cl.option::<u8>("-l"), 16);
+  assert_eq!(cl.option_str("--name"), "");
+  assert_eq!(cl.argument_str(0), "file.txt");
+}
+
+#[test]
+#[should_panic(expected = "Argument index '1' is out of bounds")]
+fn should_panic_borrowing_missing_argument() {
+  let args = ["program", "file.txt"].iter().map(|a| a.to_string());
+  CommandLineDef::new().add_argument("file").parse(args).argument_str(1);
+}
+
+#[test]
+fn should_probe_options_and_arguments_without_panicking() {
+  let args = ["cp", "-r", "a.txt", "b.txt", "dest"].iter().map(|a| a.to_string());
+  let cl = CommandLineDef::new()
+    .add_flag(vec!["-r", "--recursive"], "Copy directories")
+    .add_argument("source")
+    .add_variadic_argument("sources")
+    .add_argument("destination")
+    .parse(args);
+
+  assert_eq!(cl.get_raw("--recursive"), Some("true"));
+  assert_eq!(cl.get_raw("source"), Some("a.txt"));
+  assert_eq!(cl.get_raw("sources"), Some("b.txt"));
+  assert_eq!(cl.get_raw("destination"), Some("dest"));
+  assert_eq!(cl.get_raw("--force"), None);
+  assert!(cl.contains_argument("sources"));
+  assert!(!cl.contains_argument("--recursive"));
+  assert!(!cl.contains_argument("target"));
+}
+
+#[test]
+fn should_list_the_names_present() {
+  let args = ["tar", "-xv", "-f", "a.tar", "--db.host=h", "x", "y"].iter().map(|a| a.to_string());
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_flag(vec!["-x", "--extract"], "Extract files")
+    .add_flag(vec!["-v"], "Verbose output")
+    .add_option(vec!["-f", "--file"], Some("archive"), None, "The archive")
+    .add_option(vec!["--db.host"], Some("host"), Some("localhost"), "The database host")
+    .add_variadic_argument("members")
+    .finalize(|cl| cl.set_option("--jobs", 2));
+  let cl = cl_def.parse(args);
+
+  assert_eq!(cl.option_names(), vec!["--help", "--extract", "-v", "--file", "--db.host", "--jobs"]);
+  assert_eq!(cl.argument_names(), vec!["members"]);
+  for name in cl.option_names() {
+    assert!(cl.get_raw(name).is_some());
+  }
+  assert_eq!(cl.section("db").option_names(), vec!["--host"]);
+}
+
+#[test]
+fn should_display_a_canonical_commandline() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_flag(vec!["-x", "--extract"], "Extract files")
+    .add_flag(vec!["-z"], "Filter through gzip")
+    .add_option(vec!["-f", "--file"], Some("archive"), None, "The archive")
+    .add_option(vec!["-C", "--directory"], Some("dir"), Some("."), "The directory")
+    .add_argument("member");
+  let parse = |args: &[&str]| cl_def.parse(args.iter().map(|a| a.to_string())).to_string();
+
+  assert_eq!(parse(&["tar", "-xf", "my archive.tar", "a.txt"]), "tar --extract --file 'my archive.tar' a.txt");
+  assert_eq!(parse(&["tar", "a.txt", "-zC", "/tmp", "--file=x.tar"]), "tar -z --file x.tar --directory /tmp a.txt");
+  assert_eq!(parse(&["/usr/bin/tar", "-f", "it's", "--", "-a"]), "/usr/bin/tar --file 'it'\\''s' -- -a");
+}
+
+#[test]
+fn should_display_toggles_with_the_prefix_given() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_toggle(vec!["-x"], "Print the commands")
+    .add_toggle(vec!["-o"], "Enable the optimizations")
+    .plus_enables("-o");
+  let parse = |args: &[&str]| cl_def.parse(args.iter().map(|a| a.to_string()));
+
+  for args in [["p", "+x", "+o"], ["p", "-x", "-o"], ["p", "+x", "-o"], ["p", "-x", "+o"]] {
+    let cl = parse(&args);
+    let displayed = cl.to_string();
+    assert_eq!(displayed, args.join(" "));
+    let round_trip = parse(&displayed.split(' ').collect::<Vec<_>>());
+    assert_eq!((round_trip.option::<bool>("-x"), round_trip.option::<bool>("-o")), (cl.option::<bool>("-x"), cl.option::<bool>("-o")));
+  }
+  assert_eq!(parse(&["p", "+x"]).option::<bool>("-x"), false);
+  assert_eq!(parse(&["p", "+o"]).option::<bool>("-o"), true);
+}
+
+#[test]
+fn should_display_each_value_of_an_option_with_arity_as_an_arg() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_option(vec!["--point"], Some("x y"), Some("0,0"), "The point")
+    .arity("--point", 2)
+    .add_option(vec!["--label"], Some("label"), None, "The label");
+  let parse = |args: &[&str]| cl_def.parse(args.iter().map(|a| a.to_string())).to_string();
+
+  assert_eq!(parse(&["p", "--point", "3", "4", "--label", "a,b"]), "p --point 3 4 --label 'a,b'");
+  assert_eq!(parse(&["p", "--point", "a b", "c,d", "--label", "x"]), "p --point 'a b' 'c,d' --label x");
+
+  let cl_def = CommandLineDef::from_optstring("z::");
+  let parse = |args: &[&str]| cl_def.parse(args.iter().map(|a| a.to_string())).to_string();
+  assert_eq!(parse(&["p", "-zfast"]), "p -zfast");
+  assert_eq!(parse(&["p", "-z"]), "p -z");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_not_display_values_from_the_environment() {
+  std::env::set_var("CL_PARSE_TEST_DISPLAY_TOKEN", "s3cret");
+  std::env::set_var("CL_PARSE_TEST_DISPLAY_LEVEL", "3");
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_option(vec!["--token"], Some("token"), None, "The API token")
+    .env_only("--token", "CL_PARSE_TEST_DISPLAY_TOKEN")
+    .add_option(vec!["--level"], Some("level"), Some("1"), "The level")
+    .env("--level", "CL_PARSE_TEST_DISPLAY_LEVEL");
+  let parse = |args: &[&str]| cl_def.parse(args.iter().map(|a| a.to_string()));
+
+  let cl = parse(&["p"]);
+  assert_eq!(cl.option_str("--token"), "s3cret");
+  assert_eq!(cl.to_string(), "p");
+  assert_eq!(parse(&["p", "--level", "2"]).to_string(), "p --level 2");
+
+  let mut cl = parse(&["p", "--level", "2"]);
+  cl.set_option("--level", 5);
+  assert_eq!(cl.to_string(), "p");
+}
+
+#[test]
+fn should_report_the_alias_given() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_counter(vec!["-v", "--verbose"], "Verbose output")
+    .add_option(vec!["-o", "--output"], Some("file"), Some("-"), "The output file")
+    .add_toggle(vec!["-x"], "Trace commands")
+    .add_flag(vec!["-n", "--dry-run"], "Print without running")
+    .infer_long_options(true);
+  let cl = cl_def.parse(["sh", "-vo", "out.txt", "+x", "--verbose", "--dry"].iter().map(|a| a.to_string()));
+
+  assert_eq!(cl.matched_alias("-v"), Some("--verbose"));
+  assert_eq!(cl.matched_alias("--output"), Some("-o"));
+  assert_eq!(cl.matched_alias("-x"), Some("+x"));
+  assert_eq!(cl.matched_alias("-n"), Some("--dry-run"));
+  assert_eq!(cl.matched_alias("--help"), None);
+}
+
+#[test]
+fn should_merge_layered_commandlines() {
+  let mut build = CommandLineDef::new();
+  build
+    .add_flag(vec!["-r", "--release"], "Build with optimizations")
+    .add_option(vec!["-t", "--target"], Some("triple"), Some("host"), "The target");
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .add_counter(vec!["-v", "--verbose"], "Verbose output")
+    .add_option(vec!["--color"], Some("when"), Some("auto"), "When to color the output")
+    .add_subcommand(vec!["build"], "Compile the package", build);
+  let parse = |args: &[&str]| cl_def.parse(args.iter().map(|a| a.to_string()));
+
+  let system = parse(&["cargo", "--color", "never", "build", "-t", "wasm32"]);
+  let user = parse(&["cargo", "-vv", "build", "--release"]);
+  let invocation = parse(&["/usr/bin/cargo", "--color=always", "build"]);
+  let cl = system.merged_with(user).merged_with(invocation);
+
+  assert_eq!(cl.program_path(), "/usr/bin/cargo");
+  assert_eq!(cl.option_str("--color"), "always");
+  assert_eq!(cl.matched_alias("--color"), Some("--color"));
+  assert_eq!(cl.verbosity(), 2);
+  let (name, build) = cl.subcommand().unwrap();
+  assert_eq!(name, "build");
+  assert!(build.option::<bool>("-r"));
+  assert_eq!(build.option_str("--target"), "wasm32");
+  assert!(!build.is_default("-t"));
+  assert!(build.is_default("--help"));
+}
+
+#[test]
+fn should_pair_enable_and_disable_flags() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def.add_enable_disable("cache", true, "Cache the results");
+  let parse = |args: &[&str]| cl_def.try_parse(args.iter().map(|a| a.to_string()));
+
+  let cl = parse(&["tool"]).unwrap();
+  assert!(cl.option::<bool>("--enable-cache"));
+  assert!(cl.is_default("--disable-cache"));
+  assert_eq!(cl.to_string(), "tool");
+
+  let cl = parse(&["tool", "--enable-cache", "--disable-cache"]).unwrap();
+  assert!(!cl.option::<bool>("--enable-cache"));
+  assert_eq!(cl.matched_alias("--enable-cache"), Some("--disable-cache"));
+  assert_eq!(cl.to_string(), "tool --disable-cache");
+
+  let cl = parse(&["tool", "--disable-cache", "--enable-cache"]).unwrap();
+  assert!(cl.option::<bool>("--disable-cache"));
+  assert_eq!(cl.indices_of("--enable-cache"), [1, 2]);
+
+  assert_eq!(parse(&["tool", "--enable-cache=no"]).unwrap_err().kind(), ErrorKind::InvalidValue);
+  assert_eq!(cl_def.usage("tool"), "Usage: tool [-h] --enable-cache\n                     -h, --help : Display usage message\n--enable-cache, --disable-cache : Cache the results");
+}
+
+#[test]
+fn should_combine_option_packs_in_either_order() {
+  let args = || ["tool", "-vq", "--json", "--retries", "0"].iter().map(|a| a.to_string());
+  let mut logging_first = CommandLineDef::new();
+  logging_first.add_logging_options().add_output_options().add_network_options(30, 3);
+  let mut output_first = CommandLineDef::new();
+  output_first.add_output_options().add_logging_options().add_network_options(30, 3);
+
+  for cl_def in [&logging_first, &output_first] {
+    let cl = cl_def.parse(args());
+    assert_eq!(cl.verbosity(), 0);
+    assert!(cl.option::<bool>("--json"));
+    assert_eq!(cl.option::<u32>("--retries"), 0);
+    assert_eq!(cl.option::<u64>("--timeout"), 30);
+  }
+  let err = logging_first.try_parse(["tool", "--log-level", "loud"].iter().map(|a| a.to_string())).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::InvalidValue);
+}
+
+#[test]
+fn should_choose_the_output_format() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def.add_output_format();
+  let parse = |args: &[&str]| cl_def.parse(args.iter().map(|a| a.to_string()));
+
+  assert_eq!(parse(&["tool"]).output_format(), OutputFormat::Table);
+  assert_eq!(parse(&["tool", "--output=yaml"]).output_format(), OutputFormat::Yaml);
+  assert_eq!(OutputFormat::Json.to_string(), "json");
+  assert_eq!("table".parse(), Ok(OutputFormat::Table));
+  assert_eq!(CommandLineDef::new().parse(["tool"].iter().map(|a| a.to_string())).output_format(), OutputFormat::Table);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_write_errors_as_json() {
+  let err = SharedBuffer::default();
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .on_error(ErrorBehavior::Exit)
+    .exit_handler(|_| {})
+    .with_output(SharedBuffer::default(), err.clone())
+    .json_errors(true)
+    .infer_subcommands(true)
+    .add_option(vec!["-l", "--level"], Some("level"), Some("low"), "The level")
+    .valid_values("-l", vec!["low", "high"])
+    .add_subcommand(vec!["build"], "Compile", CommandLineDef::new())
+    .add_subcommand(vec!["bench"], "Benchmark", CommandLineDef::new());
+
+  for args in [&["cargo", "--level", "mid"][..], &["cargo", "b"][..]] {
+    let args = args.iter().map(|a| a.to_string());
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cl_def.parse(args))).is_err());
+  }
+  assert_eq!(err.text(), concat!(
+    r#"{"kind": "InvalidValue", "option": "--level", "message": "Invalid value 'mid' for option '--level'. Valid values are: low, high", "suggestions": []}"#, "\n",
+    r#"{"kind": "AmbiguousSubcommand", "option": "b", "message": "Command 'b' is ambiguous, it could be build, bench", "suggestions": ["build", "bench"]}"#, "\n"));
+
+  let help = cl_def.try_parse(["cargo", "-h"].iter().map(|a| a.to_string())).unwrap_err();
+  assert_eq!(help.to_string(), help.usage());
+}
+
+#[cfg(feature = "i18n")]
+#[test]
+fn should_show_registered_messages_for_description_keys() {
+  cl_parse::testing::fixed_locale();
+  cl_parse::register_messages("en", &[
+    ("test.level", "The level of detail"),
+    ("test.build", "Compile the package"),
+    ("test.example", "Build quietly"),
+  ]);
+  let usage = CommandLineDef::new()
+    .disable_help()
+    .add_option(vec!["-l"], Some("level"), Some("low"), "test.level")
+    .add_flag(vec!["-q"], "test.unregistered")
+    .add_subcommand(vec!["build"], "test.build", CommandLineDef::new())
+    .add_example("build -q", "test.example")
+    .usage("cargo");
+  assert!(usage.contains("-l <level> : The level of detail [default: low]"), "{usage}");
+  assert!(usage.contains("test.unregistered"), "{usage}");
+  assert!(usage.contains("Compile the package"), "{usage}");
+  assert!(usage.contains("Build quietly"), "{usage}");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_show_plain_help_when_env_var_is_set() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .disable_help()
+    .help_layout(HelpLayout::new().indent(2))
+    .plain_help_env_var("TEST_PLAIN_HELP")
+    .add_option(vec!["-l", "--level"], Some("level"), Some("low"), "The level")
+    .add_subcommand(vec!["build"], "Compile", CommandLineDef::new());
+  let compiled = CommandLineDef::builder().disable_help().plain_help_env_var("TEST_PLAIN_HELP").add_flag(vec!["-v"], "Verbose").build().unwrap();
+
+  std::env::set_var("TEST_PLAIN_HELP", "0");
+  assert!(cl_def.usage("cargo").contains("\n  -l, --level <level> : The level"));
+  assert!(compiled.usage("cargo").ends_with("\n-v : Verbose"));
+
+  std::env::set_var("TEST_PLAIN_HELP", "1");
+  assert_eq!(cl_def.usage("cargo"), "Usage: cargo [-l <level>] <command>\n-l, --level <level>: The level [default: low]\nCommands:\nbuild: Compile");
+  assert!(compiled.usage("cargo").ends_with("\n-v: Verbose"));
+  std::env::remove_var("TEST_PLAIN_HELP");
+}
+
+#[test]
+fn should_align_options_with_non_ascii_value_names() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .disable_help()
+    .add_flag(vec!["-v", "--verbose"], "Verbose")
+    .add_option(vec!["-g"], Some("größe"), Some("1"), "Die Größe");
+  assert_eq!(cl_def.usage("tool"), "Usage: tool [-v] [-g <größe>]
+-v, --verbose : Verbose
+   -g <größe> : Die Größe [default: 1]");
+
+  cl_def.help_layout(HelpLayout::new().max_option_width(10));
+  assert!(cl_def.usage("tool").ends_with("\n-g <größe> : Die Größe [default: 1]"));
+}
+
+#[test]
+fn should_lay_out_help_right_to_left() {
+  let usage = CommandLineDef::new()
+    .disable_help()
+    .help_layout(HelpLayout::new().right_to_left(true).max_option_width(4))
+    .add_flag(vec!["-v"], "Verbose")
+    .add_option(vec!["--level"], Some("level"), Some("low"), "The level")
+    .add_subcommand(vec!["run"], "Run", CommandLineDef::new())
+    .usage("tool");
+  assert_eq!(usage, concat!("Usage: tool [-v] [--level <level>] <command>\n",
+    "\u{200F}  \u{2066}-v\u{2069} : Verbose\n",
+    "\u{200F}\u{2066}--level <level>\u{2069}\n",
+    "\u{200F}     : The level [default: low]\n",
+    "Commands:\n",
+    "\u{200F} \u{2066}run\u{2069} : Run"));
+
+  let plain = CommandLineDef::new()
+    .disable_help()
+    .help_layout(HelpLayout::new().right_to_left(true).plain(true))
+    .add_flag(vec!["-v"], "Verbose")
+    .usage("tool");
+  assert_eq!(plain, "Usage: tool [-v]\n\u{200F}\u{2066}-v\u{2069}: Verbose");
+}
+
+#[test]
+fn should_link_options_to_their_docs() {
+  let mut cl_def = CommandLineDef::new();
+  cl_def
+    .disable_help()
+    .add_flag(vec!["-v"], "Verbose")
+    .add_option(vec!["--level"], Some("level"), Some("low"), "The level")
+    .doc_url("-v", "https://example.com/v");
+
+  let linked = cl_def.help_layout(HelpLayout::new().hyperlinks(true)).usage("tool");
+  assert_eq!(linked, concat!("Usage: tool [-v] [--level <level>]\n",
+    "             \x1b]8;;https://example.com/v\x1b\\-v\x1b]8;;\x1b\\ : Verbose [\x1b]8;;https://example.com/v\x1b\\see docs\x1b]8;;\x1b\\]\n",
+    "--level <level> : The level [default: low]"));
+
+  let plain = cl_def.help_layout(HelpLayout::new().hyperlinks(false)).usage("tool");
+  assert_eq!(plain, concat!("Usage: tool [-v] [--level <level>]\n",
+    "             -v : Verbose [docs: https://example.com/v]\n",
+    "--level <level> : The level [default: low]"));
+}